@@ -53,15 +53,26 @@ impl vte::Perform for Performer {
 
 // Based on https://github.com/alacritty/alacritty/blob/57c4ac9145a20fb1ae9a21102503458d3da06c7b/alacritty_terminal/src/ansi.rs#L1168
 fn ansi_term_style_from_sgr_parameters(parameters: &[i64]) -> ansi_term::Style {
-    let mut i = 0;
     let mut style = ansi_term::Style::new();
+    update_ansi_term_style_from_sgr_parameters(&mut style, parameters);
+    style
+}
+
+/// Apply the SGR parameters of an escape sequence to `style`. Parameters that reset the style, or
+/// cancel an attribute, are honored, so that this can be used to follow the style through a
+/// stream of escape sequences.
+pub fn update_ansi_term_style_from_sgr_parameters(
+    style: &mut ansi_term::Style,
+    parameters: &[i64],
+) {
+    let mut i = 0;
     loop {
         if i >= parameters.len() {
             break;
         }
 
         match parameters[i] {
-            // 0 => Some(Attr::Reset),
+            0 => *style = ansi_term::Style::new(),
             1 => style.is_bold = true,
             2 => style.is_dimmed = true,
             3 => style.is_italic = true,
@@ -71,14 +82,17 @@ fn ansi_term_style_from_sgr_parameters(parameters: &[i64]) -> ansi_term::Style {
             7 => style.is_reverse = true,
             8 => style.is_hidden = true,
             9 => style.is_strikethrough = true,
-            // 21 => Some(Attr::CancelBold),
-            // 22 => Some(Attr::CancelBoldDim),
-            // 23 => Some(Attr::CancelItalic),
-            // 24 => Some(Attr::CancelUnderline),
-            // 25 => Some(Attr::CancelBlink),
-            // 27 => Some(Attr::CancelReverse),
-            // 28 => Some(Attr::CancelHidden),
-            // 29 => Some(Attr::CancelStrike),
+            21 => style.is_bold = false,
+            22 => {
+                style.is_bold = false;
+                style.is_dimmed = false;
+            }
+            23 => style.is_italic = false,
+            24 => style.is_underline = false,
+            25 => style.is_blink = false,
+            27 => style.is_reverse = false,
+            28 => style.is_hidden = false,
+            29 => style.is_strikethrough = false,
             30 => style.foreground = Some(ansi_term::Color::Black),
             31 => style.foreground = Some(ansi_term::Color::Red),
             32 => style.foreground = Some(ansi_term::Color::Green),
@@ -94,7 +108,7 @@ fn ansi_term_style_from_sgr_parameters(parameters: &[i64]) -> ansi_term::Style {
                     style.foreground = Some(color);
                 }
             }
-            // 39 => Some(Attr::Foreground(Color::Named(NamedColor::Foreground))),
+            39 => style.foreground = None,
             40 => style.background = Some(ansi_term::Color::Black),
             41 => style.background = Some(ansi_term::Color::Red),
             42 => style.background = Some(ansi_term::Color::Green),
@@ -110,7 +124,7 @@ fn ansi_term_style_from_sgr_parameters(parameters: &[i64]) -> ansi_term::Style {
                     style.background = Some(color);
                 }
             }
-            // 49 => Some(Attr::Background(Color::Named(NamedColor::Background))),
+            49 => style.background = None,
             // "bright" colors. ansi_term doesn't offer a way to emit them as, e.g., 90m; instead
             // that would be 38;5;8.
             90 => style.foreground = Some(ansi_term::Color::Fixed(8)),
@@ -133,7 +147,6 @@ fn ansi_term_style_from_sgr_parameters(parameters: &[i64]) -> ansi_term::Style {
         };
         i += 1;
     }
-    style
 }
 
 // Based on https://github.com/alacritty/alacritty/blob/57c4ac9145a20fb1ae9a21102503458d3da06c7b/alacritty_terminal/src/ansi.rs#L1258
@@ -0,0 +1,299 @@
+/// Interpretation of delta's terminal output, for the output formats (HTML and SVG) that are
+/// rendered from it.
+///
+/// `render` follows the style (SGR), "clear to end of line" (CSI K) and hyperlink (OSC 8) escape
+/// sequences in the text, and passes the text, in runs of a single style, to a `Renderer`.
+use crate::ansi::parse::update_ansi_term_style_from_sgr_parameters;
+use crate::bat::terminal::to_ansi_color;
+use crate::config::Config;
+
+/// The style and hyperlink in effect at a point in the text.
+pub(crate) struct State {
+    pub style: ansi_term::Style,
+    /// ansi_term has no overline attribute, so it is tracked here.
+    pub is_overline: bool,
+    pub link: Option<String>,
+    pub page_foreground: Option<ansi_term::Color>,
+    pub page_background: Option<ansi_term::Color>,
+}
+
+impl State {
+    fn new(
+        page_foreground: Option<ansi_term::Color>,
+        page_background: Option<ansi_term::Color>,
+    ) -> Self {
+        Self {
+            style: ansi_term::Style::new(),
+            is_overline: false,
+            link: None,
+            page_foreground,
+            page_background,
+        }
+    }
+
+    fn update_from_sgr_parameters(&mut self, params: &[i64]) {
+        if params.is_empty() {
+            self.style = ansi_term::Style::new();
+            self.is_overline = false;
+            return;
+        }
+        for param in params {
+            match param {
+                0 | 55 => self.is_overline = false,
+                53 => self.is_overline = true,
+                _ => {}
+            }
+        }
+        update_ansi_term_style_from_sgr_parameters(&mut self.style, params);
+    }
+
+    /// The (foreground, background) colors of the current style, taking reverse video into
+    /// account.
+    pub fn effective_colors(&self) -> (Option<ansi_term::Color>, Option<ansi_term::Color>) {
+        if self.style.is_reverse {
+            (
+                self.style.background.or(self.page_background),
+                self.style.foreground.or(self.page_foreground),
+            )
+        } else {
+            (self.style.foreground, self.style.background)
+        }
+    }
+
+    /// The CSS (and SVG) text-decoration values of the current style.
+    pub fn text_decorations(&self) -> Vec<&'static str> {
+        [
+            (self.style.is_underline, "underline"),
+            (self.is_overline, "overline"),
+            (self.style.is_strikethrough, "line-through"),
+        ]
+        .iter()
+        .filter(|(is_set, _)| *is_set)
+        .map(|(_, decoration)| *decoration)
+        .collect()
+    }
+}
+
+pub(crate) trait Renderer {
+    /// Add a character to the pending text. All pending text has the current style.
+    fn print(&mut self, c: char);
+
+    /// Emit the pending text, in style `state`. This is called before the style changes.
+    fn flush_text(&mut self, state: &State);
+
+    /// End the current line. The pending text has been flushed.
+    fn end_line(&mut self, state: &State);
+
+    /// Extend the current background color to the end of the line (ANSI "clear to end of line").
+    /// The pending text has been flushed.
+    fn fill_line(&mut self, state: &State);
+
+    /// The hyperlink has changed from `previous_link` to `state.link`. The pending text has been
+    /// flushed.
+    fn set_link(&mut self, _previous_link: Option<&str>, _state: &State) {}
+}
+
+/// Pass the text of `ansi` to `renderer`, and return the state at the end of the text. Pending
+/// text has been flushed, but the last line has not been ended if it has no trailing newline.
+pub(crate) fn render<R: Renderer>(ansi: &str, renderer: &mut R, config: &Config) -> State {
+    let (page_foreground, page_background) = get_page_colors(config);
+    let mut performer = Performer {
+        renderer,
+        state: State::new(page_foreground, page_background),
+    };
+    let mut parser = vte::Parser::new();
+    for byte in ansi.bytes() {
+        parser.advance(&mut performer, byte);
+    }
+    performer.renderer.flush_text(&performer.state);
+    performer.state
+}
+
+struct Performer<'a, R: Renderer> {
+    renderer: &'a mut R,
+    state: State,
+}
+
+impl<'a, R: Renderer> vte::Perform for Performer<'a, R> {
+    fn csi_dispatch(&mut self, params: &[i64], intermediates: &[u8], ignore: bool, c: char) {
+        if ignore || !intermediates.is_empty() {
+            return;
+        }
+        match c {
+            'm' => {
+                self.renderer.flush_text(&self.state);
+                self.state.update_from_sgr_parameters(params);
+            }
+            'K' if params.is_empty() || params == [0] => {
+                self.renderer.flush_text(&self.state);
+                self.renderer.fill_line(&self.state);
+            }
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        self.renderer.print(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                self.renderer.flush_text(&self.state);
+                self.renderer.end_line(&self.state);
+            }
+            b'\t' => self.renderer.print('\t'),
+            _ => {}
+        }
+    }
+
+    fn hook(&mut self, _params: &[i64], _intermediates: &[u8], _ignore: bool, _c: char) {}
+
+    fn put(&mut self, _byte: u8) {}
+
+    fn unhook(&mut self) {}
+
+    /// OSC 8 hyperlinks: "\x1b]8;;URL\x1b\\" starts a link and "\x1b]8;;\x1b\\" ends it.
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        if params.len() >= 3 && params[0] == b"8" {
+            let url = params[2..]
+                .iter()
+                .map(|param| String::from_utf8_lossy(param))
+                .collect::<Vec<_>>()
+                .join(";");
+            self.renderer.flush_text(&self.state);
+            let previous_link = std::mem::replace(
+                &mut self.state.link,
+                if url.is_empty() { None } else { Some(url) },
+            );
+            self.renderer
+                .set_link(previous_link.as_deref(), &self.state);
+        }
+    }
+
+    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+}
+
+/// The page's foreground and background colors: those of the syntax theme, if there is one.
+pub(crate) fn get_page_colors(
+    config: &Config,
+) -> (Option<ansi_term::Color>, Option<ansi_term::Color>) {
+    match &config.syntax_theme {
+        Some(theme) => (
+            theme
+                .settings
+                .foreground
+                .map(|color| to_ansi_color(color, true)),
+            theme
+                .settings
+                .background
+                .map(|color| to_ansi_color(color, true)),
+        ),
+        None => (None, None),
+    }
+}
+
+pub(crate) fn css_color(color: ansi_term::Color) -> String {
+    let (r, g, b) = match color {
+        ansi_term::Color::RGB(r, g, b) => (r, g, b),
+        ansi_term::Color::Fixed(n) => ansi_colours::rgb_from_ansi256(n),
+        ansi_term::Color::Black => ansi_colours::rgb_from_ansi256(0),
+        ansi_term::Color::Red => ansi_colours::rgb_from_ansi256(1),
+        ansi_term::Color::Green => ansi_colours::rgb_from_ansi256(2),
+        ansi_term::Color::Yellow => ansi_colours::rgb_from_ansi256(3),
+        ansi_term::Color::Blue => ansi_colours::rgb_from_ansi256(4),
+        ansi_term::Color::Purple => ansi_colours::rgb_from_ansi256(5),
+        ansi_term::Color::Cyan => ansi_colours::rgb_from_ansi256(6),
+        ansi_term::Color::White => ansi_colours::rgb_from_ansi256(7),
+    };
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Escape text for use in HTML or SVG content or attribute values.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::integration_test_utils::make_config_from_args;
+
+    pub const DIFF: &str = "\
+diff --git a/file.txt b/file.txt
+index 1111111..2222222 100644
+--- a/file.txt
++++ b/file.txt
+@@ -1 +1 @@
+-a < b
++a > b
+";
+
+    /// Records each run of text with its style, and the other calls.
+    #[derive(Default)]
+    struct TestRenderer {
+        text: String,
+        calls: Vec<String>,
+    }
+
+    impl Renderer for TestRenderer {
+        fn print(&mut self, c: char) {
+            self.text.push(c);
+        }
+
+        fn flush_text(&mut self, state: &State) {
+            if !self.text.is_empty() {
+                self.calls.push(format!(
+                    "{:?} {:?} {:?}",
+                    self.text,
+                    state.effective_colors(),
+                    state.text_decorations()
+                ));
+                self.text.clear();
+            }
+        }
+
+        fn end_line(&mut self, _state: &State) {
+            self.calls.push("end_line".to_string());
+        }
+
+        fn fill_line(&mut self, state: &State) {
+            self.calls
+                .push(format!("fill_line {:?}", state.effective_colors().1));
+        }
+
+        fn set_link(&mut self, previous_link: Option<&str>, state: &State) {
+            self.calls
+                .push(format!("set_link {:?} {:?}", previous_link, state.link));
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let config = make_config_from_args(&["--syntax-theme", "none"]);
+        let mut renderer = TestRenderer::default();
+        let state = render(
+            "a\x1b[7;31mb\x1b[27;4;53mc\x1b[55m\x1b]8;;x\x1b\\d\x1b[0K\nde\x1b[m",
+            &mut renderer,
+            &config,
+        );
+        assert_eq!(
+            renderer.calls,
+            vec![
+                r#""a" (None, None) []"#,
+                r#""b" (None, Some(Red)) []"#,
+                r#""c" (Some(Red), None) ["underline", "overline"]"#,
+                r#"set_link None Some("x")"#,
+                r#""d" (Some(Red), None) ["underline"]"#,
+                "fill_line None",
+                "end_line",
+                r#""de" (Some(Red), None) ["underline"]"#,
+            ]
+        );
+        assert_eq!(state.link.as_deref(), Some("x"));
+        assert_eq!(state.style, ansi_term::Style::new());
+    }
+}
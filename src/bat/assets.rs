@@ -1,15 +1,16 @@
 // Based on code from https://github.com/sharkdp/bat a1b9334a44a2c652f52dddaa83dbacba57372468
 // See src/bat/LICENSE
 
-use std::fs::File;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::io::{self, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use ansi_term::Colour::Green;
 use ansi_term::Style;
-use syntect::dumps::{from_binary, from_reader};
+use syntect::dumps::{dump_to_file, from_binary, from_reader};
 use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
 
 use crate::bat::dirs::PROJECT_DIRS;
 use crate::errors::*;
@@ -21,7 +22,8 @@ pub struct HighlightingAssets {
 
 impl HighlightingAssets {
     pub fn new() -> Self {
-        Self::from_cache().unwrap_or_else(|_| Self::from_binary())
+        let cache_dir = select_cache_dir(PROJECT_DIRS.cache_dir(), PROJECT_DIRS.bat_cache_dir());
+        Self::from_cache(cache_dir).unwrap_or_else(|_| Self::from_binary())
     }
 
     fn get_integrated_syntaxset() -> SyntaxSet {
@@ -32,12 +34,13 @@ impl HighlightingAssets {
         from_binary(include_bytes!("../../etc/assets/themes.bin"))
     }
 
-    fn from_cache() -> Result<Self> {
-        let theme_set_path = theme_set_path();
-        let syntax_set_file = File::open(&syntax_set_path()).chain_err(|| {
+    fn from_cache(cache_dir: &Path) -> Result<Self> {
+        let theme_set_path = cache_dir.join("themes.bin");
+        let syntax_set_path = cache_dir.join("syntaxes.bin");
+        let syntax_set_file = File::open(&syntax_set_path).chain_err(|| {
             format!(
                 "Could not load cached syntax set '{}'",
-                syntax_set_path().to_string_lossy()
+                syntax_set_path.to_string_lossy()
             )
         })?;
         let syntax_set: SyntaxSet = from_reader(BufReader::new(syntax_set_file))
@@ -58,6 +61,92 @@ impl HighlightingAssets {
         })
     }
 
+    /// Build assets consisting of the integrated themes and syntaxes, together with the themes
+    /// (.tmTheme files) in `source_dir/themes` and the syntaxes (.sublime-syntax files) in
+    /// `source_dir/syntaxes`. A theme or syntax with the same name as an integrated one replaces
+    /// it.
+    pub fn from_files(source_dir: &Path) -> Result<Self> {
+        let mut theme_set = Self::get_integrated_themeset();
+        let theme_dir = source_dir.join("themes");
+        if theme_dir.is_dir() {
+            for path in find_files(&theme_dir, "tmTheme")? {
+                let theme = ThemeSet::get_theme(&path).chain_err(|| {
+                    format!("Could not load theme file '{}'", path.to_string_lossy())
+                })?;
+                let name = path.file_stem().unwrap().to_string_lossy().to_string();
+                theme_set.themes.insert(name, theme);
+            }
+        } else {
+            println!(
+                "No themes were found in '{}', using the default set",
+                theme_dir.to_string_lossy()
+            );
+        }
+
+        let mut syntax_set_builder = Self::get_integrated_syntaxset().into_builder();
+        let syntax_dir = source_dir.join("syntaxes");
+        if syntax_dir.is_dir() {
+            for path in find_files(&syntax_dir, "sublime-syntax")? {
+                let contents = fs::read_to_string(&path)?;
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string());
+                let syntax = SyntaxDefinition::load_from_str(&contents, true, name.as_deref())
+                    .chain_err(|| {
+                        format!("Could not load syntax file '{}'", path.to_string_lossy())
+                    })?;
+                syntax_set_builder.add(syntax);
+            }
+        } else {
+            println!(
+                "No syntaxes were found in '{}', using the default set",
+                syntax_dir.to_string_lossy()
+            );
+        }
+
+        Ok(HighlightingAssets {
+            syntax_set: syntax_set_builder.build(),
+            theme_set,
+        })
+    }
+
+    pub fn save_to_cache(&self, target_dir: &Path) -> Result<()> {
+        fs::create_dir_all(target_dir).chain_err(|| {
+            format!(
+                "Could not create cache directory '{}'",
+                target_dir.to_string_lossy()
+            )
+        })?;
+        let theme_set_path = target_dir.join("themes.bin");
+        let syntax_set_path = target_dir.join("syntaxes.bin");
+
+        print!(
+            "Writing theme set to {} ... ",
+            theme_set_path.to_string_lossy()
+        );
+        dump_to_file(&self.theme_set, &theme_set_path).chain_err(|| {
+            format!(
+                "Could not save theme set to {}",
+                theme_set_path.to_string_lossy()
+            )
+        })?;
+        println!("okay");
+
+        print!(
+            "Writing syntax set to {} ... ",
+            syntax_set_path.to_string_lossy()
+        );
+        dump_to_file(&self.syntax_set, &syntax_set_path).chain_err(|| {
+            format!(
+                "Could not save syntax set to {}",
+                syntax_set_path.to_string_lossy()
+            )
+        })?;
+        println!("okay");
+
+        Ok(())
+    }
+
     fn from_binary() -> Self {
         let syntax_set = Self::get_integrated_syntaxset();
         let theme_set = Self::get_integrated_themeset();
@@ -69,12 +158,56 @@ impl HighlightingAssets {
     }
 }
 
-fn theme_set_path() -> PathBuf {
-    PROJECT_DIRS.cache_dir().join("themes.bin")
+/// Return the directory holding the cache to load: delta's cache directory, unless delta has no
+/// cache, in which case bat's. Earlier versions of delta used bat's cache, so that is where the
+/// custom themes and syntaxes of users who have not run `delta --build-cache` are found.
+fn select_cache_dir<'a>(cache_dir: &'a Path, bat_cache_dir: &'a Path) -> &'a Path {
+    let has_cache =
+        |dir: &Path| dir.join("themes.bin").exists() || dir.join("syntaxes.bin").exists();
+    if !has_cache(cache_dir) && has_cache(bat_cache_dir) {
+        bat_cache_dir
+    } else {
+        cache_dir
+    }
+}
+
+/// Return the paths of the files in `dir` and its subdirectories having extension `extension`,
+/// in sorted order.
+fn find_files(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            paths.extend(find_files(&path, extension)?);
+        } else if path.extension() == Some(OsStr::new(extension)) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Build the cache of themes and syntaxes from the files in `source_dir` (by default, delta's
+/// config directory). See HighlightingAssets::from_files.
+pub fn build_cache(source_dir: Option<&Path>) -> Result<()> {
+    let source_dir = source_dir.unwrap_or_else(|| PROJECT_DIRS.config_dir());
+    HighlightingAssets::from_files(source_dir)?.save_to_cache(PROJECT_DIRS.cache_dir())
+}
+
+pub fn clear_cache() -> Result<()> {
+    _clear_cache(PROJECT_DIRS.cache_dir())
 }
 
-fn syntax_set_path() -> PathBuf {
-    PROJECT_DIRS.cache_dir().join("syntaxes.bin")
+fn _clear_cache(cache_dir: &Path) -> Result<()> {
+    for path in &[cache_dir.join("themes.bin"), cache_dir.join("syntaxes.bin")] {
+        if path.exists() {
+            print!("Removing {} ... ", path.to_string_lossy());
+            fs::remove_file(path)
+                .chain_err(|| format!("Could not remove {}", path.to_string_lossy()))?;
+            println!("okay");
+        }
+    }
+    Ok(())
 }
 
 pub fn list_languages() -> std::io::Result<()> {
@@ -142,3 +275,127 @@ pub fn list_languages() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const THEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>name</key>
+    <string>Toy Theme</string>
+    <key>settings</key>
+    <array>
+        <dict>
+            <key>settings</key>
+            <dict>
+                <key>background</key>
+                <string>#102030</string>
+                <key>foreground</key>
+                <string>#e0e0e0</string>
+            </dict>
+        </dict>
+    </array>
+</dict>
+</plist>
+"#;
+
+    const SYNTAX: &str = r#"%YAML 1.2
+---
+name: Toy
+file_extensions: [toy]
+scope: source.toy
+contexts:
+  main:
+    - match: '\bfrob\b'
+      scope: keyword.control.toy
+"#;
+
+    /// Create a source directory containing the given themes and syntaxes.
+    fn make_source_dir(name: &str, themes: &[(&str, &str)], syntaxes: &[(&str, &str)]) -> PathBuf {
+        let source_dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&source_dir);
+        for (subdir, files) in &[("themes", themes), ("syntaxes", syntaxes)] {
+            fs::create_dir_all(source_dir.join(subdir)).unwrap();
+            for (file_name, contents) in files.iter() {
+                fs::write(source_dir.join(subdir).join(file_name), contents).unwrap();
+            }
+        }
+        source_dir
+    }
+
+    #[test]
+    fn test_build_and_clear_cache() {
+        let source_dir = make_source_dir(
+            "delta__test_build_and_clear_cache",
+            &[("toy.tmTheme", THEME)],
+            &[("toy.sublime-syntax", SYNTAX)],
+        );
+        let assets = HighlightingAssets::from_files(&source_dir).unwrap();
+        assert!(assets.theme_set.themes.contains_key("toy"));
+        assert!(assets.theme_set.themes.contains_key("GitHub"));
+        assert!(assets.syntax_set.find_syntax_by_extension("toy").is_some());
+        assert!(assets.syntax_set.find_syntax_by_extension("rs").is_some());
+
+        let cache_dir = source_dir.join("cache");
+        assets.save_to_cache(&cache_dir).unwrap();
+        assert!(cache_dir.join("themes.bin").exists());
+        assert!(cache_dir.join("syntaxes.bin").exists());
+
+        _clear_cache(&cache_dir).unwrap();
+        assert!(!cache_dir.join("themes.bin").exists());
+        assert!(!cache_dir.join("syntaxes.bin").exists());
+
+        fs::remove_dir_all(&source_dir).unwrap();
+    }
+
+    #[test]
+    fn test_bat_cache_is_used_when_delta_has_no_cache() {
+        let source_dir = make_source_dir(
+            "delta__test_bat_cache_is_used_when_delta_has_no_cache",
+            &[("toy.tmTheme", THEME)],
+            &[],
+        );
+        let cache_dir = source_dir.join("delta");
+        let bat_cache_dir = source_dir.join("bat");
+        HighlightingAssets::from_files(&source_dir)
+            .unwrap()
+            .save_to_cache(&bat_cache_dir)
+            .unwrap();
+        assert_eq!(select_cache_dir(&cache_dir, &bat_cache_dir), bat_cache_dir);
+        let assets = HighlightingAssets::from_cache(&bat_cache_dir).unwrap();
+        assert!(assets.theme_set.themes.contains_key("toy"));
+
+        HighlightingAssets::from_binary()
+            .save_to_cache(&cache_dir)
+            .unwrap();
+        assert_eq!(select_cache_dir(&cache_dir, &bat_cache_dir), cache_dir);
+
+        fs::remove_dir_all(&source_dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_cache_reports_malformed_files() {
+        let source_dir = make_source_dir(
+            "delta__test_build_cache_reports_malformed_theme",
+            &[("broken.tmTheme", "<plist><dict>")],
+            &[],
+        );
+        let error = HighlightingAssets::from_files(&source_dir).err().unwrap();
+        assert!(error.to_string().contains("broken.tmTheme"));
+        fs::remove_dir_all(&source_dir).unwrap();
+
+        let source_dir = make_source_dir(
+            "delta__test_build_cache_reports_malformed_syntax",
+            &[],
+            &[("broken.sublime-syntax", "name: Broken\ncontexts: [")],
+        );
+        let error = HighlightingAssets::from_files(&source_dir).err().unwrap();
+        assert!(error.to_string().contains("broken.sublime-syntax"));
+        fs::remove_dir_all(&source_dir).unwrap();
+    }
+}
@@ -1,5 +1,4 @@
 use std::collections::{HashMap, HashSet};
-#[cfg(test)]
 use std::ffi::OsString;
 use std::path::PathBuf;
 
@@ -12,6 +11,7 @@ use syntect::parsing::SyntaxSet;
 
 use crate::bat::assets::HighlightingAssets;
 use crate::bat::output::PagingMode;
+use crate::errors;
 use crate::git_config::GitConfig;
 use crate::git_config_entry::GitConfigEntry;
 use crate::options;
@@ -81,7 +81,10 @@ That means: For removed lines, syntax-highlight the text, and make it bold, and
             normally does for the background.
 
 The available attributes are: 'blink', 'bold', 'dim', 'hidden', 'italic', 'reverse', 'strike',
-and 'ul' (or 'underline').
+'ul' (or 'underline'), and 'ol' (or 'overline').
+
+Many terminals also support underlines with a shape and a color: use 'ul:double', 'ul:curly',
+'ul:dotted', or 'ul:dashed', and 'ul-color:<color>', e.g. 'ul:curly ul-color:yellow'.
 
 The attribute 'omit' is supported by commit-style, file-style, and hunk-header-style, meaning to
 remove the element entirely from the output.
@@ -116,7 +119,7 @@ A complete description of the style string syntax follows:
 COLORS
 ------
 
-There are three ways to specify a color (this section applies to foreground and background colors
+There are several ways to specify a color (this section applies to foreground and background colors
 within a style string):
 
 1. RGB hex code
@@ -153,6 +156,26 @@ within a style string):
    Specifying colors like this is useful if your terminal only supports 256 colors (i.e. doesn\'t
    support 24-bit color).
 
+4. CSS color name
+
+   The CSS named colors (https://www.w3.org/TR/css-color-4/#named-colors) are accepted, e.g.
+   --file-style=\"darkorange\". Where a name is also an ANSI color name, the ANSI color is used.
+
+5. rgb() and hsl() functions
+
+   As in CSS, a color can be written as rgb(r, g, b) with components 0-255, or as hsl(h, s%, l%)
+   with the hue in degrees, e.g. --file-style=\"hsl(210, 50%, 40%)\".
+
+6. Palette name
+
+   Colors can be given names in the [delta \"palette\"] section of your gitconfig, and referred to
+   as $name in any style string. For example, with
+
+   [delta \"palette\"]
+       added-strong = \"#006000\"
+
+   you can use plus-emph-style = \"syntax $added-strong\".
+
 
 LINE NUMBERS
 ------------
@@ -212,6 +235,19 @@ pub struct Opt {
     #[structopt(long = "dark")]
     pub dark: bool,
 
+    /// Whether to ask the terminal for its background color, in order to choose between light and
+    /// dark mode (and the default syntax theme) when neither --light/--dark nor a syntax theme
+    /// determines the choice. Options are: auto (only when the output is going to a terminal),
+    /// always, and never.
+    #[structopt(long = "detect-dark-light", default_value = "auto")]
+    pub detect_dark_light: String,
+
+    /// Derive the default background colors of removed and added lines from the background color
+    /// of the syntax theme, by blending it toward red and green. See
+    /// --syntax-theme-background-strength.
+    #[structopt(long = "syntax-theme-backgrounds")]
+    pub syntax_theme_backgrounds: bool,
+
     /// Display line numbers next to the diff. See LINE NUMBERS section.
     #[structopt(short = "n", long = "line-numbers")]
     pub line_numbers: bool,
@@ -228,6 +264,13 @@ pub struct Opt {
     /// Emulate diff-so-fancy (https://github.com/so-fancy/diff-so-fancy)
     pub diff_so_fancy: bool,
 
+    #[structopt(long = "colorblind")]
+    /// Use colors that remain distinguishable under the common forms of color blindness (orange
+    /// for removed lines and blue for added lines), underline and embolden emphasized sections,
+    /// and mark removed and added lines using --minus-marker and --plus-marker, so that the type
+    /// of a change never depends on color alone.
+    pub colorblind: bool,
+
     #[structopt(long = "navigate")]
     /// Activate diff navigation: use n to jump forwards and N to jump backwards. To change the
     /// file labels used see --file-modified-label, --file-removed-label, --file-added-label,
@@ -247,11 +290,27 @@ pub struct Opt {
     /// fork of tmux (see https://github.com/dandavison/tmux).
     pub hyperlinks: bool,
 
+    #[structopt(long = "json-syntax-scopes")]
+    /// With --output-format=json, also divide the text of each line into sections labeled with
+    /// their syntax scopes (e.g. "source.rust", "keyword.control.rust").
+    pub json_syntax_scopes: bool,
+
     #[structopt(long = "keep-plus-minus-markers")]
     /// Prefix added/removed lines with a +/- character, exactly as git does. By default, delta
     /// does not emit any prefix, so code can be copied directly from delta's output.
     pub keep_plus_minus_markers: bool,
 
+    #[structopt(long = "minus-marker", default_value = "")]
+    /// Text to display before removed lines, in place of the - character of
+    /// --keep-plus-minus-markers. This is displayed in side-by-side mode also. Unchanged lines are
+    /// padded to the same width so that the code remains aligned.
+    pub minus_marker: String,
+
+    #[structopt(long = "plus-marker", default_value = "")]
+    /// Text to display before added lines, in place of the + character of
+    /// --keep-plus-minus-markers. See --minus-marker.
+    pub plus_marker: String,
+
     /// Display the active values for all Delta options. Style options are displayed with
     /// foreground and background colors. This can be used to experiment with colors by combining
     /// this option with other options such as --minus-style, --zero-style, --plus-style, --light,
@@ -273,6 +332,30 @@ pub struct Opt {
     #[structopt(long = "show-syntax-themes")]
     pub show_syntax_themes: bool,
 
+    /// Show all builtin themes, each with an example of diff output. A theme is a builtin feature
+    /// setting delta's colors; use one with e.g. `--features github-dark`. With --light or --dark,
+    /// only the themes designed for that terminal background are shown. If diff output is
+    /// supplied on standard input then this will be used for the demo.
+    #[structopt(long = "show-themes")]
+    pub show_themes: bool,
+
+    /// Build delta's cache of syntax-highlighting themes and language syntaxes, from the .tmTheme
+    /// files in the "themes" subdirectory, and the .sublime-syntax files in the "syntaxes"
+    /// subdirectory, of delta's config directory (e.g. ~/.config/delta). Use --source to read
+    /// them from a different directory. Until delta's cache is built, bat's cache is used, as in
+    /// earlier versions of delta.
+    #[structopt(long = "build-cache")]
+    pub build_cache: bool,
+
+    /// With --build-cache, the directory containing the "themes" and "syntaxes" subdirectories.
+    #[structopt(long = "source", parse(from_os_str))]
+    pub cache_source: Option<PathBuf>,
+
+    /// Remove delta's cache of syntax-highlighting themes and language syntaxes, so that only the
+    /// built-in themes and syntaxes (or those in bat's cache: see --build-cache) are used.
+    #[structopt(long = "clear-cache")]
+    pub clear_cache: bool,
+
     #[structopt(long = "no-gitconfig")]
     /// Do not take any settings from git config. See GIT CONFIG section.
     pub no_gitconfig: bool,
@@ -300,6 +383,23 @@ pub struct Opt {
     /// --syntax-theme=none disables all syntax highlighting.
     pub syntax_theme: Option<String>,
 
+    #[structopt(long = "syntax-theme-background-strength", default_value = "0.15")]
+    /// With --syntax-theme-backgrounds, the proportion (0-1) of red or green blended into the
+    /// syntax theme's background color to make the background of removed and added lines.
+    pub syntax_theme_background_strength: f64,
+
+    #[structopt(long = "syntax-theme-background-emph-strength", default_value = "0.35")]
+    /// With --syntax-theme-backgrounds, the proportion (0-1) of red or green blended into the
+    /// syntax theme's background color to make the background of emphasized sections of removed
+    /// and added lines.
+    pub syntax_theme_background_emph_strength: f64,
+
+    #[structopt(long = "syntax-theme-background-min-contrast", default_value = "1.1")]
+    /// With --syntax-theme-backgrounds, the minimum contrast ratio (as defined by WCAG, 1-21)
+    /// between a derived background color and the syntax theme's background color. The blend
+    /// strength is increased as necessary to reach it.
+    pub syntax_theme_background_min_contrast: f64,
+
     #[structopt(long = "minus-style", default_value = "normal auto")]
     /// Style (foreground, background, attributes) for removed lines. See STYLES section.
     pub minus_style: String,
@@ -340,9 +440,52 @@ pub struct Opt {
     #[structopt(long = "commit-decoration-style", default_value = "")]
     /// Style (foreground, background, attributes) for the commit hash decoration. See STYLES
     /// section. The style string should contain one of the special attributes 'box', 'ul'
-    /// (underline), 'ol' (overline), or the combination 'ul ol'.
+    /// (underline), 'ol' (overline), or the combination 'ul ol'. A box may be combined with 'ul'
+    /// and/or 'ol', and drawn as 'heavy-box', 'rounded-box' or 'double-box'. Alternatively, use
+    /// 'left-bar' or 'banner'.
     pub commit_decoration_style: String,
 
+    #[structopt(long = "commit-author-style", default_value = "raw")]
+    /// Style (foreground, background, attributes) for the author in the commit metadata (the
+    /// "Author:" and "Commit:" lines). See STYLES section.
+    pub commit_author_style: String,
+
+    #[structopt(long = "commit-date-style", default_value = "raw")]
+    /// Style (foreground, background, attributes) for the date in the commit metadata. See STYLES
+    /// section.
+    pub commit_date_style: String,
+
+    #[structopt(long = "commit-subject-style", default_value = "raw")]
+    /// Style (foreground, background, attributes) for the first line of the commit message. See
+    /// STYLES section.
+    pub commit_subject_style: String,
+
+    #[structopt(long = "commit-branch-style", default_value = "raw")]
+    /// Style (foreground, background, attributes) for branch names in the ref decorations of the
+    /// commit line, e.g. "(HEAD -> main)". See STYLES section. The value 'raw' means that
+    /// commit-style is used.
+    pub commit_branch_style: String,
+
+    #[structopt(long = "commit-remote-branch-style", default_value = "raw")]
+    /// Style (foreground, background, attributes) for remote-tracking branch names (e.g.
+    /// "origin/main") in the ref decorations of the commit line. See STYLES section. The value
+    /// 'raw' means that commit-style is used.
+    pub commit_remote_branch_style: String,
+
+    #[structopt(long = "commit-tag-style", default_value = "raw")]
+    /// Style (foreground, background, attributes) for tag names in the ref decorations of the
+    /// commit line. See STYLES section. The value 'raw' means that commit-style is used.
+    pub commit_tag_style: String,
+
+    #[structopt(long = "commit-hash-length", default_value = "0")]
+    /// Abbreviate the commit hash in the commit line to this many characters. The value 0 means
+    /// that the hash is displayed in full.
+    pub commit_hash_length: usize,
+
+    #[structopt(long = "relative-dates")]
+    /// Display commit dates relative to the current time, e.g. "3 days ago".
+    pub relative_dates: bool,
+
     #[structopt(long = "file-style", default_value = "blue")]
     /// Style (foreground, background, attributes) for the file section. See STYLES section. The
     /// style 'omit' can be used to remove the file section from the output.
@@ -351,7 +494,9 @@ pub struct Opt {
     #[structopt(long = "file-decoration-style", default_value = "blue ul")]
     /// Style (foreground, background, attributes) for the file decoration. See STYLES section. The
     /// style string should contain one of the special attributes 'box', 'ul' (underline), 'ol'
-    /// (overline), or the combination 'ul ol'.
+    /// (overline), or the combination 'ul ol'. A box may be combined with 'ul' and/or 'ol', and
+    /// drawn as 'heavy-box', 'rounded-box' or 'double-box'. Alternatively, use 'left-bar' or
+    /// 'banner'.
     pub file_decoration_style: String,
 
     /// Format string for file hyperlinks. The placeholders "{path}" and "{line}" will be replaced
@@ -365,6 +510,38 @@ pub struct Opt {
     #[structopt(long = "hyperlinks-file-link-format", default_value = "file://{path}")]
     pub hyperlinks_file_link_format: String,
 
+    /// Format string for commit hyperlinks. The placeholders "{host}", "{repo}" and "{commit}"
+    /// will be replaced by the host of the git remote (see --hyperlinks-remote), the repository
+    /// path on that host, and the commit hash. For example
+    /// "https://git.corp/{repo}/commit/{commit}". If this option is not supplied, commit links are
+    /// created for remotes hosted by GitHub, GitLab, Bitbucket, Gitea, and sourcehut (see
+    /// --hyperlinks-forge), but not for other hosts.
+    #[structopt(long = "hyperlinks-commit-link-format")]
+    pub hyperlinks_commit_link_format: Option<String>,
+
+    /// Rules for creating hyperlinks from references to issues, pull requests, tickets, etc. in
+    /// commit messages. The value is a space-separated list of entries of the form REGEX=URL, for
+    /// example 'JIRA-[0-9]+=https://jira.local/browse/{0}'. Since entries are separated by
+    /// whitespace and split at their first '=', REGEX cannot contain a literal space or '=': write
+    /// these as \x20 (or \s) and \x3D instead. In URL, "{0}" is replaced by the text matched by
+    /// REGEX and "{1}", "{2}", etc. by its capture groups. Issue references of the form "#123" are
+    /// linked automatically when the remote (see --hyperlinks-remote) is hosted by GitHub,
+    /// GitLab, Bitbucket, or Gitea (see --hyperlinks-forge).
+    #[structopt(long = "hyperlinks-issue-links", default_value = "")]
+    pub hyperlinks_issue_links: String,
+
+    /// The git remote whose URL is used to create commit hyperlinks.
+    #[structopt(long = "hyperlinks-remote", default_value = "origin")]
+    pub hyperlinks_remote: String,
+
+    /// The software hosting the git remote (see --hyperlinks-remote): one of "github", "gitlab",
+    /// "bitbucket", "gitea", or "sourcehut". This determines the form of commit and issue
+    /// hyperlinks. Only the public hosts github.com, gitlab.com, bitbucket.org, codeberg.org,
+    /// gitea.com and sr.ht are recognized automatically, so use this option for a self-hosted
+    /// forge.
+    #[structopt(long = "hyperlinks-forge", default_value = "")]
+    pub hyperlinks_forge: String,
+
     #[structopt(long = "hunk-header-style", default_value = "syntax")]
     /// Style (foreground, background, attributes) for the hunk-header. See STYLES section. The
     /// style 'omit' can be used to remove the hunk header section from the output.
@@ -373,9 +550,20 @@ pub struct Opt {
     #[structopt(long = "hunk-header-decoration-style", default_value = "blue box")]
     /// Style (foreground, background, attributes) for the hunk-header decoration. See STYLES
     /// section. The style string should contain one of the special attributes 'box', 'ul'
-    /// (underline), 'ol' (overline), or the combination 'ul ol'.
+    /// (underline), 'ol' (overline), or the combination 'ul ol'. A box may be combined with 'ul'
+    /// and/or 'ol', and drawn as 'heavy-box', 'rounded-box' or 'double-box'. Alternatively, use
+    /// 'left-bar' or 'banner'.
     pub hunk_header_decoration_style: String,
 
+    /// Assign syntax-highlighting languages to files whose paths match glob patterns. The value is
+    /// a space-separated list of entries of the form GLOB=LANGUAGE, for example
+    /// '*.conf=INI Dockerfile.*=Dockerfile'. See --include for the glob syntax. LANGUAGE may be a
+    /// language name or a file extension associated with the language (see --list-languages).
+    /// These entries take precedence over `diff=<driver>` gitattributes, which in turn take
+    /// precedence over the file extension.
+    #[structopt(long = "syntax-map", default_value = "")]
+    pub syntax_map: String,
+
     /// The regular expression used to decide what a word is for the within-line highlight
     /// algorithm. For less fine-grained matching than the default try --word-diff-regex="\S+"
     /// --max-line-distance=1.0 (this is more similar to `git --word-diff`).
@@ -388,6 +576,13 @@ pub struct Opt {
     #[structopt(long = "max-line-distance", default_value = "0.6")]
     pub max_line_distance: f64,
 
+    /// Truncate lines longer than this many characters, replacing the rest of the line with a
+    /// "… (N more chars)" marker. Syntax highlighting and within-line edit inference are slow on
+    /// very long lines, such as those of minified files. Use --max-line-length=0 to display lines
+    /// in full. Lines are not truncated by --raw or --color-only.
+    #[structopt(long = "max-line-length", default_value = "512")]
+    pub max_line_length: usize,
+
     /// Style (foreground, background, attributes) for line numbers in the old (minus) version of
     /// the file. See STYLES and LINE NUMBERS sections.
     #[structopt(long = "line-numbers-minus-style", default_value = "auto")]
@@ -443,6 +638,33 @@ pub struct Opt {
     /// Text to display in front of a renamed file path.
     pub file_renamed_label: String,
 
+    #[structopt(long = "relative-paths")]
+    /// Display file paths relative to the current directory, rather than relative to the
+    /// repository root. This is useful when running git diff from a subdirectory.
+    pub relative_paths: bool,
+
+    #[structopt(long = "include", default_value = "")]
+    /// Only display files whose paths match one of these glob patterns (space-separated). A
+    /// pattern containing no '/' is matched against the file name; otherwise it is matched against
+    /// the path relative to the repository root. '*' does not match '/', whereas '**' does.
+    pub include: String,
+
+    #[structopt(long = "exclude", default_value = "")]
+    /// Do not display files whose paths match one of these glob patterns (space-separated). See
+    /// --include for the pattern syntax. --exclude takes precedence over --include.
+    pub exclude: String,
+
+    #[structopt(long = "expand-generated-files")]
+    /// Display the diff of files that are marked in gitattributes as generated
+    /// (linguist-generated) or as not to be diffed (-diff). By default such files are collapsed to
+    /// a single line stating the number of lines changed.
+    pub expand_generated_files: bool,
+
+    #[structopt(long = "summarize-hidden-files")]
+    /// Instead of silently removing the files hidden by --include/--exclude, display a single
+    /// "N files hidden" line in their place.
+    pub summarize_hidden_files: bool,
+
     /// The width of underline/overline decorations. Use --width=variable to extend decorations and
     /// background colors to the end of the text only. Otherwise background colors extend to the
     /// full terminal width.
@@ -456,6 +678,12 @@ pub struct Opt {
     #[structopt(long = "tabs", default_value = "4")]
     pub tab_width: usize,
 
+    /// The number of threads used to paint the files of a diff in parallel, which speeds up
+    /// syntax highlighting of large diffs. The default, 0, uses one thread per CPU; use --workers=1
+    /// to paint the diff in a single thread.
+    #[structopt(long = "workers", default_value = "0")]
+    pub workers: usize,
+
     /// Whether to emit 24-bit ("true color") RGB color codes. Options are auto, always, and never.
     /// "auto" means that delta will emit 24-bit color codes if the environment variable COLORTERM
     /// has the value "truecolor" or "24bit". If your terminal application (the application you use
@@ -464,6 +692,24 @@ pub struct Opt {
     #[structopt(long = "24-bit-color", default_value = "auto")]
     pub true_color: String,
 
+    /// Whether to emit colors and other styling. Options are auto, always, and never. "auto" means
+    /// that delta will use colors unless the environment variable NO_COLOR is set (see
+    /// https://no-color.org). With "never", the output contains no ANSI escape sequences, but
+    /// decorations, line numbers, and side-by-side panels are retained, and within-line edits
+    /// are marked using --minus-emph-delimiters and --plus-emph-delimiters.
+    #[structopt(long = "color", default_value = "auto")]
+    pub color: String,
+
+    /// The text placed around within-line edits in removed lines when colors are not used (see
+    /// --color). The opening and closing delimiters are separated by "..".
+    #[structopt(long = "minus-emph-delimiters", default_value = "[-..-]")]
+    pub minus_emph_delimiters: String,
+
+    /// The text placed around within-line edits in added lines when colors are not used (see
+    /// --color). The opening and closing delimiters are separated by "..".
+    #[structopt(long = "plus-emph-delimiters", default_value = "{+..+}")]
+    pub plus_emph_delimiters: String,
+
     /// Whether to examine ANSI color escape sequences in raw lines received from Git and handle
     /// lines colored in certain ways specially. This is on by default: it is how Delta supports
     /// Git's --color-moved feature. Set this to "false" to disable this behavior.
@@ -476,6 +722,16 @@ pub struct Opt {
     #[structopt(long = "paging", default_value = "auto")]
     pub paging_mode: String,
 
+    /// The format of delta's output. Options are ansi (text colored using ANSI escape sequences,
+    /// for display in a terminal), html (a standalone HTML page with inline CSS, e.g. for
+    /// attaching to code-review emails or CI artifacts), svg (an image with the text laid out
+    /// in a monospace grid as wide as --width, e.g. for design docs and slides), and json (a stream
+    /// of commit, file, hunk and line records, one JSON object per line, including the inferred
+    /// within-line edits; see --json-syntax-scopes). HTML, SVG and JSON output is written to
+    /// standard output without using a pager.
+    #[structopt(long = "output-format", default_value = "ansi")]
+    pub output_format: String,
+
     /// First file to be compared when delta is being used in diff mode: `delta file_1 file_2` is
     /// equivalent to `diff -u file_1 file_2 | delta`.
     #[structopt(parse(from_os_str))]
@@ -561,10 +817,12 @@ pub struct ComputedValues {
     pub syntax_theme: Option<SyntaxTheme>,
     pub syntax_dummy_theme: SyntaxTheme,
     pub true_color: bool,
+    pub monochrome: bool,
     pub available_terminal_width: usize,
     pub decorations_width: Width,
     pub background_color_extends_to_terminal_width: bool,
     pub paging_mode: PagingMode,
+    pub output_format: OutputFormat,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -585,6 +843,20 @@ pub enum InspectRawLines {
     False,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Ansi,
+    Html,
+    Svg,
+    Json,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        OutputFormat::Ansi
+    }
+}
+
 impl Default for InspectRawLines {
     fn default() -> Self {
         InspectRawLines::False
@@ -601,12 +873,14 @@ impl Opt {
     pub fn from_args_and_git_config(
         git_config: &mut Option<GitConfig>,
         assets: HighlightingAssets,
-    ) -> Self {
+    ) -> Result<Self, String> {
         Self::from_clap_and_git_config(Self::clap().get_matches(), git_config, assets)
     }
 
-    #[cfg(test)]
-    pub fn from_iter_and_git_config<I>(iter: I, git_config: &mut Option<GitConfig>) -> Self
+    pub fn from_iter_and_git_config<I>(
+        iter: I,
+        git_config: &mut Option<GitConfig>,
+    ) -> Result<Self, String>
     where
         I: IntoIterator,
         I::Item: Into<OsString> + Clone,
@@ -615,15 +889,37 @@ impl Opt {
         Self::from_clap_and_git_config(Self::clap().get_matches_from(iter), git_config, assets)
     }
 
+    /// As `from_iter_and_git_config`, but return a command line parsing error (without color)
+    /// instead of exiting.
+    pub fn from_iter_safe_and_git_config<I>(
+        iter: I,
+        git_config: &mut Option<GitConfig>,
+    ) -> errors::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString> + Clone,
+    {
+        let arg_matches = Self::clap()
+            .setting(clap::AppSettings::ColorNever)
+            .get_matches_from_safe(iter)?;
+        let assets = HighlightingAssets::new();
+        Ok(Self::from_clap_and_git_config(
+            arg_matches,
+            git_config,
+            assets,
+        )?)
+    }
+
+    /// Fails with a message describing the first invalid option value.
     fn from_clap_and_git_config(
         arg_matches: clap::ArgMatches,
         git_config: &mut Option<GitConfig>,
         assets: HighlightingAssets,
-    ) -> Self {
+    ) -> Result<Self, String> {
         let mut opt = Opt::from_clap(&arg_matches);
-        options::rewrite::apply_rewrite_rules(&mut opt, &arg_matches);
-        options::set::set_options(&mut opt, git_config, &arg_matches, assets);
-        opt
+        options::rewrite::apply_rewrite_rules(&mut opt, &arg_matches)?;
+        options::set::set_options(&mut opt, git_config, &arg_matches, assets)?;
+        Ok(opt)
     }
 
     #[allow(dead_code)]
@@ -660,10 +956,14 @@ lazy_static! {
         "deprecated-highlight-minus-lines",
         "deprecated-theme",
         "deprecated-commit-color",
+        "build-cache",
+        "cache-source",
+        "clear-cache",
         "list-languages",
         "list-syntax-themes",
         "show-config",
         "show-syntax-themes",
+        "show-themes",
     ]
     .into_iter()
     .collect();
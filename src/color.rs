@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::process;
 use std::str::FromStr;
 
 use ansi_term::Color;
@@ -9,24 +8,77 @@ use syntect::highlighting::Color as SyntectColor;
 use crate::bat::terminal::to_ansi_color;
 use crate::syntect_color;
 
-pub fn parse_color(s: &str, true_color: bool) -> Option<Color> {
+pub fn parse_color(s: &str, true_color: bool) -> Result<Option<Color>, String> {
     if s == "normal" {
-        return None;
+        return Ok(None);
     }
-    let die = || {
-        eprintln!("Invalid color or style attribute: {}", s);
-        process::exit(1);
-    };
     let syntect_color = if s.starts_with("#") {
-        SyntectColor::from_str(s).unwrap_or_else(|_| die())
+        SyntectColor::from_str(s).ok()
+    } else if s.starts_with("rgb(") || s.starts_with("hsl(") {
+        parse_color_function(s)
     } else {
         s.parse::<u8>()
             .ok()
             .and_then(syntect_color::syntect_color_from_ansi_number)
             .or_else(|| syntect_color::syntect_color_from_ansi_name(s))
-            .unwrap_or_else(die)
+            .or_else(|| syntect_color_from_css_name(s))
+    }
+    .ok_or_else(|| format!("Invalid color or style attribute: {}", s))?;
+    Ok(Some(to_ansi_color(syntect_color, true_color)))
+}
+
+/// Parse a color written as a CSS function: rgb(r, g, b) with components in 0-255, or
+/// hsl(h, s%, l%) with hue in degrees.
+fn parse_color_function(s: &str) -> Option<SyntectColor> {
+    let (function, args) = s.strip_suffix(')')?.split_at(4);
+    let args: Vec<&str> = args.split(',').map(str::trim).collect();
+    if args.len() != 3 {
+        return None;
+    }
+    let (r, g, b) = match function {
+        "rgb(" => (
+            args[0].parse::<u8>().ok()?,
+            args[1].parse::<u8>().ok()?,
+            args[2].parse::<u8>().ok()?,
+        ),
+        "hsl(" => {
+            let parse_percentage = |arg: &str| match arg.trim_end_matches('%').parse::<f64>() {
+                Ok(value) if (0.0..=100.0).contains(&value) => Some(value / 100.0),
+                _ => None,
+            };
+            hsl_to_rgb(
+                args[0].trim_end_matches("deg").parse::<f64>().ok()?,
+                parse_percentage(args[1])?,
+                parse_percentage(args[2])?,
+            )
+        }
+        _ => return None,
+    };
+    Some(SyntectColor { r, g, b, a: 0xFF })
+}
+
+/// Convert a color from HSL (hue in degrees, saturation and lightness in 0-1) to RGB.
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
     };
-    Some(to_ansi_color(syntect_color, true_color))
+    let m = lightness - chroma / 2.0;
+    let to_u8 = |c: f64| ((c + m) * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+fn syntect_color_from_css_name(name: &str) -> Option<SyntectColor> {
+    CSS_COLORS
+        .get(name)
+        .map(|&(r, g, b)| SyntectColor { r, g, b, a: 0xFF })
 }
 
 pub fn color_to_string(color: Color) -> String {
@@ -83,6 +135,165 @@ lazy_static! {
     };
 }
 
+// The CSS named colors. The 16 ANSI color names above take precedence over these.
+// See https://www.w3.org/TR/css-color-4/#named-colors
+lazy_static! {
+    static ref CSS_COLORS: HashMap<&'static str, (u8, u8, u8)> = {
+        vec![
+            ("aliceblue", (0xf0, 0xf8, 0xff)),
+            ("antiquewhite", (0xfa, 0xeb, 0xd7)),
+            ("aqua", (0x00, 0xff, 0xff)),
+            ("aquamarine", (0x7f, 0xff, 0xd4)),
+            ("azure", (0xf0, 0xff, 0xff)),
+            ("beige", (0xf5, 0xf5, 0xdc)),
+            ("bisque", (0xff, 0xe4, 0xc4)),
+            ("black", (0x00, 0x00, 0x00)),
+            ("blanchedalmond", (0xff, 0xeb, 0xcd)),
+            ("blue", (0x00, 0x00, 0xff)),
+            ("blueviolet", (0x8a, 0x2b, 0xe2)),
+            ("brown", (0xa5, 0x2a, 0x2a)),
+            ("burlywood", (0xde, 0xb8, 0x87)),
+            ("cadetblue", (0x5f, 0x9e, 0xa0)),
+            ("chartreuse", (0x7f, 0xff, 0x00)),
+            ("chocolate", (0xd2, 0x69, 0x1e)),
+            ("coral", (0xff, 0x7f, 0x50)),
+            ("cornflowerblue", (0x64, 0x95, 0xed)),
+            ("cornsilk", (0xff, 0xf8, 0xdc)),
+            ("crimson", (0xdc, 0x14, 0x3c)),
+            ("cyan", (0x00, 0xff, 0xff)),
+            ("darkblue", (0x00, 0x00, 0x8b)),
+            ("darkcyan", (0x00, 0x8b, 0x8b)),
+            ("darkgoldenrod", (0xb8, 0x86, 0x0b)),
+            ("darkgray", (0xa9, 0xa9, 0xa9)),
+            ("darkgreen", (0x00, 0x64, 0x00)),
+            ("darkgrey", (0xa9, 0xa9, 0xa9)),
+            ("darkkhaki", (0xbd, 0xb7, 0x6b)),
+            ("darkmagenta", (0x8b, 0x00, 0x8b)),
+            ("darkolivegreen", (0x55, 0x6b, 0x2f)),
+            ("darkorange", (0xff, 0x8c, 0x00)),
+            ("darkorchid", (0x99, 0x32, 0xcc)),
+            ("darkred", (0x8b, 0x00, 0x00)),
+            ("darksalmon", (0xe9, 0x96, 0x7a)),
+            ("darkseagreen", (0x8f, 0xbc, 0x8f)),
+            ("darkslateblue", (0x48, 0x3d, 0x8b)),
+            ("darkslategray", (0x2f, 0x4f, 0x4f)),
+            ("darkslategrey", (0x2f, 0x4f, 0x4f)),
+            ("darkturquoise", (0x00, 0xce, 0xd1)),
+            ("darkviolet", (0x94, 0x00, 0xd3)),
+            ("deeppink", (0xff, 0x14, 0x93)),
+            ("deepskyblue", (0x00, 0xbf, 0xff)),
+            ("dimgray", (0x69, 0x69, 0x69)),
+            ("dimgrey", (0x69, 0x69, 0x69)),
+            ("dodgerblue", (0x1e, 0x90, 0xff)),
+            ("firebrick", (0xb2, 0x22, 0x22)),
+            ("floralwhite", (0xff, 0xfa, 0xf0)),
+            ("forestgreen", (0x22, 0x8b, 0x22)),
+            ("fuchsia", (0xff, 0x00, 0xff)),
+            ("gainsboro", (0xdc, 0xdc, 0xdc)),
+            ("ghostwhite", (0xf8, 0xf8, 0xff)),
+            ("gold", (0xff, 0xd7, 0x00)),
+            ("goldenrod", (0xda, 0xa5, 0x20)),
+            ("gray", (0x80, 0x80, 0x80)),
+            ("green", (0x00, 0x80, 0x00)),
+            ("greenyellow", (0xad, 0xff, 0x2f)),
+            ("grey", (0x80, 0x80, 0x80)),
+            ("honeydew", (0xf0, 0xff, 0xf0)),
+            ("hotpink", (0xff, 0x69, 0xb4)),
+            ("indianred", (0xcd, 0x5c, 0x5c)),
+            ("indigo", (0x4b, 0x00, 0x82)),
+            ("ivory", (0xff, 0xff, 0xf0)),
+            ("khaki", (0xf0, 0xe6, 0x8c)),
+            ("lavender", (0xe6, 0xe6, 0xfa)),
+            ("lavenderblush", (0xff, 0xf0, 0xf5)),
+            ("lawngreen", (0x7c, 0xfc, 0x00)),
+            ("lemonchiffon", (0xff, 0xfa, 0xcd)),
+            ("lightblue", (0xad, 0xd8, 0xe6)),
+            ("lightcoral", (0xf0, 0x80, 0x80)),
+            ("lightcyan", (0xe0, 0xff, 0xff)),
+            ("lightgoldenrodyellow", (0xfa, 0xfa, 0xd2)),
+            ("lightgray", (0xd3, 0xd3, 0xd3)),
+            ("lightgreen", (0x90, 0xee, 0x90)),
+            ("lightgrey", (0xd3, 0xd3, 0xd3)),
+            ("lightpink", (0xff, 0xb6, 0xc1)),
+            ("lightsalmon", (0xff, 0xa0, 0x7a)),
+            ("lightseagreen", (0x20, 0xb2, 0xaa)),
+            ("lightskyblue", (0x87, 0xce, 0xfa)),
+            ("lightslategray", (0x77, 0x88, 0x99)),
+            ("lightslategrey", (0x77, 0x88, 0x99)),
+            ("lightsteelblue", (0xb0, 0xc4, 0xde)),
+            ("lightyellow", (0xff, 0xff, 0xe0)),
+            ("lime", (0x00, 0xff, 0x00)),
+            ("limegreen", (0x32, 0xcd, 0x32)),
+            ("linen", (0xfa, 0xf0, 0xe6)),
+            ("magenta", (0xff, 0x00, 0xff)),
+            ("maroon", (0x80, 0x00, 0x00)),
+            ("mediumaquamarine", (0x66, 0xcd, 0xaa)),
+            ("mediumblue", (0x00, 0x00, 0xcd)),
+            ("mediumorchid", (0xba, 0x55, 0xd3)),
+            ("mediumpurple", (0x93, 0x70, 0xdb)),
+            ("mediumseagreen", (0x3c, 0xb3, 0x71)),
+            ("mediumslateblue", (0x7b, 0x68, 0xee)),
+            ("mediumspringgreen", (0x00, 0xfa, 0x9a)),
+            ("mediumturquoise", (0x48, 0xd1, 0xcc)),
+            ("mediumvioletred", (0xc7, 0x15, 0x85)),
+            ("midnightblue", (0x19, 0x19, 0x70)),
+            ("mintcream", (0xf5, 0xff, 0xfa)),
+            ("mistyrose", (0xff, 0xe4, 0xe1)),
+            ("moccasin", (0xff, 0xe4, 0xb5)),
+            ("navajowhite", (0xff, 0xde, 0xad)),
+            ("navy", (0x00, 0x00, 0x80)),
+            ("oldlace", (0xfd, 0xf5, 0xe6)),
+            ("olive", (0x80, 0x80, 0x00)),
+            ("olivedrab", (0x6b, 0x8e, 0x23)),
+            ("orange", (0xff, 0xa5, 0x00)),
+            ("orangered", (0xff, 0x45, 0x00)),
+            ("orchid", (0xda, 0x70, 0xd6)),
+            ("palegoldenrod", (0xee, 0xe8, 0xaa)),
+            ("palegreen", (0x98, 0xfb, 0x98)),
+            ("paleturquoise", (0xaf, 0xee, 0xee)),
+            ("palevioletred", (0xdb, 0x70, 0x93)),
+            ("papayawhip", (0xff, 0xef, 0xd5)),
+            ("peachpuff", (0xff, 0xda, 0xb9)),
+            ("peru", (0xcd, 0x85, 0x3f)),
+            ("pink", (0xff, 0xc0, 0xcb)),
+            ("plum", (0xdd, 0xa0, 0xdd)),
+            ("powderblue", (0xb0, 0xe0, 0xe6)),
+            ("purple", (0x80, 0x00, 0x80)),
+            ("rebeccapurple", (0x66, 0x33, 0x99)),
+            ("red", (0xff, 0x00, 0x00)),
+            ("rosybrown", (0xbc, 0x8f, 0x8f)),
+            ("royalblue", (0x41, 0x69, 0xe1)),
+            ("saddlebrown", (0x8b, 0x45, 0x13)),
+            ("salmon", (0xfa, 0x80, 0x72)),
+            ("sandybrown", (0xf4, 0xa4, 0x60)),
+            ("seagreen", (0x2e, 0x8b, 0x57)),
+            ("seashell", (0xff, 0xf5, 0xee)),
+            ("sienna", (0xa0, 0x52, 0x2d)),
+            ("silver", (0xc0, 0xc0, 0xc0)),
+            ("skyblue", (0x87, 0xce, 0xeb)),
+            ("slateblue", (0x6a, 0x5a, 0xcd)),
+            ("slategray", (0x70, 0x80, 0x90)),
+            ("slategrey", (0x70, 0x80, 0x90)),
+            ("snow", (0xff, 0xfa, 0xfa)),
+            ("springgreen", (0x00, 0xff, 0x7f)),
+            ("steelblue", (0x46, 0x82, 0xb4)),
+            ("tan", (0xd2, 0xb4, 0x8c)),
+            ("teal", (0x00, 0x80, 0x80)),
+            ("thistle", (0xd8, 0xbf, 0xd8)),
+            ("tomato", (0xff, 0x63, 0x47)),
+            ("turquoise", (0x40, 0xe0, 0xd0)),
+            ("violet", (0xee, 0x82, 0xee)),
+            ("wheat", (0xf5, 0xde, 0xb3)),
+            ("white", (0xff, 0xff, 0xff)),
+            ("whitesmoke", (0xf5, 0xf5, 0xf5)),
+            ("yellow", (0xff, 0xff, 0x00)),
+            ("yellowgreen", (0x9a, 0xcd, 0x32)),
+        ]
+        .into_iter()
+        .collect()
+    };
+}
+
 pub fn ansi_16_color_name_to_number(name: &str) -> Option<u8> {
     ANSI_16_COLORS.get(name).map(|n| *n)
 }
@@ -132,6 +343,75 @@ pub fn get_plus_emph_background_color_default(is_light_mode: bool, is_true_color
     }
 }
 
+/// Return (minus, minus-emph, plus, plus-emph) background colors made by blending the syntax
+/// theme's background color toward red and green. `strength` and `emph_strength` are the
+/// proportions of red or green; they are increased as necessary until the contrast ratio with the
+/// theme's background is at least `min_contrast`.
+pub fn get_background_color_defaults_from_syntax_theme(
+    theme_background: SyntectColor,
+    strength: f64,
+    emph_strength: f64,
+    min_contrast: f64,
+    is_true_color: bool,
+) -> (Color, Color, Color, Color) {
+    let background = (theme_background.r, theme_background.g, theme_background.b);
+    let make_color = |target: (u8, u8, u8), strength: f64| {
+        let (r, g, b) = blend_with_min_contrast(background, target, strength, min_contrast);
+        to_ansi_color(SyntectColor { r, g, b, a: 0xFF }, is_true_color)
+    };
+    (
+        make_color(BLEND_TARGET_RED, strength),
+        make_color(BLEND_TARGET_RED, emph_strength),
+        make_color(BLEND_TARGET_GREEN, strength),
+        make_color(BLEND_TARGET_GREEN, emph_strength),
+    )
+}
+
+const BLEND_TARGET_RED: (u8, u8, u8) = (0xff, 0x00, 0x00);
+
+const BLEND_TARGET_GREEN: (u8, u8, u8) = (0x00, 0xc0, 0x00);
+
+fn blend_with_min_contrast(
+    background: (u8, u8, u8),
+    target: (u8, u8, u8),
+    strength: f64,
+    min_contrast: f64,
+) -> (u8, u8, u8) {
+    let mut strength = strength.clamp(0.0, 1.0);
+    loop {
+        let color = blend(background, target, strength);
+        if strength >= 1.0 || contrast_ratio(color, background) >= min_contrast {
+            return color;
+        }
+        strength = (strength + 0.01).min(1.0);
+    }
+}
+
+/// Return the color that is `strength` of the way from `a` to `b`.
+fn blend(a: (u8, u8, u8), b: (u8, u8, u8), strength: f64) -> (u8, u8, u8) {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * strength).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// The relative luminance of a color, as defined by WCAG 2.0.
+pub fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let linearize = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linearize(r) + 0.7152 * linearize(g) + 0.0722 * linearize(b)
+}
+
+/// The contrast ratio of two colors, as defined by WCAG 2.0 (from 1 to 21).
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
 const LIGHT_THEME_MINUS_COLOR: Color = Color::RGB(0xff, 0xe0, 0xe0);
 
 const LIGHT_THEME_MINUS_COLOR_256: Color = Color::Fixed(224);
@@ -163,3 +443,95 @@ const DARK_THEME_PLUS_COLOR_256: Color = Color::Fixed(22);
 const DARK_THEME_PLUS_EMPH_COLOR: Color = Color::RGB(0x00, 0x60, 0x00);
 
 const DARK_THEME_PLUS_EMPH_COLOR_256: Color = Color::Fixed(28);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("normal", true).unwrap(), None);
+        assert_eq!(parse_color("red", true).unwrap(), Some(Color::Fixed(1)));
+        assert_eq!(
+            parse_color("#aabbcc", true).unwrap(),
+            Some(Color::RGB(170, 187, 204))
+        );
+        assert_eq!(
+            parse_color("rebeccapurple", true).unwrap(),
+            Some(Color::RGB(102, 51, 153))
+        );
+        assert_eq!(
+            parse_color("darkgreen", true).unwrap(),
+            Some(Color::RGB(0, 100, 0))
+        );
+        assert_eq!(
+            parse_color("rgb(1,2,3)", true).unwrap(),
+            Some(Color::RGB(1, 2, 3))
+        );
+        assert_eq!(
+            parse_color("rgb(1, 2, 3)", true).unwrap(),
+            Some(Color::RGB(1, 2, 3))
+        );
+        assert_eq!(
+            parse_color("hsl(0, 100%, 50%)", true).unwrap(),
+            Some(Color::RGB(255, 0, 0))
+        );
+        assert_eq!(
+            parse_color("hsl(120,100%,25%)", true).unwrap(),
+            Some(Color::RGB(0, 128, 0))
+        );
+        assert_eq!(
+            parse_color("hsl(210, 50%, 40%)", true).unwrap(),
+            Some(Color::RGB(51, 102, 153))
+        );
+        assert_eq!(
+            parse_color("rgb(255, 0, 0)", false).unwrap(),
+            Some(Color::Fixed(ansi_colours::ansi256_from_rgb((255, 0, 0))))
+        );
+        assert_eq!(
+            parse_color("notacolor", true),
+            Err("Invalid color or style attribute: notacolor".to_string())
+        );
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert_eq!(contrast_ratio((0, 0, 0), (0xff, 0xff, 0xff)), 21.0);
+        assert_eq!(contrast_ratio((0x80, 0x80, 0x80), (0x80, 0x80, 0x80)), 1.0);
+    }
+
+    #[test]
+    fn test_get_background_color_defaults_from_syntax_theme() {
+        let nord = SyntectColor {
+            r: 0x2e,
+            g: 0x34,
+            b: 0x40,
+            a: 0xFF,
+        };
+        let (minus, minus_emph, plus, plus_emph) =
+            get_background_color_defaults_from_syntax_theme(nord, 0.15, 0.35, 1.0, true);
+        assert_eq!(minus, Color::RGB(0x4d, 0x2c, 0x36));
+        assert_eq!(minus_emph, Color::RGB(0x77, 0x22, 0x2a));
+        assert_eq!(plus, Color::RGB(0x27, 0x49, 0x36));
+        assert_eq!(plus_emph, Color::RGB(0x1e, 0x65, 0x2a));
+
+        // The strength is increased until the minimum contrast is reached.
+        let (minus, _, _, _) =
+            get_background_color_defaults_from_syntax_theme(nord, 0.0, 0.35, 1.5, true);
+        let minus = match minus {
+            Color::RGB(r, g, b) => (r, g, b),
+            _ => unreachable!(),
+        };
+        let nord = (nord.r, nord.g, nord.b);
+        assert!(contrast_ratio(minus, nord) >= 1.5);
+        assert!(contrast_ratio(minus, nord) < 1.6);
+    }
+
+    #[test]
+    fn test_parse_color_function_invalid() {
+        assert_eq!(parse_color_function("rgb(1, 2)"), None);
+        assert_eq!(parse_color_function("rgb(1, 2, 256)"), None);
+        assert_eq!(parse_color_function("hsl(0, 100%, 150%)"), None);
+        assert_eq!(parse_color_function("rgb(1, 2, 3"), None);
+    }
+}
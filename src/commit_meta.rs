@@ -0,0 +1,350 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::config::Config;
+use crate::features::hyperlinks;
+use crate::style::Style;
+
+/// Position within the commit metadata section (headers, then a blank line, then the message).
+#[derive(Default)]
+pub struct CommitMetaState {
+    in_message: bool,
+    seen_subject: bool,
+}
+
+impl CommitMetaState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+lazy_static! {
+    static ref COMMIT_LINE_REGEX: Regex =
+        Regex::new(r"^(commit )([0-9a-f]{4,40})(?: \((.*)\))?(.*)$").unwrap();
+    static ref AUTHOR_LINE_REGEX: Regex = Regex::new(r"^((?:Author|Commit):\s+)(.*)$").unwrap();
+    static ref DATE_LINE_REGEX: Regex =
+        Regex::new(r"^((?:Date|AuthorDate|CommitDate):\s+)(.*)$").unwrap();
+}
+
+/// Are any of the commit line styling options in effect? If not, the commit line is handled by
+/// commit-style alone.
+pub fn should_format_commit_line(config: &Config) -> bool {
+    config.commit_hash_length > 0
+        || !config.commit_branch_style.is_raw
+        || !config.commit_remote_branch_style.is_raw
+        || !config.commit_tag_style.is_raw
+}
+
+/// Return the "commit <hash> (<refs>)" line, with the hash abbreviated according to
+/// --commit-hash-length and the refs painted with the branch, remote branch and tag styles. Text
+/// not belonging to a ref is painted with commit-style (or left unstyled if that is 'raw'). The
+/// hash is hyperlinked if --hyperlinks is in effect. Return None if no formatting applies.
+pub fn format_commit_line(line: &str, config: &Config) -> Option<String> {
+    if !should_format_commit_line(config) {
+        return None;
+    }
+    let captures = COMMIT_LINE_REGEX.captures(line)?;
+    let base_style = if config.commit_style.is_raw {
+        Style::new()
+    } else {
+        config.commit_style
+    };
+    let commit = captures.get(2).unwrap().as_str();
+    let abbreviated_commit = if config.commit_hash_length > 0 {
+        &commit[..config.commit_hash_length.min(commit.len())]
+    } else {
+        commit
+    };
+    let commit_text = if config.hyperlinks {
+        hyperlinks::format_osc8_commit_hyperlink(commit, abbreviated_commit, config)
+    } else {
+        abbreviated_commit.to_string()
+    };
+    let mut formatted = format!(
+        "{}{}",
+        base_style.paint(captures.get(1).unwrap().as_str()),
+        base_style.paint(commit_text)
+    );
+    if let Some(refs) = captures.get(3) {
+        formatted.push_str(&base_style.paint(" (").to_string());
+        for (i, ref_) in refs.as_str().split(", ").enumerate() {
+            if i > 0 {
+                formatted.push_str(&base_style.paint(", ").to_string());
+            }
+            formatted.push_str(&format_ref(ref_, base_style, config));
+        }
+        formatted.push_str(&base_style.paint(")").to_string());
+    }
+    formatted.push_str(
+        &base_style
+            .paint(captures.get(4).unwrap().as_str())
+            .to_string(),
+    );
+    Some(formatted)
+}
+
+/// Paint one ref decoration, e.g. "HEAD -> main", "origin/main", or "tag: v1.2".
+fn format_ref(ref_: &str, base_style: Style, config: &Config) -> String {
+    let or_base = |style: Style| if style.is_raw { base_style } else { style };
+    if let Some(tag) = ref_.strip_prefix("tag: ") {
+        format!(
+            "{}{}",
+            base_style.paint("tag: "),
+            or_base(config.commit_tag_style).paint(tag)
+        )
+    } else if let Some(i) = ref_.find(" -> ") {
+        format!(
+            "{}{}{}",
+            or_base(config.commit_branch_style).paint(&ref_[..i]),
+            base_style.paint(" -> "),
+            format_ref(&ref_[i + " -> ".len()..], base_style, config)
+        )
+    } else if is_remote_branch(ref_, config) {
+        or_base(config.commit_remote_branch_style)
+            .paint(ref_)
+            .to_string()
+    } else {
+        or_base(config.commit_branch_style).paint(ref_).to_string()
+    }
+}
+
+fn is_remote_branch(ref_: &str, config: &Config) -> bool {
+    match ref_.find('/') {
+        Some(i) => config.remote_names.iter().any(|name| name == &ref_[..i]),
+        None => false,
+    }
+}
+
+/// Format a line of the commit metadata section other than the "commit <hash>" line: the author,
+/// the date (optionally as a relative date), and the subject (the first line of the message).
+/// Return None if no formatting applies, in which case the raw line should be emitted.
+pub fn format_commit_meta_line(
+    line: &str,
+    state: &mut CommitMetaState,
+    config: &Config,
+) -> Option<String> {
+    if state.in_message {
+        if !state.seen_subject && !line.trim().is_empty() {
+            state.seen_subject = true;
+            if !config.commit_subject_style.is_raw {
+                let subject = line.trim_start();
+                let indent = &line[..line.len() - subject.len()];
+                return Some(format!(
+                    "{}{}",
+                    indent,
+                    config.commit_subject_style.paint(subject)
+                ));
+            }
+        }
+        None
+    } else if line.is_empty() {
+        state.in_message = true;
+        None
+    } else if let Some(captures) = AUTHOR_LINE_REGEX.captures(line) {
+        if config.commit_author_style.is_raw {
+            return None;
+        }
+        Some(format!(
+            "{}{}",
+            captures.get(1).unwrap().as_str(),
+            config
+                .commit_author_style
+                .paint(captures.get(2).unwrap().as_str())
+        ))
+    } else if let Some(captures) = DATE_LINE_REGEX.captures(line) {
+        if config.commit_date_style.is_raw && !config.relative_dates {
+            return None;
+        }
+        let date = captures.get(2).unwrap().as_str();
+        let date = match parse_git_date(date) {
+            Some(timestamp) if config.relative_dates => format_relative_date(timestamp, now()),
+            _ => date.to_string(),
+        };
+        let date_style = if config.commit_date_style.is_raw {
+            Style::new()
+        } else {
+            config.commit_date_style
+        };
+        Some(format!(
+            "{}{}",
+            captures.get(1).unwrap().as_str(),
+            date_style.paint(date)
+        ))
+    } else {
+        None
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parse a date in one of the formats emitted by git log (default, --date=iso, --date=iso-strict,
+/// --date=rfc), returning seconds since the Unix epoch.
+fn parse_git_date(date: &str) -> Option<i64> {
+    let date = date.replace(',', "");
+    let tokens: Vec<&str> = date.split_whitespace().collect();
+    let month = |name: &str| MONTHS.iter().position(|m| *m == name).map(|i| i as i64 + 1);
+    let (year, month, day, time, tz) = match tokens.as_slice() {
+        // Thu Jul 11 10:41:11 2019 -0400
+        [_, month_name, day, time, year, tz] if month(month_name).is_some() => (
+            year.parse().ok()?,
+            month(month_name)?,
+            day.parse().ok()?,
+            *time,
+            *tz,
+        ),
+        // Thu 11 Jul 2019 10:41:11 -0400
+        [_, day, month_name, year, time, tz] => (
+            year.parse().ok()?,
+            month(month_name)?,
+            day.parse().ok()?,
+            *time,
+            *tz,
+        ),
+        // 2019-07-11 10:41:11 -0400
+        [ymd, time, tz] => {
+            let ymd: Vec<&str> = ymd.split('-').collect();
+            match ymd.as_slice() {
+                [y, m, d] => (
+                    y.parse().ok()?,
+                    m.parse().ok()?,
+                    d.parse().ok()?,
+                    *time,
+                    *tz,
+                ),
+                _ => return None,
+            }
+        }
+        // 2019-07-11T10:41:11-04:00
+        [iso_strict] => {
+            let (ymd, rest) = iso_strict.split_at(iso_strict.find('T')?);
+            let rest = &rest[1..];
+            let tz_start = rest.find(['+', '-', 'Z'].as_ref())?;
+            let ymd: Vec<&str> = ymd.split('-').collect();
+            match ymd.as_slice() {
+                [y, m, d] => (
+                    y.parse().ok()?,
+                    m.parse().ok()?,
+                    d.parse().ok()?,
+                    &rest[..tz_start],
+                    &rest[tz_start..],
+                ),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let hms: Vec<i64> = time
+        .split(':')
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    let seconds_in_day = match hms.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        _ => return None,
+    };
+    Some(days_from_civil(year, month, day) * 86400 + seconds_in_day - parse_tz_offset(tz)?)
+}
+
+/// Parse a timezone offset such as "-0400", "+05:30" or "Z", returning seconds east of UTC.
+fn parse_tz_offset(tz: &str) -> Option<i64> {
+    if tz == "Z" {
+        return Some(0);
+    }
+    let sign = match tz.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = tz[1..].replace(':', "");
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Number of days since 1970-01-01 of the given date in the proleptic Gregorian calendar.
+// See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Describe the time elapsed between `then` and `now` in the manner of git's --date=relative.
+fn format_relative_date(then: i64, now: i64) -> String {
+    let seconds = now - then;
+    if seconds < 0 {
+        return "in the future".to_string();
+    }
+    let plural =
+        |n: i64, unit: &str| format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" });
+    let minutes = (seconds + 30) / 60;
+    let hours = (minutes + 30) / 60;
+    let days = (hours + 12) / 24;
+    if seconds < 90 {
+        plural(seconds, "second")
+    } else if minutes < 90 {
+        plural(minutes, "minute")
+    } else if hours < 36 {
+        plural(hours, "hour")
+    } else if days < 14 {
+        plural(days, "day")
+    } else if days < 70 {
+        plural((days + 3) / 7, "week")
+    } else if days < 365 {
+        plural((days + 15) / 30, "month")
+    } else {
+        plural((days + 183) / 365, "year")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_date() {
+        let expected = Some(1562856071);
+        assert_eq!(parse_git_date("Thu Jul 11 10:41:11 2019 -0400"), expected);
+        assert_eq!(parse_git_date("Thu, 11 Jul 2019 10:41:11 -0400"), expected);
+        assert_eq!(parse_git_date("2019-07-11 10:41:11 -0400"), expected);
+        assert_eq!(parse_git_date("2019-07-11T10:41:11-04:00"), expected);
+        assert_eq!(parse_git_date("2019-07-11T14:41:11Z"), expected);
+        assert_eq!(parse_git_date("3 days ago"), None);
+    }
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+
+    #[test]
+    fn test_format_relative_date() {
+        let now = 1_600_000_000;
+        assert_eq!(format_relative_date(now - 1, now), "1 second ago");
+        assert_eq!(format_relative_date(now - 600, now), "10 minutes ago");
+        assert_eq!(format_relative_date(now - 5 * 3600, now), "5 hours ago");
+        assert_eq!(format_relative_date(now - 3 * 86400, now), "3 days ago");
+        assert_eq!(format_relative_date(now - 21 * 86400, now), "3 weeks ago");
+        assert_eq!(format_relative_date(now - 120 * 86400, now), "4 months ago");
+        assert_eq!(format_relative_date(now - 800 * 86400, now), "2 years ago");
+        assert_eq!(format_relative_date(now + 10, now), "in the future");
+    }
+}
@@ -1,7 +1,12 @@
+use std::cmp::max;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::thread;
+use std::time::Duration;
 
+use console::measure_text_width;
 use regex::Regex;
 use structopt::clap;
 use syntect::highlighting::Style as SyntectStyle;
@@ -14,24 +19,46 @@ use crate::color;
 use crate::delta::State;
 use crate::env;
 use crate::features::side_by_side;
+use crate::git_attributes::GitAttributes;
 use crate::git_config_entry::GitConfigEntry;
+use crate::glob::{self, Glob};
+use crate::parse;
 use crate::style::{self, Style};
 
 pub struct Config {
     pub available_terminal_width: usize,
     pub background_color_extends_to_terminal_width: bool,
+    pub commit_author_style: Style,
+    pub commit_branch_style: Style,
+    pub commit_date_style: Style,
+    pub commit_hash_length: usize,
+    pub commit_remote_branch_style: Style,
     pub commit_style: Style,
+    pub commit_subject_style: Style,
+    pub commit_tag_style: Style,
+    pub cwd_relative_to_repo_root: Option<String>,
     pub decorations_width: cli::Width,
+    pub diff_prefixes: Vec<String>,
+    pub exclude_globs: Vec<Glob>,
+    pub expand_generated_files: bool,
     pub file_added_label: String,
     pub file_modified_label: String,
     pub file_removed_label: String,
     pub file_renamed_label: String,
     pub file_style: Style,
+    pub git_attributes: Option<GitAttributes>,
     pub git_config_entries: HashMap<String, GitConfigEntry>,
     pub hunk_header_style: Style,
     pub hyperlinks: bool,
+    pub hyperlinks_commit_link_format: Option<String>,
     pub hyperlinks_file_link_format: String,
+    pub hyperlinks_forge: String,
+    pub hyperlinks_issue_links: Vec<(Regex, String)>,
+    pub hyperlinks_remote: String,
+    pub idle_flush_timeout: Duration,
+    pub include_globs: Vec<Glob>,
     pub inspect_raw_lines: cli::InspectRawLines,
+    pub json_syntax_scopes: bool,
     pub keep_plus_minus_markers: bool,
     pub line_numbers: bool,
     pub line_numbers_left_format: String,
@@ -41,28 +68,41 @@ pub struct Config {
     pub line_numbers_right_format: String,
     pub line_numbers_right_style: Style,
     pub line_numbers_zero_style: Style,
+    pub max_alignment_cells: usize,
     pub max_buffered_lines: usize,
     pub max_line_distance: f64,
     pub max_line_distance_for_naively_paired_lines: f64,
+    pub max_line_length: usize,
+    pub minus_emph_delimiters: (String, String),
     pub minus_emph_style: Style,
     pub minus_empty_line_marker_style: Style,
     pub minus_file: Option<PathBuf>,
+    pub minus_marker: String,
     pub minus_non_emph_style: Style,
     pub minus_style: Style,
+    pub monochrome: bool,
     pub navigate: bool,
     pub null_style: Style,
     pub null_syntect_style: SyntectStyle,
+    pub output_format: cli::OutputFormat,
     pub paging_mode: PagingMode,
+    pub plus_emph_delimiters: (String, String),
     pub plus_emph_style: Style,
     pub plus_empty_line_marker_style: Style,
     pub plus_file: Option<PathBuf>,
+    pub plus_marker: String,
     pub plus_non_emph_style: Style,
     pub plus_style: Style,
+    pub relative_dates: bool,
+    pub relative_paths: bool,
+    pub remote_names: Vec<String>,
     pub git_minus_style: Style,
     pub git_plus_style: Style,
     pub side_by_side: bool,
     pub side_by_side_data: side_by_side::SideBySideData,
+    pub summarize_hidden_files: bool,
     pub syntax_dummy_theme: SyntaxTheme,
+    pub syntax_map: Vec<(Glob, String)>,
     pub syntax_set: SyntaxSet,
     pub syntax_theme: Option<SyntaxTheme>,
     pub tab_width: usize,
@@ -70,6 +110,8 @@ pub struct Config {
     pub true_color: bool,
     pub truncation_symbol: String,
     pub whitespace_error_style: Style,
+    pub workers: usize,
+    pub zero_marker: Option<String>,
     pub zero_style: Style,
 }
 
@@ -86,8 +128,11 @@ impl Config {
     }
 }
 
-impl From<cli::Opt> for Config {
-    fn from(opt: cli::Opt) -> Self {
+impl TryFrom<cli::Opt> for Config {
+    type Error = String;
+
+    /// Fails with a message describing the first invalid option value.
+    fn try_from(opt: cli::Opt) -> Result<Self, String> {
         let (
             minus_style,
             minus_emph_style,
@@ -99,10 +144,19 @@ impl From<cli::Opt> for Config {
             plus_non_emph_style,
             plus_empty_line_marker_style,
             whitespace_error_style,
-        ) = make_hunk_styles(&opt);
+        ) = make_hunk_styles(&opt)?;
 
         let (commit_style, file_style, hunk_header_style) =
-            make_commit_file_hunk_header_styles(&opt);
+            make_commit_file_hunk_header_styles(&opt)?;
+
+        let (
+            commit_author_style,
+            commit_branch_style,
+            commit_date_style,
+            commit_remote_branch_style,
+            commit_subject_style,
+            commit_tag_style,
+        ) = make_commit_meta_styles(&opt)?;
 
         let (
             line_numbers_minus_style,
@@ -110,22 +164,56 @@ impl From<cli::Opt> for Config {
             line_numbers_plus_style,
             line_numbers_left_style,
             line_numbers_right_style,
-        ) = make_line_number_styles(&opt);
+        ) = make_line_number_styles(&opt)?;
 
         let max_line_distance_for_naively_paired_lines =
             env::get_env_var("DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES")
                 .map(|s| s.parse::<f64>().unwrap_or(0.0))
                 .unwrap_or(0.0);
 
-        let tokenization_regex = Regex::new(&opt.tokenization_regex).unwrap_or_else(|_| {
-            eprintln!(
+        let tokenization_regex = Regex::new(&opt.tokenization_regex).map_err(|_| {
+            format!(
                 "Invalid word-diff-regex: {}. \
                  The value must be a valid Rust regular expression. \
                  See https://docs.rs/regex.",
                 opt.tokenization_regex
-            );
-            process::exit(1);
-        });
+            )
+        })?;
+
+        let include_globs = make_globs(&opt.include, "include")?;
+        let exclude_globs = make_globs(&opt.exclude, "exclude")?;
+
+        let syntax_map = make_syntax_map(&opt.syntax_map, &opt.computed.syntax_set)?;
+
+        let git_attributes = match opt.git_config_entries.get("delta.__workdir__") {
+            Some(GitConfigEntry::Path(workdir)) => GitAttributes::try_create(workdir),
+            _ => None,
+        };
+
+        let hyperlinks_issue_links = make_hyperlinks_issue_links(
+            &opt.hyperlinks_issue_links,
+            opt.git_config_entries
+                .get(&format!("remote.{}.url", opt.hyperlinks_remote)),
+        )?;
+
+        // Used to distinguish remote-tracking branches in commit ref decorations.
+        let remote_names = match opt.git_config_entries.get("delta.__remotes__") {
+            Some(GitConfigEntry::String(names)) => {
+                names.split_whitespace().map(|s| s.to_string()).collect()
+            }
+            _ => vec!["origin".to_string(), "upstream".to_string()],
+        };
+
+        let diff_prefixes = make_diff_prefixes(&opt.git_config_entries);
+
+        let (minus_marker, zero_marker, plus_marker) = make_line_markers(&opt);
+
+        let cwd_relative_to_repo_root = match opt.git_config_entries.get("delta.__workdir__") {
+            Some(GitConfigEntry::Path(workdir)) if opt.relative_paths => {
+                get_cwd_relative_to_repo_root(workdir)
+            }
+            _ => None,
+        };
 
         let side_by_side_data = side_by_side::SideBySideData::new(
             &opt.computed.decorations_width,
@@ -133,31 +221,59 @@ impl From<cli::Opt> for Config {
         );
 
         let git_minus_style = match opt.git_config_entries.get("color.diff.old") {
-            Some(GitConfigEntry::Style(s)) => Style::from_git_str(s),
+            Some(GitConfigEntry::Style(s)) => Style::from_git_str(s)?,
             _ => *style::GIT_DEFAULT_MINUS_STYLE,
         };
         let git_plus_style = match opt.git_config_entries.get("color.diff.new") {
-            Some(GitConfigEntry::Style(s)) => Style::from_git_str(s),
+            Some(GitConfigEntry::Style(s)) => Style::from_git_str(s)?,
             _ => *style::GIT_DEFAULT_PLUS_STYLE,
         };
 
-        Self {
+        let workers = match opt.workers {
+            0 => thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            n => n,
+        };
+
+        let mut config = Self {
             available_terminal_width: opt.computed.available_terminal_width,
             background_color_extends_to_terminal_width: opt
                 .computed
                 .background_color_extends_to_terminal_width,
+            commit_author_style,
+            commit_branch_style,
+            commit_date_style,
+            commit_hash_length: opt.commit_hash_length,
+            commit_remote_branch_style,
             commit_style,
+            commit_subject_style,
+            commit_tag_style,
+            cwd_relative_to_repo_root,
             decorations_width: opt.computed.decorations_width,
+            diff_prefixes,
+            exclude_globs,
+            expand_generated_files: opt.expand_generated_files,
             file_added_label: opt.file_added_label,
             file_modified_label: opt.file_modified_label,
             file_removed_label: opt.file_removed_label,
             file_renamed_label: opt.file_renamed_label,
             file_style,
+            git_attributes,
             git_config_entries: opt.git_config_entries,
             hunk_header_style,
             hyperlinks: opt.hyperlinks,
+            hyperlinks_commit_link_format: opt.hyperlinks_commit_link_format,
             hyperlinks_file_link_format: opt.hyperlinks_file_link_format,
+            hyperlinks_forge: opt.hyperlinks_forge,
+            hyperlinks_issue_links,
+            hyperlinks_remote: opt.hyperlinks_remote,
+            // When no input has arrived for this long, the output painted so far is written, so
+            // that a slow producer's output appears as it is produced.
+            idle_flush_timeout: Duration::from_millis(100),
+            include_globs,
             inspect_raw_lines: opt.computed.inspect_raw_lines,
+            json_syntax_scopes: opt.json_syntax_scopes,
             keep_plus_minus_markers: opt.keep_plus_minus_markers,
             line_numbers: opt.line_numbers,
             line_numbers_left_format: opt.line_numbers_left_format,
@@ -167,28 +283,49 @@ impl From<cli::Opt> for Config {
             line_numbers_right_format: opt.line_numbers_right_format,
             line_numbers_right_style,
             line_numbers_zero_style,
+            // Within-line edits are not inferred for the rest of a block of changed lines once
+            // the alignment tables computed for it have this many cells in total.
+            max_alignment_cells: 16_000_000,
             max_buffered_lines: 32,
             max_line_distance: opt.max_line_distance,
             max_line_distance_for_naively_paired_lines,
+            max_line_length: opt.max_line_length,
+            minus_emph_delimiters: parse_emph_delimiters(
+                &opt.minus_emph_delimiters,
+                "minus-emph-delimiters",
+            )?,
             minus_emph_style,
             minus_empty_line_marker_style,
             minus_file: opt.minus_file.map(|s| s.clone()),
+            minus_marker,
             minus_non_emph_style,
             minus_style,
+            monochrome: opt.computed.monochrome,
             navigate: opt.navigate,
             null_style: Style::new(),
             null_syntect_style: SyntectStyle::default(),
+            output_format: opt.computed.output_format,
             paging_mode: opt.computed.paging_mode,
+            plus_emph_delimiters: parse_emph_delimiters(
+                &opt.plus_emph_delimiters,
+                "plus-emph-delimiters",
+            )?,
             plus_emph_style,
             plus_empty_line_marker_style,
             plus_file: opt.plus_file.map(|s| s.clone()),
+            plus_marker,
             plus_non_emph_style,
             plus_style,
+            relative_dates: opt.relative_dates,
+            relative_paths: opt.relative_paths,
+            remote_names,
             git_minus_style,
             git_plus_style,
             side_by_side: opt.side_by_side,
             side_by_side_data,
+            summarize_hidden_files: opt.summarize_hidden_files,
             syntax_dummy_theme: SyntaxTheme::default(),
+            syntax_map,
             syntax_set: opt.computed.syntax_set,
             syntax_theme: opt.computed.syntax_theme,
             tab_width: opt.tab_width,
@@ -196,54 +333,151 @@ impl From<cli::Opt> for Config {
             true_color: opt.computed.true_color,
             truncation_symbol: "→".to_string(),
             whitespace_error_style,
+            workers,
+            zero_marker,
             zero_style,
+        };
+        if config.monochrome {
+            make_monochrome(&mut config);
         }
+        Ok(config)
     }
 }
 
-fn make_hunk_styles<'a>(
-    opt: &'a cli::Opt,
+/// Remove colors and other styling from all styles used to paint output. (git_minus_style and
+/// git_plus_style are used only to recognize the colors of input lines.)
+fn make_monochrome(config: &mut Config) {
+    for style in &mut [
+        &mut config.commit_author_style,
+        &mut config.commit_branch_style,
+        &mut config.commit_date_style,
+        &mut config.commit_remote_branch_style,
+        &mut config.commit_style,
+        &mut config.commit_subject_style,
+        &mut config.commit_tag_style,
+        &mut config.file_style,
+        &mut config.hunk_header_style,
+        &mut config.line_numbers_left_style,
+        &mut config.line_numbers_minus_style,
+        &mut config.line_numbers_plus_style,
+        &mut config.line_numbers_right_style,
+        &mut config.line_numbers_zero_style,
+        &mut config.minus_emph_style,
+        &mut config.minus_empty_line_marker_style,
+        &mut config.minus_non_emph_style,
+        &mut config.minus_style,
+        &mut config.plus_emph_style,
+        &mut config.plus_empty_line_marker_style,
+        &mut config.plus_non_emph_style,
+        &mut config.plus_style,
+        &mut config.whitespace_error_style,
+        &mut config.zero_style,
+    ] {
+        **style = style.to_monochrome();
+    }
+    config.syntax_theme = None;
+    // Hyperlinks are escape sequences too.
+    config.hyperlinks = false;
+}
+
+/// Parse a value of --minus-emph-delimiters or --plus-emph-delimiters, e.g. "[-..-]". An empty
+/// value means no delimiters.
+fn parse_emph_delimiters(delimiters: &str, option_name: &str) -> Result<(String, String), String> {
+    if delimiters.is_empty() {
+        return Ok(("".to_string(), "".to_string()));
+    }
+    match delimiters.find("..") {
+        Some(i) => Ok((
+            delimiters[..i].to_string(),
+            delimiters[i + "..".len()..].to_string(),
+        )),
+        None => Err(format!(
+                "Invalid value for --{} option: {} (the opening and closing delimiters must be separated by \"..\", e.g. \"[-..-]\")",
+            option_name, delimiters
+        )),
+    }
+}
+
+/// Return the default (minus, minus-emph, plus, plus-emph) background colors.
+fn get_background_color_defaults(
+    opt: &cli::Opt,
 ) -> (
-    Style,
-    Style,
-    Style,
-    Style,
-    Style,
-    Style,
-    Style,
-    Style,
-    Style,
-    Style,
+    ansi_term::Color,
+    ansi_term::Color,
+    ansi_term::Color,
+    ansi_term::Color,
 ) {
     let is_light_mode = opt.computed.is_light_mode;
     let true_color = opt.computed.true_color;
+    let theme_background = opt
+        .computed
+        .syntax_theme
+        .as_ref()
+        .and_then(|theme| theme.settings.background);
+    match theme_background {
+        Some(theme_background) if opt.syntax_theme_backgrounds => {
+            color::get_background_color_defaults_from_syntax_theme(
+                theme_background,
+                opt.syntax_theme_background_strength,
+                opt.syntax_theme_background_emph_strength,
+                opt.syntax_theme_background_min_contrast,
+                true_color,
+            )
+        }
+        _ => (
+            color::get_minus_background_color_default(is_light_mode, true_color),
+            color::get_minus_emph_background_color_default(is_light_mode, true_color),
+            color::get_plus_background_color_default(is_light_mode, true_color),
+            color::get_plus_emph_background_color_default(is_light_mode, true_color),
+        ),
+    }
+}
+
+fn make_hunk_styles<'a>(
+    opt: &'a cli::Opt,
+) -> Result<
+    (
+        Style,
+        Style,
+        Style,
+        Style,
+        Style,
+        Style,
+        Style,
+        Style,
+        Style,
+        Style,
+    ),
+    String,
+> {
+    let true_color = opt.computed.true_color;
+    let (
+        minus_background_color_default,
+        minus_emph_background_color_default,
+        plus_background_color_default,
+        plus_emph_background_color_default,
+    ) = get_background_color_defaults(opt);
     let minus_style = Style::from_str(
         &opt.minus_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_minus_background_color_default(
-                is_light_mode,
-                true_color,
-            )),
+            Some(minus_background_color_default),
         )),
         None,
         true_color,
         false,
-    );
+    )?;
 
     let minus_emph_style = Style::from_str(
         &opt.minus_emph_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_minus_emph_background_color_default(
-                is_light_mode,
-                true_color,
-            )),
+            Some(minus_emph_background_color_default),
         )),
         None,
         true_color,
         true,
-    );
+    )?;
 
     let minus_non_emph_style = Style::from_str(
         &opt.minus_non_emph_style,
@@ -251,7 +485,7 @@ fn make_hunk_styles<'a>(
         None,
         true_color,
         false,
-    );
+    )?;
 
     // The style used to highlight a removed empty line when otherwise it would be invisible due to
     // lack of background color in minus-style.
@@ -259,45 +493,36 @@ fn make_hunk_styles<'a>(
         &opt.minus_empty_line_marker_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_minus_background_color_default(
-                is_light_mode,
-                true_color,
-            )),
+            Some(minus_background_color_default),
         )),
         None,
         true_color,
         false,
-    );
+    )?;
 
-    let zero_style = Style::from_str(&opt.zero_style, None, None, true_color, false);
+    let zero_style = Style::from_str(&opt.zero_style, None, None, true_color, false)?;
 
     let plus_style = Style::from_str(
         &opt.plus_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_plus_background_color_default(
-                is_light_mode,
-                true_color,
-            )),
+            Some(plus_background_color_default),
         )),
         None,
         true_color,
         false,
-    );
+    )?;
 
     let plus_emph_style = Style::from_str(
         &opt.plus_emph_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_plus_emph_background_color_default(
-                is_light_mode,
-                true_color,
-            )),
+            Some(plus_emph_background_color_default),
         )),
         None,
         true_color,
         true,
-    );
+    )?;
 
     let plus_non_emph_style = Style::from_str(
         &opt.plus_non_emph_style,
@@ -305,7 +530,7 @@ fn make_hunk_styles<'a>(
         None,
         true_color,
         false,
-    );
+    )?;
 
     // The style used to highlight an added empty line when otherwise it would be invisible due to
     // lack of background color in plus-style.
@@ -313,20 +538,17 @@ fn make_hunk_styles<'a>(
         &opt.plus_empty_line_marker_style,
         Some(Style::from_colors(
             None,
-            Some(color::get_plus_background_color_default(
-                is_light_mode,
-                true_color,
-            )),
+            Some(plus_background_color_default),
         )),
         None,
         true_color,
         false,
-    );
+    )?;
 
     let whitespace_error_style =
-        Style::from_str(&opt.whitespace_error_style, None, None, true_color, false);
+        Style::from_str(&opt.whitespace_error_style, None, None, true_color, false)?;
 
-    (
+    Ok((
         minus_style,
         minus_emph_style,
         minus_non_emph_style,
@@ -337,38 +559,87 @@ fn make_hunk_styles<'a>(
         plus_non_emph_style,
         plus_empty_line_marker_style,
         whitespace_error_style,
-    )
+    ))
 }
 
-fn make_line_number_styles<'a>(opt: &'a cli::Opt) -> (Style, Style, Style, Style, Style) {
+fn make_line_number_styles<'a>(
+    opt: &'a cli::Opt,
+) -> Result<(Style, Style, Style, Style, Style), String> {
     let true_color = opt.computed.true_color;
     let line_numbers_left_style =
-        Style::from_str(&opt.line_numbers_left_style, None, None, true_color, false);
+        Style::from_str(&opt.line_numbers_left_style, None, None, true_color, false)?;
 
     let line_numbers_minus_style =
-        Style::from_str(&opt.line_numbers_minus_style, None, None, true_color, false);
+        Style::from_str(&opt.line_numbers_minus_style, None, None, true_color, false)?;
 
     let line_numbers_zero_style =
-        Style::from_str(&opt.line_numbers_zero_style, None, None, true_color, false);
+        Style::from_str(&opt.line_numbers_zero_style, None, None, true_color, false)?;
 
     let line_numbers_plus_style =
-        Style::from_str(&opt.line_numbers_plus_style, None, None, true_color, false);
+        Style::from_str(&opt.line_numbers_plus_style, None, None, true_color, false)?;
 
     let line_numbers_right_style =
-        Style::from_str(&opt.line_numbers_right_style, None, None, true_color, false);
+        Style::from_str(&opt.line_numbers_right_style, None, None, true_color, false)?;
 
-    (
+    Ok((
         line_numbers_minus_style,
         line_numbers_zero_style,
         line_numbers_plus_style,
         line_numbers_left_style,
         line_numbers_right_style,
-    )
+    ))
 }
 
-fn make_commit_file_hunk_header_styles(opt: &cli::Opt) -> (Style, Style, Style) {
+/// Return the text displayed before minus, zero, and plus lines. --minus-marker and --plus-marker
+/// take precedence over the markers of --keep-plus-minus-markers; when either is in use, the
+/// markers are padded to a common width, and zero lines are prefixed with that many spaces. A zero
+/// marker of None means that zero lines keep their raw first character as before.
+fn make_line_markers(opt: &cli::Opt) -> (String, Option<String>, String) {
+    let marker = |custom_marker: &str, git_marker: &str| {
+        if !custom_marker.is_empty() {
+            custom_marker.to_string()
+        } else if opt.keep_plus_minus_markers {
+            git_marker.to_string()
+        } else {
+            "".to_string()
+        }
+    };
+    let (minus_marker, plus_marker) = (
+        marker(&opt.minus_marker, "-"),
+        marker(&opt.plus_marker, "+"),
+    );
+    if opt.minus_marker.is_empty() && opt.plus_marker.is_empty() {
+        return (minus_marker, None, plus_marker);
+    }
+    let width = max(
+        measure_text_width(&minus_marker),
+        measure_text_width(&plus_marker),
+    );
+    let pad = |marker: String| {
+        let padding = " ".repeat(width - measure_text_width(&marker));
+        marker + &padding
+    };
+    (pad(minus_marker), Some(" ".repeat(width)), pad(plus_marker))
+}
+
+fn make_commit_meta_styles(
+    opt: &cli::Opt,
+) -> Result<(Style, Style, Style, Style, Style, Style), String> {
     let true_color = opt.computed.true_color;
-    (
+    let make_style = |style_string| Style::from_str(style_string, None, None, true_color, false);
+    Ok((
+        make_style(&opt.commit_author_style)?,
+        make_style(&opt.commit_branch_style)?,
+        make_style(&opt.commit_date_style)?,
+        make_style(&opt.commit_remote_branch_style)?,
+        make_style(&opt.commit_subject_style)?,
+        make_style(&opt.commit_tag_style)?,
+    ))
+}
+
+fn make_commit_file_hunk_header_styles(opt: &cli::Opt) -> Result<(Style, Style, Style), String> {
+    let true_color = opt.computed.true_color;
+    Ok((
         Style::from_str_with_handling_of_special_decoration_attributes_and_respecting_deprecated_foreground_color_arg(
             &opt.commit_style,
             None,
@@ -376,7 +647,7 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt) -> (Style, Style, Style)
             opt.deprecated_commit_color.as_deref(),
             true_color,
             false,
-        ),
+        )?,
         Style::from_str_with_handling_of_special_decoration_attributes_and_respecting_deprecated_foreground_color_arg(
             &opt.file_style,
             None,
@@ -384,7 +655,7 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt) -> (Style, Style, Style)
             opt.deprecated_file_color.as_deref(),
             true_color,
             false,
-        ),
+        )?,
         Style::from_str_with_handling_of_special_decoration_attributes_and_respecting_deprecated_foreground_color_arg(
             &opt.hunk_header_style,
             None,
@@ -392,10 +663,123 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt) -> (Style, Style, Style)
             opt.deprecated_hunk_color.as_deref(),
             true_color,
             false,
-        ),
+        )?,
+    ))
+}
+
+fn make_globs(patterns: &str, option_name: &str) -> Result<Vec<Glob>, String> {
+    glob::parse_globs(patterns)
+        .map_err(|err| format!("Invalid value for --{} option: {}", option_name, err))
+}
+
+/// Parse the --hyperlinks-issue-links rules, and append the default rules for the forge hosting
+/// the git remote.
+fn make_hyperlinks_issue_links(
+    rules: &str,
+    git_remote: Option<&GitConfigEntry>,
+) -> Result<Vec<(Regex, String)>, String> {
+    let mut rules: Vec<(String, String)> = rules
+        .split_whitespace()
+        .map(|entry| match entry.find('=') {
+            Some(i) => Ok((entry[..i].to_string(), entry[i + 1..].to_string())),
+            None => Err(format!(
+                "Invalid value for --hyperlinks-issue-links option: {}. \
+                 Entries must be of the form REGEX=URL.",
+                entry
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    if let Some(GitConfigEntry::GitRemote(repo)) = git_remote {
+        rules.extend(repo.issue_link_rules());
+    }
+    rules
+        .into_iter()
+        .map(|(regex, url)| {
+            let regex = Regex::new(&regex).map_err(|_| {
+                format!(
+                    "Invalid regular expression in --hyperlinks-issue-links option: {}. \
+                     See https://docs.rs/regex.",
+                    regex
+                )
+            })?;
+            Ok((regex, url))
+        })
+        .collect()
+}
+
+/// Return the prefixes that git places in front of file paths in "---" and "+++" lines, according
+/// to the diff.noprefix, diff.mnemonicPrefix, diff.srcPrefix, and diff.dstPrefix git config
+/// settings.
+fn make_diff_prefixes(git_config_entries: &HashMap<String, GitConfigEntry>) -> Vec<String> {
+    let is_set = |key| match git_config_entries.get(key) {
+        Some(GitConfigEntry::Bool(value)) => *value,
+        _ => false,
+    };
+    if is_set("diff.noprefix") {
+        return Vec::new();
+    }
+    let mut prefixes: Vec<String> = parse::DIFF_PREFIXES.iter().map(|s| s.to_string()).collect();
+    if is_set("diff.mnemonicprefix") {
+        // Used by `git diff --no-index`.
+        prefixes.extend(vec!["1/".to_string(), "2/".to_string()]);
+    }
+    for key in &["diff.srcprefix", "diff.dstprefix"] {
+        if let Some(GitConfigEntry::String(prefix)) = git_config_entries.get(*key) {
+            if !prefix.is_empty() && !prefixes.contains(prefix) {
+                prefixes.push(prefix.clone());
+            }
+        }
+    }
+    prefixes
+}
+
+/// Return the current directory relative to the repository root, using '/' as separator, or None
+/// if the current directory is not inside the repository.
+fn get_cwd_relative_to_repo_root(workdir: &Path) -> Option<String> {
+    let workdir = workdir.canonicalize().ok()?;
+    let cwd = std::env::current_dir().ok()?.canonicalize().ok()?;
+    let relative = cwd.strip_prefix(workdir).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
     )
 }
 
+/// Parse the --syntax-map entries into (glob, syntax name) pairs.
+fn make_syntax_map(
+    syntax_map: &str,
+    syntax_set: &SyntaxSet,
+) -> Result<Vec<(Glob, String)>, String> {
+    syntax_map
+        .split_whitespace()
+        .map(|entry| {
+            let (pattern, language) = match entry.rfind('=') {
+                Some(i) => (&entry[..i], &entry[i + 1..]),
+                None => {
+                    return Err(format!(
+                        "Invalid value for --syntax-map option: {}. \
+                         Entries must be of the form GLOB=LANGUAGE.",
+                        entry
+                    ));
+                }
+            };
+            let glob = Glob::new(pattern)
+                .map_err(|err| format!("Invalid value for --syntax-map option: {}", err))?;
+            match syntax_set.find_syntax_by_token(language) {
+                Some(syntax) => Ok((glob, syntax.name.clone())),
+                None => Err(format!(
+                    "Invalid value for --syntax-map option: unknown language: {}. \
+                     Use --list-languages to see available languages.",
+                    language
+                )),
+            }
+        })
+        .collect()
+}
+
 /// Did the user supply `option` on the command line?
 pub fn user_supplied_option(option: &str, arg_matches: &clap::ArgMatches) -> bool {
     arg_matches.occurrences_of(option) > 0
@@ -409,3 +793,38 @@ pub fn delta_unreachable(message: &str) -> ! {
     );
     process::exit(1);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_default_options() {
+        // E.g. --show-syntax-themes uses such a config to set up its pager.
+        let config = Config::try_from(cli::Opt::default()).unwrap();
+        assert_eq!(
+            config.minus_emph_delimiters,
+            ("".to_string(), "".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_emph_delimiters() {
+        assert_eq!(
+            parse_emph_delimiters("[-..-]", "minus-emph-delimiters"),
+            Ok(("[-".to_string(), "-]".to_string()))
+        );
+        assert_eq!(
+            parse_emph_delimiters("", "minus-emph-delimiters"),
+            Ok(("".to_string(), "".to_string()))
+        );
+        assert_eq!(
+            parse_emph_delimiters("[-", "minus-emph-delimiters"),
+            Err(
+                "Invalid value for --minus-emph-delimiters option: [- (the opening and closing \
+                 delimiters must be separated by \"..\", e.g. \"[-..-]\")"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -1,19 +1,25 @@
 use std::borrow::Cow;
 use std::io::BufRead;
 use std::io::Write;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 
 use bytelines::ByteLines;
 use console::strip_ansi_codes;
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::cli;
+use crate::commit_meta::{self, CommitMetaState};
 use crate::config::Config;
 use crate::draw;
+use crate::events::{Event, Events, FileChange, LineKind, Source};
 use crate::features;
 use crate::format;
 use crate::paint::Painter;
+use crate::parallel;
 use crate::parse;
-use crate::style::{self, DecorationStyle};
+use crate::style::{self, DecorationStyle, Style};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum State {
@@ -26,22 +32,6 @@ pub enum State {
     Unknown,
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Source {
-    GitDiff,     // Coming from a `git diff` command
-    DiffUnified, // Coming from a `diff -u` command
-    Unknown,
-}
-
-impl State {
-    fn is_in_hunk(&self) -> bool {
-        match *self {
-            State::HunkHeader | State::HunkZero | State::HunkMinus(_) | State::HunkPlus(_) => true,
-            _ => false,
-        }
-    }
-}
-
 // Possible transitions, with actions on entry:
 //
 //
@@ -54,136 +44,464 @@ impl State {
 // | HunkMinus   | flush, emit | flush, emit | flush, emit | flush, emit | push        | push     |
 // | HunkPlus    | flush, emit | flush, emit | flush, emit | flush, emit | flush, push | push     |
 
-pub fn delta<I>(
-    mut lines: ByteLines<I>,
-    writer: &mut dyn Write,
-    config: &Config,
-) -> std::io::Result<()>
+/// The number of events that may be read ahead of the painting of the diff.
+const MAX_READ_AHEAD_EVENTS: usize = 1024;
+
+pub fn delta<I>(lines: ByteLines<I>, writer: &mut dyn Write, config: &Config) -> std::io::Result<()>
 where
-    I: BufRead,
+    I: BufRead + Send,
 {
-    let mut painter = Painter::new(writer, config);
-    let mut minus_file = "".to_string();
-    let mut plus_file = "".to_string();
-    let mut state = State::Unknown;
-    let mut source = Source::Unknown;
-
-    // When a file is modified, we use lines starting with '---' or '+++' to obtain the file name.
-    // When a file is renamed without changes, we use lines starting with 'rename' to obtain the
-    // file name (there is no diff hunk and hence no lines starting with '---' or '+++'). But when
-    // a file is renamed with changes, both are present, and we rely on the following variables to
-    // avoid emitting the file meta header line twice (#245).
-    let mut current_file_pair;
-    let mut handled_file_meta_header_line_file_pair = None;
-
-    while let Some(Ok(raw_line_bytes)) = lines.next() {
-        let raw_line = String::from_utf8_lossy(&raw_line_bytes);
-        let line = strip_ansi_codes(&raw_line).to_string();
-        if source == Source::Unknown {
-            source = detect_source(&line);
+    let mut events = Events::new(lines, config);
+    let (event_sender, event_receiver) = mpsc::sync_channel(MAX_READ_AHEAD_EVENTS);
+    // The diff is read on its own thread, so that the output painted so far can be written while
+    // waiting for a slow producer, such as `git log -p -S`.
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            while let Some(event) = events.next() {
+                // An error means that the output has stopped being written.
+                if event_sender.send((event, *events.source())).is_err() {
+                    return;
+                }
+            }
+        });
+        let inputs = Inputs {
+            events: event_receiver,
+            idle_timeout: config.idle_flush_timeout,
+            is_idle: false,
+        };
+        if config.workers > 1 {
+            parallel::delta_in_parallel(inputs, writer, config)
+        } else {
+            delta_sequentially(inputs, writer, config)
         }
-        if line.starts_with("commit ") {
-            painter.paint_buffered_minus_and_plus_lines();
-            state = State::CommitMeta;
-            if should_handle(&state, config) {
-                painter.emit()?;
-                handle_commit_meta_header_line(&mut painter, &line, &raw_line, config)?;
+    })
+}
+
+pub enum Input {
+    /// An event, with the source of the diff as known when it was read.
+    Event(Event, Source),
+    /// No event has been read for `config.idle_flush_timeout`: the output painted so far should be
+    /// written.
+    Idle,
+}
+
+/// The events read by the reader thread, each pause in which is marked by a single `Input::Idle`.
+pub struct Inputs {
+    events: Receiver<(Event, Source)>,
+    idle_timeout: Duration,
+    is_idle: bool,
+}
+
+impl Iterator for Inputs {
+    type Item = Input;
+
+    fn next(&mut self) -> Option<Input> {
+        let event = if self.is_idle {
+            self.events
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            self.events.recv_timeout(self.idle_timeout)
+        };
+        match event {
+            Ok((event, source)) => {
+                self.is_idle = false;
+                Some(Input::Event(event, source))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                self.is_idle = true;
+                Some(Input::Idle)
+            }
+            Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+}
+
+/// Paint the file sections of the diff one after another.
+fn delta_sequentially(
+    inputs: Inputs,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<()> {
+    let mut hidden_files = HiddenFiles::default();
+    let mut section: Option<SectionPainter> = None;
+    for input in inputs {
+        let (event, source) = match input {
+            Input::Event(event, source) => (event, source),
+            Input::Idle => {
+                if let Some(section) = section.as_mut() {
+                    section.flush()?;
+                }
                 continue;
             }
-        } else if line.starts_with("diff ") {
-            painter.paint_buffered_minus_and_plus_lines();
-            state = State::FileMeta;
-            handled_file_meta_header_line_file_pair = None;
-        } else if (state == State::FileMeta || source == Source::DiffUnified)
-            && (line.starts_with("--- ") || line.starts_with("rename from "))
-        {
-            minus_file = parse::get_file_path_from_file_meta_line(&line, source == Source::GitDiff);
-            if source == Source::DiffUnified {
-                state = State::FileMeta;
-                painter.set_syntax(parse::get_file_extension_from_marker_line(&line));
-            } else {
-                painter.set_syntax(parse::get_file_extension_from_file_meta_line_file_path(
-                    &minus_file,
-                ));
+        };
+        if section.is_none() || is_start_of_file_section(event.line(), &source) {
+            if let Some(section) = section.take() {
+                hidden_files.add(section.finish()?);
             }
-        } else if (state == State::FileMeta || source == Source::DiffUnified)
-            && (line.starts_with("+++ ") || line.starts_with("rename to "))
+            hidden_files.write(is_commit_header(&event), writer, config)?;
+            section = Some(SectionPainter::new(writer, source, config));
+        }
+        if let Some(section) = section.as_mut() {
+            section.handle_event(event)?;
+        }
+    }
+    if let Some(section) = section {
+        hidden_files.add(section.finish()?);
+    }
+    hidden_files.write(true, writer, config)
+}
+
+/// Paints a file section: the events from the start of a commit or file up to the start of the
+/// next (see `is_start_of_file_section`). Each section is painted by its own Painter, so that the
+/// sections of a diff may be painted in parallel.
+pub struct SectionPainter<'a> {
+    painter: Painter<'a>,
+    config: &'a Config,
+    source: Source,
+    plus_file: String,
+    state: State,
+    commit_meta_state: CommitMetaState,
+    // Once the file has been hidden, the rest of the section is skipped.
+    hidden_file: Option<HiddenFile>,
+    // The numbers of lines of the current hunk, in the minus and plus files, not yet read. This is
+    // not known for the hunks of merge commits.
+    n_hunk_lines_remaining: Option<(usize, usize)>,
+    // The events of the first hunk of a file whose syntax is to be inferred from its contents,
+    // held until the hunk is complete (see `Painter::infer_syntax_from_hunk`).
+    held_events: Option<Vec<Event>>,
+}
+
+impl<'a> SectionPainter<'a> {
+    pub fn new(writer: &'a mut dyn Write, source: Source, config: &'a Config) -> Self {
+        Self {
+            painter: Painter::new(writer, config),
+            config,
+            source,
+            plus_file: "".to_string(),
+            state: State::Unknown,
+            commit_meta_state: CommitMetaState::new(),
+            hidden_file: None,
+            n_hunk_lines_remaining: None,
+            held_events: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> std::io::Result<()> {
+        if self.held_events.is_some() {
+            if let Event::Line { kind, .. } = event {
+                self.count_hunk_line(kind);
+                let held_events = self.held_events.as_mut().unwrap();
+                held_events.push(event);
+                if self.n_hunk_lines_remaining == Some((0, 0))
+                    || held_events.len() > self.config.max_buffered_lines
+                {
+                    self.paint_held_events()?;
+                }
+                return Ok(());
+            }
+            self.paint_held_events()?;
+        } else if let Event::HunkHeader {
+            line_numbers_and_hunk_lengths,
+            ..
+        } = &event
         {
-            plus_file = parse::get_file_path_from_file_meta_line(&line, source == Source::GitDiff);
-            painter.set_syntax(parse::get_file_extension_from_file_meta_line_file_path(
-                &plus_file,
-            ));
-            current_file_pair = Some((minus_file.clone(), plus_file.clone()));
-            if should_handle(&State::FileMeta, config)
-                && handled_file_meta_header_line_file_pair != current_file_pair
-            {
+            if self.painter.infer_syntax_from_contents && self.hidden_file.is_none() {
+                self.set_hunk_lengths(line_numbers_and_hunk_lengths);
+                self.held_events = Some(vec![event]);
+                return Ok(());
+            }
+        }
+        self.paint_event(event)
+    }
+
+    /// Infer the syntax of the file from the held hunk, and then paint it.
+    fn paint_held_events(&mut self) -> std::io::Result<()> {
+        if let Some(events) = self.held_events.take() {
+            let starts_at_first_line = match events.first() {
+                Some(Event::HunkHeader {
+                    line_numbers_and_hunk_lengths,
+                    ..
+                }) => line_numbers_and_hunk_lengths.iter().any(|(n, _)| *n <= 1),
+                _ => false,
+            };
+            let lines: Vec<&str> = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Line { kind, line, .. } if *kind != LineKind::Other => {
+                        Some(line.as_str())
+                    }
+                    _ => None,
+                })
+                .collect();
+            self.painter.infer_syntax_from_hunk(
+                &lines,
+                self.n_hunk_lines_remaining == Some((0, 0)),
+                starts_at_first_line,
+            );
+            for event in events {
+                self.paint_event(event)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_hunk_lengths(&mut self, line_numbers_and_hunk_lengths: &[(usize, usize)]) {
+        self.n_hunk_lines_remaining = match line_numbers_and_hunk_lengths[..] {
+            [(_, n_minus_lines), (_, n_plus_lines)] => Some((n_minus_lines, n_plus_lines)),
+            _ => None,
+        };
+    }
+
+    fn count_hunk_line(&mut self, kind: LineKind) {
+        if let Some((n_minus_lines, n_plus_lines)) = self.n_hunk_lines_remaining.as_mut() {
+            if kind == LineKind::Minus || kind == LineKind::Zero {
+                *n_minus_lines = n_minus_lines.saturating_sub(1);
+            }
+            if kind == LineKind::Plus || kind == LineKind::Zero {
+                *n_plus_lines = n_plus_lines.saturating_sub(1);
+            }
+        }
+    }
+
+    fn paint_event(&mut self, event: Event) -> std::io::Result<()> {
+        let config = self.config;
+        let painter = &mut self.painter;
+        if let Some(hidden_file) = self.hidden_file.as_mut() {
+            if let HiddenFile::Collapsed(collapsed_file) = hidden_file {
+                collapsed_file.count_event(&event);
+            }
+            return Ok(());
+        }
+        if let Event::Line { .. } = event {
+        } else {
+            painter.paint_buffered_minus_and_plus_lines();
+        }
+        match event {
+            Event::CommitHeader { line, raw_line } => {
+                painter.emit()?;
+                if should_handle(&State::CommitMeta, config) {
+                    handle_commit_meta_header_line(painter, &line, &raw_line, config)?;
+                } else {
+                    write_raw_commit_meta_line(
+                        painter,
+                        &line,
+                        &raw_line,
+                        &mut self.commit_meta_state,
+                        config,
+                    )?;
+                }
+            }
+            Event::CommitMeta { line, raw_line } => {
                 painter.emit()?;
-                handle_file_meta_header_line(
-                    &mut painter,
-                    &minus_file,
-                    &plus_file,
+                write_raw_commit_meta_line(
+                    painter,
+                    &line,
+                    &raw_line,
+                    &mut self.commit_meta_state,
                     config,
-                    source == Source::DiffUnified,
                 )?;
-                handled_file_meta_header_line_file_pair = current_file_pair
             }
-        } else if line.starts_with("@@") {
-            painter.paint_buffered_minus_and_plus_lines();
-            state = State::HunkHeader;
-            painter.set_highlighter();
-            if should_handle(&state, config) {
+            Event::FileMeta { line, raw_line } => {
+                if self.source == Source::DiffUnified && line.starts_with("--- ") {
+                    painter.set_syntax(parse::get_file_extension_from_marker_line(&line));
+                }
+                // The file metadata section is 4 lines. Skip them under non-plain file-styles.
+                if !should_handle(&State::FileMeta, config) {
+                    painter.emit()?;
+                    write_raw_line(painter, &raw_line, config)?;
+                }
+            }
+            Event::FileHeader {
+                minus_file,
+                plus_file,
+                change,
+                raw_line,
+                ..
+            } => {
+                let comparing = self.source == Source::DiffUnified;
+                if is_hidden_file(&minus_file, &plus_file, config) {
+                    painter.emit()?;
+                    self.hidden_file = Some(HiddenFile::Excluded);
+                    return Ok(());
+                }
+                if self.collapse_if_generated(&minus_file, &plus_file, change)? {
+                    return Ok(());
+                }
+                let painter = &mut self.painter;
+                painter.set_syntax_for_file(&plus_file);
                 painter.emit()?;
-                handle_hunk_header_line(&mut painter, &line, &raw_line, &plus_file, config)?;
-                continue;
+                if should_handle(&State::FileMeta, config) {
+                    handle_file_meta_header_line(
+                        painter,
+                        &minus_file,
+                        &plus_file,
+                        change,
+                        config,
+                        comparing,
+                    )?;
+                } else {
+                    write_raw_line(painter, &raw_line, config)?;
+                }
+                self.plus_file = plus_file;
             }
-        } else if source == Source::DiffUnified && line.starts_with("Only in ")
-            || line.starts_with("Submodule ")
-            || line.starts_with("Binary files ")
-        {
-            // Additional FileMeta cases:
-            //
-            // 1. When comparing directories with diff -u, if filenames match between the
-            //    directories, the files themselves will be compared. However, if an equivalent
-            //    filename is not present, diff outputs a single line (Only in...) starting
-            //    indicating that the file is present in only one of the directories.
-            //
-            // 2. Git diff emits lines describing submodule state such as "Submodule x/y/z contains
-            //    untracked content"
-            //
-            // See https://github.com/dandavison/delta/issues/60#issuecomment-557485242 for a
-            // proposal for more robust parsing logic.
-
-            painter.paint_buffered_minus_and_plus_lines();
-            state = State::FileMeta;
-            if should_handle(&State::FileMeta, config) {
+            Event::GenericFileHeader { line, raw_line } => {
+                // The diff of a file marked as not to be diffed (-diff) is a "Binary files" line,
+                // with no "---" and "+++" lines.
+                if self.source == Source::GitDiff {
+                    if let Some((minus_file, plus_file)) =
+                        parse::get_file_paths_from_binary_files_line(&line, &config.diff_prefixes)
+                    {
+                        let change = FileChange::new(&minus_file, &plus_file);
+                        if self.collapse_if_generated(&minus_file, &plus_file, change)? {
+                            return Ok(());
+                        }
+                    }
+                }
+                let painter = &mut self.painter;
+                painter.emit()?;
+                if should_handle(&State::FileMeta, config) {
+                    handle_generic_file_meta_header_line(painter.writer, &line, &raw_line, config)?;
+                } else {
+                    write_raw_line(painter, &raw_line, config)?;
+                }
+            }
+            Event::HunkHeader {
+                code_fragment,
+                line_numbers_and_hunk_lengths,
+                line,
+                raw_line,
+            } => {
+                self.state = State::HunkHeader;
+                self.set_hunk_lengths(&line_numbers_and_hunk_lengths);
+                let painter = &mut self.painter;
+                painter.set_highlighter();
+                painter.emit()?;
+                if should_handle(&self.state, config) {
+                    handle_hunk_header_line(
+                        painter,
+                        &line,
+                        &raw_line,
+                        &code_fragment,
+                        line_numbers_and_hunk_lengths,
+                        &self.plus_file,
+                        config,
+                    )?;
+                } else {
+                    write_raw_line(painter, &raw_line, config)?;
+                }
+            }
+            Event::Line {
+                kind,
+                line,
+                raw_line,
+            } => {
+                self.count_hunk_line(kind);
+                let painter = &mut self.painter;
+                let state = std::mem::replace(&mut self.state, State::Unknown);
+                self.state = handle_hunk_line(painter, kind, &line, &raw_line, state, config);
                 painter.emit()?;
-                handle_generic_file_meta_header_line(&mut painter, &line, &raw_line, config)?;
-                continue;
             }
-        } else if state.is_in_hunk() {
-            // A true hunk line should start with one of: '+', '-', ' '. However, handle_hunk_line
-            // handles all lines until the state machine transitions away from the hunk states.
-            state = handle_hunk_line(&mut painter, &line, &raw_line, state, config);
-            painter.emit()?;
-            continue;
+            Event::Other { raw_line, .. } => {
+                painter.emit()?;
+                write_raw_line(painter, &raw_line, config)?;
+            }
         }
+        Ok(())
+    }
 
-        if state == State::FileMeta && should_handle(&State::FileMeta, config) {
-            // The file metadata section is 4 lines. Skip them under non-plain file-styles.
-            continue;
-        } else {
-            painter.emit()?;
-            writeln!(
-                painter.writer,
-                "{}",
-                format::format_raw_line(&raw_line, config)
-            )?;
+    /// If gitattributes mark the file as generated, hide the rest of its section, which is to be
+    /// represented by a single line, and return true.
+    fn collapse_if_generated(
+        &mut self,
+        minus_file: &str,
+        plus_file: &str,
+        change: FileChange,
+    ) -> std::io::Result<bool> {
+        if !is_generated_file(minus_file, plus_file, self.config) {
+            return Ok(false);
+        }
+        self.painter.emit()?;
+        self.hidden_file = Some(HiddenFile::Collapsed(CollapsedFile::new(
+            parse::get_file_change_description(
+                minus_file,
+                plus_file,
+                change,
+                self.source == Source::DiffUnified,
+                self.config,
+            ),
+        )));
+        Ok(true)
+    }
+
+    /// Write the output painted so far. Buffered removed and added lines are painted only once
+    /// their hunk is complete, since the lines that follow could change how they are paired.
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        if self.n_hunk_lines_remaining == Some((0, 0)) {
+            self.painter.paint_buffered_minus_and_plus_lines();
+        }
+        self.painter.emit()?;
+        self.painter.writer.flush()
+    }
+
+    /// Write any remaining output of the section, returning the file hidden in the section, if
+    /// any.
+    pub fn finish(mut self) -> std::io::Result<Option<HiddenFile>> {
+        self.paint_held_events()?;
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.emit()?;
+        Ok(self.hidden_file)
+    }
+}
+
+/// A file that is not displayed: either hidden by --include/--exclude, or collapsed because it is
+/// generated.
+pub enum HiddenFile {
+    Excluded,
+    Collapsed(CollapsedFile),
+}
+
+/// The hidden files of which the output has not yet taken account. A collapsed file is represented
+/// by a single line at the end of its section, and the files hidden by --include/--exclude are
+/// counted at the end of each commit.
+#[derive(Default)]
+pub struct HiddenFiles {
+    n_hidden_files: usize,
+    collapsed_file: Option<CollapsedFile>,
+}
+
+impl HiddenFiles {
+    pub fn add(&mut self, hidden_file: Option<HiddenFile>) {
+        match hidden_file {
+            Some(HiddenFile::Excluded) => self.n_hidden_files += 1,
+            Some(HiddenFile::Collapsed(collapsed_file)) => {
+                self.collapsed_file = Some(collapsed_file)
+            }
+            None => {}
         }
     }
 
-    painter.paint_buffered_minus_and_plus_lines();
-    painter.emit()?;
-    Ok(())
+    /// Write the lines standing in for hidden files that precede the next section, which is the
+    /// start of a new commit if `is_end_of_commit` is true.
+    pub fn write(
+        &mut self,
+        is_end_of_commit: bool,
+        writer: &mut dyn Write,
+        config: &Config,
+    ) -> std::io::Result<()> {
+        if let Some(collapsed_file) = self.collapsed_file.take() {
+            handle_collapsed_file_meta_header_line(writer, &collapsed_file, config)?;
+        }
+        if is_end_of_commit && self.n_hidden_files > 0 {
+            handle_hidden_files_summary_line(writer, self.n_hidden_files, config)?;
+            self.n_hidden_files = 0;
+        }
+        Ok(())
+    }
+}
+
+pub fn is_commit_header(event: &Event) -> bool {
+    matches!(event, Event::CommitHeader { .. })
 }
 
 /// Should a handle_* function be called on this element?
@@ -195,25 +513,99 @@ fn should_handle(state: &State, config: &Config) -> bool {
     !(style.is_raw && style.decoration_style == DecorationStyle::NoDecoration)
 }
 
-/// Try to detect what is producing the input for delta.
-///
-/// Currently can detect:
-/// * git diff
-/// * diff -u
-fn detect_source(line: &str) -> Source {
-    if line.starts_with("commit ") || line.starts_with("diff --git ") {
-        Source::GitDiff
-    } else if line.starts_with("diff -u")
-        || line.starts_with("diff -ru")
-        || line.starts_with("diff -r -u")
-        || line.starts_with("diff -U")
-        || line.starts_with("--- ")
-        || line.starts_with("Only in ")
-    {
-        Source::DiffUnified
+/// Does this line start the section for a new file (or a new commit)?
+pub fn is_start_of_file_section(line: &str, source: &Source) -> bool {
+    line.starts_with("commit ")
+        || line.starts_with("diff ")
+        || *source == Source::DiffUnified
+            && (line.starts_with("--- ") || line.starts_with("Only in "))
+}
+
+/// Should the section for this file be suppressed, according to --include and --exclude? Both
+/// paths are considered, so that a rename is shown if either side of it is included.
+fn is_hidden_file(minus_file: &str, plus_file: &str, config: &Config) -> bool {
+    if config.include_globs.is_empty() && config.exclude_globs.is_empty() {
+        return false;
+    }
+    let paths: Vec<&str> = [minus_file, plus_file]
+        .iter()
+        .filter(|path| **path != "/dev/null")
+        .copied()
+        .collect();
+    let matches = |globs: &[crate::glob::Glob]| {
+        globs
+            .iter()
+            .any(|glob| paths.iter().any(|path| glob.is_match(path)))
+    };
+    (!config.include_globs.is_empty() && !matches(&config.include_globs))
+        || matches(&config.exclude_globs)
+}
+
+/// Should the section for this file be collapsed, because gitattributes mark the file as
+/// generated?
+fn is_generated_file(minus_file: &str, plus_file: &str, config: &Config) -> bool {
+    if config.expand_generated_files {
+        return false;
+    }
+    let path = if plus_file == "/dev/null" {
+        minus_file
     } else {
-        Source::Unknown
+        plus_file
+    };
+    match &config.git_attributes {
+        Some(git_attributes) => git_attributes.is_generated(path),
+        None => false,
+    }
+}
+
+/// A file whose diff is not displayed, but whose changed lines are counted.
+pub struct CollapsedFile {
+    description: String,
+    n_lines_changed: usize,
+}
+
+impl CollapsedFile {
+    fn new(description: String) -> Self {
+        Self {
+            description,
+            n_lines_changed: 0,
+        }
     }
+
+    fn count_event(&mut self, event: &Event) {
+        if let Event::Line { kind, .. } = event {
+            if *kind == LineKind::Minus || *kind == LineKind::Plus {
+                self.n_lines_changed += 1;
+            }
+        }
+    }
+}
+
+/// Write a line of commit metadata that is not handled by handle_commit_meta_header_line.
+fn write_raw_commit_meta_line(
+    painter: &mut Painter,
+    line: &str,
+    raw_line: &str,
+    commit_meta_state: &mut CommitMetaState,
+    config: &Config,
+) -> std::io::Result<()> {
+    let formatted_line = if line.starts_with("commit ") {
+        commit_meta::format_commit_line(line, config)
+    } else {
+        commit_meta::format_commit_meta_line(line, commit_meta_state, config)
+    };
+    let formatted_raw_line = match formatted_line {
+        Some(formatted_line) => Cow::from(
+            format::format_raw_commit_meta_line(&formatted_line, config).into_owned(),
+        ),
+        None => format::format_raw_commit_meta_line(raw_line, config),
+    };
+    writeln!(painter.writer, "{}", formatted_raw_line)
+}
+
+/// Write a line that is not handled by any of the handle_* functions.
+fn write_raw_line(painter: &mut Painter, raw_line: &str, config: &Config) -> std::io::Result<()> {
+    writeln!(painter.writer, "{}", format::format_raw_line(raw_line, config))
 }
 
 fn handle_commit_meta_header_line(
@@ -225,46 +617,26 @@ fn handle_commit_meta_header_line(
     if config.commit_style.is_omitted {
         return Ok(());
     }
-    let decoration_ansi_term_style;
-    let mut pad = false;
-    let draw_fn = match config.commit_style.decoration_style {
-        DecorationStyle::Box(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed
-        }
-        DecorationStyle::BoxWithUnderline(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed_with_underline
-        }
-        DecorationStyle::BoxWithOverline(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed // TODO: not implemented
-        }
-        DecorationStyle::BoxWithUnderOverline(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed // TODO: not implemented
-        }
-        DecorationStyle::Underline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_underlined
-        }
-        DecorationStyle::Overline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_overlined
-        }
-        DecorationStyle::UnderOverline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_underoverlined
-        }
-        DecorationStyle::NoDecoration => {
-            decoration_ansi_term_style = ansi_term::Style::new();
-            draw::write_no_decoration
-        }
-    };
+    let (draw_fn, pad, decoration_ansi_term_style) =
+        draw::get_draw_function(config.commit_style.decoration_style);
+    if let Some(formatted_line) = commit_meta::format_commit_line(line, config) {
+        // The line has been painted already; it is drawn as if it were raw.
+        return draw_fn(
+            painter.writer,
+            &format!(
+                "{}{}",
+                strip_ansi_codes(&formatted_line),
+                if pad { " " } else { "" }
+            ),
+            &format!("{}{}", formatted_line, if pad { " " } else { "" }),
+            &config.decorations_width,
+            Style {
+                is_raw: true,
+                ..config.commit_style
+            },
+            decoration_ansi_term_style,
+        );
+    }
     let (formatted_line, formatted_raw_line) = if config.hyperlinks {
         (
             Cow::from(
@@ -296,19 +668,19 @@ fn handle_file_meta_header_line(
     painter: &mut Painter,
     minus_file: &str,
     plus_file: &str,
+    change: FileChange,
     config: &Config,
     comparing: bool,
 ) -> std::io::Result<()> {
-    let line = parse::get_file_change_description_from_file_paths(
-        minus_file, plus_file, comparing, config,
-    );
+    let line =
+        parse::get_file_change_description(minus_file, plus_file, change, comparing, config);
     // FIXME: no support for 'raw'
-    handle_generic_file_meta_header_line(painter, &line, &line, config)
+    handle_generic_file_meta_header_line(painter.writer, &line, &line, config)
 }
 
 /// Write `line` with FileMeta styling.
 fn handle_generic_file_meta_header_line(
-    painter: &mut Painter,
+    writer: &mut dyn Write,
     line: &str,
     raw_line: &str,
     config: &Config,
@@ -316,49 +688,11 @@ fn handle_generic_file_meta_header_line(
     if config.file_style.is_omitted {
         return Ok(());
     }
-    let decoration_ansi_term_style;
-    let mut pad = false;
-    let draw_fn = match config.file_style.decoration_style {
-        DecorationStyle::Box(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed
-        }
-        DecorationStyle::BoxWithUnderline(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed_with_underline
-        }
-        DecorationStyle::BoxWithOverline(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed // TODO: not implemented
-        }
-        DecorationStyle::BoxWithUnderOverline(style) => {
-            pad = true;
-            decoration_ansi_term_style = style;
-            draw::write_boxed // TODO: not implemented
-        }
-        DecorationStyle::Underline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_underlined
-        }
-        DecorationStyle::Overline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_overlined
-        }
-        DecorationStyle::UnderOverline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_underoverlined
-        }
-        DecorationStyle::NoDecoration => {
-            decoration_ansi_term_style = ansi_term::Style::new();
-            draw::write_no_decoration
-        }
-    };
-    writeln!(painter.writer)?;
+    let (draw_fn, pad, decoration_ansi_term_style) =
+        draw::get_draw_function(config.file_style.decoration_style);
+    writeln!(writer)?;
     draw_fn(
-        painter.writer,
+        writer,
         &format!("{}{}", line, if pad { " " } else { "" }),
         &format!("{}{}", raw_line, if pad { " " } else { "" }),
         &config.decorations_width,
@@ -368,52 +702,56 @@ fn handle_generic_file_meta_header_line(
     Ok(())
 }
 
+/// Write the "N files hidden" line standing in for files suppressed by --include/--exclude.
+fn handle_hidden_files_summary_line(
+    writer: &mut dyn Write,
+    n_hidden_files: usize,
+    config: &Config,
+) -> std::io::Result<()> {
+    if !config.summarize_hidden_files {
+        return Ok(());
+    }
+    let line = format!(
+        "{} file{} hidden",
+        n_hidden_files,
+        if n_hidden_files == 1 { "" } else { "s" }
+    );
+    handle_generic_file_meta_header_line(writer, &line, &line, config)
+}
+
+/// Write the single line standing in for the diff of a collapsed generated file.
+fn handle_collapsed_file_meta_header_line(
+    writer: &mut dyn Write,
+    collapsed_file: &CollapsedFile,
+    config: &Config,
+) -> std::io::Result<()> {
+    let line = format!(
+        "{} (generated file: {} line{} changed, collapsed)",
+        collapsed_file.description,
+        collapsed_file.n_lines_changed,
+        if collapsed_file.n_lines_changed == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+    handle_generic_file_meta_header_line(writer, &line, &line, config)
+}
+
 fn handle_hunk_header_line(
     painter: &mut Painter,
     line: &str,
     raw_line: &str,
+    raw_code_fragment: &str,
+    line_numbers: Vec<(usize, usize)>,
     plus_file: &str,
     config: &Config,
 ) -> std::io::Result<()> {
     if config.hunk_header_style.is_omitted {
         return Ok(());
     }
-    let decoration_ansi_term_style;
-    let draw_fn = match config.hunk_header_style.decoration_style {
-        DecorationStyle::Box(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_boxed
-        }
-        DecorationStyle::BoxWithUnderline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_boxed_with_underline
-        }
-        DecorationStyle::BoxWithOverline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_boxed // TODO: not implemented
-        }
-        DecorationStyle::BoxWithUnderOverline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_boxed // TODO: not implemented
-        }
-        DecorationStyle::Underline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_underlined
-        }
-        DecorationStyle::Overline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_overlined
-        }
-        DecorationStyle::UnderOverline(style) => {
-            decoration_ansi_term_style = style;
-            draw::write_underoverlined
-        }
-        DecorationStyle::NoDecoration => {
-            decoration_ansi_term_style = ansi_term::Style::new();
-            draw::write_no_decoration
-        }
-    };
-    let (raw_code_fragment, line_numbers) = parse::parse_hunk_header(&line);
+    let (draw_fn, _, decoration_ansi_term_style) =
+        draw::get_draw_function(config.hunk_header_style.decoration_style);
     // Emit the hunk header, with any requested decoration
     if config.hunk_header_style.is_raw {
         if config.hunk_header_style.decoration_style != DecorationStyle::NoDecoration {
@@ -428,13 +766,13 @@ fn handle_hunk_header_line(
             decoration_ansi_term_style,
         )?;
     } else {
-        let line = match painter.prepare(&raw_code_fragment, false) {
+        let line = match painter.prepare(raw_code_fragment, false) {
             s if s.len() > 0 => format!("{} ", s),
             s => s,
         };
         writeln!(painter.writer)?;
         if !line.is_empty() {
-            let lines = vec![(line, State::HunkHeader)];
+            let lines = vec![(line, "".to_string(), State::HunkHeader)];
             let syntax_style_sections = Painter::get_syntax_style_sections_for_lines(
                 &lines,
                 &State::HunkHeader,
@@ -503,6 +841,7 @@ fn handle_hunk_header_line(
 // an unchanged line, we paint it immediately.
 fn handle_hunk_line(
     painter: &mut Painter,
+    kind: LineKind,
     line: &str,
     raw_line: &str,
     state: State,
@@ -516,8 +855,8 @@ fn handle_hunk_line(
     {
         painter.paint_buffered_minus_and_plus_lines();
     }
-    match line.chars().next() {
-        Some('-') => {
+    match kind {
+        LineKind::Minus => {
             if let State::HunkPlus(_) = state {
                 painter.paint_buffered_minus_and_plus_lines();
             }
@@ -532,12 +871,15 @@ fn handle_hunk_line(
                 }
                 _ => State::HunkMinus(None),
             };
-            painter
-                .minus_lines
-                .push((painter.prepare(&line, true), state.clone()));
+            let (line, truncation_marker) = painter.truncate(&line);
+            painter.minus_lines.push((
+                painter.prepare(line, true),
+                truncation_marker,
+                state.clone(),
+            ));
             state
         }
-        Some('+') => {
+        LineKind::Plus => {
             let state = match config.inspect_raw_lines {
                 cli::InspectRawLines::True
                     if style::line_has_style_other_than(
@@ -549,17 +891,20 @@ fn handle_hunk_line(
                 }
                 _ => State::HunkPlus(None),
             };
-            painter
-                .plus_lines
-                .push((painter.prepare(&line, true), state.clone()));
+            let (line, truncation_marker) = painter.truncate(&line);
+            painter.plus_lines.push((
+                painter.prepare(line, true),
+                truncation_marker,
+                state.clone(),
+            ));
             state
         }
-        Some(' ') => {
+        LineKind::Zero => {
             painter.paint_buffered_minus_and_plus_lines();
             painter.paint_zero_line(&line);
             State::HunkZero
         }
-        _ => {
+        LineKind::Other => {
             // The first character here could be e.g. '\' from '\ No newline at end of file'. This
             // is not a hunk line, but the parser does not have a more accurate state corresponding
             // to this.
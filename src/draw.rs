@@ -7,7 +7,98 @@ use console::strip_ansi_codes;
 use unicode_width::UnicodeWidthStr;
 
 use crate::cli::Width;
-use crate::style::Style;
+use crate::style::{BoxShape, DecorationStyle, Style};
+
+pub type DrawFunction =
+    dyn Fn(&mut dyn Write, &str, &str, &Width, Style, ansi_term::Style) -> std::io::Result<()>;
+
+/// Return the function that draws text with the requested decoration, whether the text should be
+/// padded with a trailing space (so that it does not touch the right edge of a box), and the
+/// style of the decoration.
+pub fn get_draw_function(
+    decoration_style: DecorationStyle,
+) -> (Box<DrawFunction>, bool, ansi_term::Style) {
+    match decoration_style {
+        DecorationStyle::Box(style, shape) => (
+            Box::new(
+                move |writer, text, raw_text, line_width, text_style, decoration_style| {
+                    write_boxed(
+                        writer,
+                        text,
+                        raw_text,
+                        line_width,
+                        text_style,
+                        decoration_style,
+                        shape,
+                    )
+                },
+            ),
+            true,
+            style,
+        ),
+        DecorationStyle::BoxWithUnderline(style, shape) => (
+            Box::new(
+                move |writer, text, raw_text, line_width, text_style, decoration_style| {
+                    write_boxed_with_underline(
+                        writer,
+                        text,
+                        raw_text,
+                        line_width,
+                        text_style,
+                        decoration_style,
+                        shape,
+                    )
+                },
+            ),
+            true,
+            style,
+        ),
+        DecorationStyle::BoxWithOverline(style, shape) => (
+            Box::new(
+                move |writer, text, raw_text, line_width, text_style, decoration_style| {
+                    write_boxed_with_overline(
+                        writer,
+                        text,
+                        raw_text,
+                        line_width,
+                        text_style,
+                        decoration_style,
+                        shape,
+                    )
+                },
+            ),
+            true,
+            style,
+        ),
+        DecorationStyle::BoxWithUnderOverline(style, shape) => (
+            Box::new(
+                move |writer, text, raw_text, line_width, text_style, decoration_style| {
+                    write_boxed_with_underoverline(
+                        writer,
+                        text,
+                        raw_text,
+                        line_width,
+                        text_style,
+                        decoration_style,
+                        shape,
+                    )
+                },
+            ),
+            true,
+            style,
+        ),
+        DecorationStyle::Underline(style) => (Box::new(write_underlined), false, style),
+        DecorationStyle::Overline(style) => (Box::new(write_overlined), false, style),
+        DecorationStyle::UnderOverline(style) => (Box::new(write_underoverlined), false, style),
+        DecorationStyle::LeftBar(style) => (Box::new(write_left_bar), false, style),
+        DecorationStyle::Banner(style) => (Box::new(write_banner), false, style),
+        DecorationStyle::NoDecoration => (
+            Box::new(write_no_decoration),
+            false,
+            ansi_term::Style::new(),
+        ),
+    }
+}
 
 pub fn write_no_decoration(
     writer: &mut dyn Write,
@@ -25,32 +116,82 @@ pub fn write_no_decoration(
     Ok(())
 }
 
+/// The characters used to draw a box.
+struct BoxCharacters {
+    horizontal: &'static str,
+    vertical: &'static str,
+    down_left: &'static str,
+    up_left: &'static str,
+    down_horizontal: &'static str,
+    up_horizontal: &'static str,
+}
+
+impl BoxCharacters {
+    fn new(shape: BoxShape) -> Self {
+        match shape {
+            BoxShape::Light => Self {
+                horizontal: box_drawing::light::HORIZONTAL,
+                vertical: box_drawing::light::VERTICAL,
+                down_left: box_drawing::light::DOWN_LEFT,
+                up_left: box_drawing::light::UP_LEFT,
+                down_horizontal: box_drawing::light::DOWN_HORIZONTAL,
+                up_horizontal: box_drawing::light::UP_HORIZONTAL,
+            },
+            BoxShape::Heavy => Self {
+                horizontal: box_drawing::heavy::HORIZONTAL,
+                vertical: box_drawing::heavy::VERTICAL,
+                down_left: box_drawing::heavy::DOWN_LEFT,
+                up_left: box_drawing::heavy::UP_LEFT,
+                down_horizontal: box_drawing::heavy::DOWN_HORIZONTAL,
+                up_horizontal: box_drawing::heavy::UP_HORIZONTAL,
+            },
+            BoxShape::Double => Self {
+                horizontal: box_drawing::double::HORIZONTAL,
+                vertical: box_drawing::double::VERTICAL,
+                down_left: box_drawing::double::DOWN_LEFT,
+                up_left: box_drawing::double::UP_LEFT,
+                down_horizontal: box_drawing::double::DOWN_HORIZONTAL,
+                up_horizontal: box_drawing::double::UP_HORIZONTAL,
+            },
+            BoxShape::Rounded => Self {
+                down_left: box_drawing::arc::DOWN_LEFT,
+                up_left: box_drawing::arc::UP_LEFT,
+                ..Self::new(BoxShape::Light)
+            },
+        }
+    }
+
+    /// The characters used for a plain (underline/overline) horizontal line.
+    fn for_line(decoration_style: ansi_term::Style) -> Self {
+        if decoration_style.is_bold {
+            Self::new(BoxShape::Heavy)
+        } else {
+            Self::new(BoxShape::Light)
+        }
+    }
+}
+
 /// Write text to stream, surrounded by a box, leaving the cursor just
 /// beyond the bottom right corner.
 pub fn write_boxed(
     writer: &mut dyn Write,
     text: &str,
     raw_text: &str,
-    _line_width: &Width, // ignored
+    line_width: &Width,
     text_style: Style,
     decoration_style: ansi_term::Style,
+    box_shape: BoxShape,
 ) -> std::io::Result<()> {
-    let up_left = if decoration_style.is_bold {
-        box_drawing::heavy::UP_LEFT
-    } else {
-        box_drawing::light::UP_LEFT
-    };
-    let box_width = UnicodeWidthStr::width(strip_ansi_codes(text).as_ref());
-    write_boxed_partial(
+    _write_boxed(
         writer,
         text,
         raw_text,
-        box_width,
+        line_width,
         text_style,
         decoration_style,
-    )?;
-    writeln!(writer, "{}", decoration_style.paint(up_left))?;
-    Ok(())
+        box_shape,
+        UnderOverline::Neither,
+    )
 }
 
 /// Write text to stream, surrounded by a box, and extend a line from
@@ -62,38 +203,123 @@ pub fn write_boxed_with_underline(
     line_width: &Width,
     text_style: Style,
     decoration_style: ansi_term::Style,
+    box_shape: BoxShape,
 ) -> std::io::Result<()> {
-    let box_width = UnicodeWidthStr::width(strip_ansi_codes(text).as_ref());
-    write_boxed_with_horizontal_whisker(
+    _write_boxed(
         writer,
         text,
         raw_text,
-        box_width,
+        line_width,
         text_style,
         decoration_style,
-    )?;
-    let line_width = match *line_width {
-        Width::Fixed(n) => n,
-        Width::Variable => box_width,
-    };
-    write_horizontal_line(
+        box_shape,
+        UnderOverline::Under,
+    )
+}
+
+/// Write text to stream, surrounded by a box, and extend a line from
+/// the top right corner.
+pub fn write_boxed_with_overline(
+    writer: &mut dyn Write,
+    text: &str,
+    raw_text: &str,
+    line_width: &Width,
+    text_style: Style,
+    decoration_style: ansi_term::Style,
+    box_shape: BoxShape,
+) -> std::io::Result<()> {
+    _write_boxed(
         writer,
-        if line_width > box_width {
-            line_width - box_width - 1
-        } else {
-            0
-        },
+        text,
+        raw_text,
+        line_width,
         text_style,
         decoration_style,
-    )?;
-    write!(writer, "\n")?;
-    Ok(())
+        box_shape,
+        UnderOverline::Over,
+    )
+}
+
+/// Write text to stream, surrounded by a box, and extend lines from
+/// the top right and bottom right corners.
+pub fn write_boxed_with_underoverline(
+    writer: &mut dyn Write,
+    text: &str,
+    raw_text: &str,
+    line_width: &Width,
+    text_style: Style,
+    decoration_style: ansi_term::Style,
+    box_shape: BoxShape,
+) -> std::io::Result<()> {
+    _write_boxed(
+        writer,
+        text,
+        raw_text,
+        line_width,
+        text_style,
+        decoration_style,
+        box_shape,
+        UnderOverline::Underover,
+    )
 }
 
 enum UnderOverline {
     Under,
     Over,
     Underover,
+    Neither,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn _write_boxed(
+    writer: &mut dyn Write,
+    text: &str,
+    raw_text: &str,
+    line_width: &Width,
+    text_style: Style,
+    decoration_style: ansi_term::Style,
+    box_shape: BoxShape,
+    underoverline: UnderOverline,
+) -> std::io::Result<()> {
+    let chars = BoxCharacters::new(box_shape);
+    let box_width = UnicodeWidthStr::width(strip_ansi_codes(text).as_ref());
+    let whisker_width = match *line_width {
+        Width::Fixed(n) if n > box_width => n - box_width - 1,
+        _ => 0,
+    };
+    let horizontal_edge = chars.horizontal.repeat(box_width);
+    let (top_corner, top_whisker) = match underoverline {
+        UnderOverline::Over | UnderOverline::Underover => (
+            chars.down_horizontal,
+            chars.horizontal.repeat(whisker_width),
+        ),
+        _ => (chars.down_left, "".to_string()),
+    };
+    let (bottom_corner, bottom_whisker) = match underoverline {
+        UnderOverline::Under | UnderOverline::Underover => {
+            (chars.up_horizontal, chars.horizontal.repeat(whisker_width))
+        }
+        _ => (chars.up_left, "".to_string()),
+    };
+    writeln!(
+        writer,
+        "{}",
+        decoration_style.paint(format!("{}{}{}", horizontal_edge, top_corner, top_whisker)),
+    )?;
+    if text_style.is_raw {
+        write!(writer, "{}", raw_text)?;
+    } else {
+        write!(writer, "{}", text_style.paint(text))?;
+    }
+    writeln!(writer, "{}", decoration_style.paint(chars.vertical))?;
+    writeln!(
+        writer,
+        "{}",
+        decoration_style.paint(format!(
+            "{}{}{}",
+            horizontal_edge, bottom_corner, bottom_whisker
+        )),
+    )
 }
 
 pub fn write_underlined(
@@ -170,11 +396,11 @@ fn _write_under_or_over_lined(
     let mut write_line: Box<dyn FnMut(&mut dyn Write) -> std::io::Result<()>> =
         Box::new(|writer| {
             write_horizontal_line(writer, line_width, text_style, decoration_style)?;
-            write!(writer, "\n")?;
+            writeln!(writer)?;
             Ok(())
         });
     match underoverline {
-        UnderOverline::Under => {}
+        UnderOverline::Under | UnderOverline::Neither => {}
         _ => write_line(writer)?,
     }
     if text_style.is_raw {
@@ -183,7 +409,7 @@ fn _write_under_or_over_lined(
         writeln!(writer, "{}", text_style.paint(text))?;
     }
     match underoverline {
-        UnderOverline::Over => {}
+        UnderOverline::Over | UnderOverline::Neither => {}
         _ => write_line(writer)?,
     }
     Ok(())
@@ -195,11 +421,7 @@ fn write_horizontal_line(
     _text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let horizontal = if decoration_style.is_bold {
-        box_drawing::heavy::HORIZONTAL
-    } else {
-        box_drawing::light::HORIZONTAL
-    };
+    let horizontal = BoxCharacters::for_line(decoration_style).horizontal;
     write!(
         writer,
         "{}",
@@ -207,68 +429,56 @@ fn write_horizontal_line(
     )
 }
 
-fn write_boxed_with_horizontal_whisker(
+/// Write text to stream, preceded by a vertical bar in the left margin.
+pub fn write_left_bar(
     writer: &mut dyn Write,
     text: &str,
     raw_text: &str,
-    box_width: usize,
+    _line_width: &Width, // ignored
     text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let up_horizontal = if decoration_style.is_bold {
-        box_drawing::heavy::UP_HORIZONTAL
+    write!(writer, "{} ", decoration_style.paint("▌"))?;
+    if text_style.is_raw {
+        writeln!(writer, "{}", raw_text)?;
     } else {
-        box_drawing::light::UP_HORIZONTAL
-    };
-    write_boxed_partial(
-        writer,
-        text,
-        raw_text,
-        box_width,
-        text_style,
-        decoration_style,
-    )?;
-    write!(writer, "{}", decoration_style.paint(up_horizontal))?;
+        writeln!(writer, "{}", text_style.paint(text))?;
+    }
     Ok(())
 }
 
-fn write_boxed_partial(
+/// Write text to stream on a background filled to the full line width. The fill color is the
+/// background color of the decoration style, or its foreground color if it has no background
+/// color, so that e.g. 'blue banner' fills the line with blue.
+pub fn write_banner(
     writer: &mut dyn Write,
     text: &str,
     raw_text: &str,
-    box_width: usize,
+    line_width: &Width,
     text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let (horizontal, down_left, vertical) = if decoration_style.is_bold {
-        (
-            box_drawing::heavy::HORIZONTAL,
-            box_drawing::heavy::DOWN_LEFT,
-            box_drawing::heavy::VERTICAL,
-        )
-    } else {
-        (
-            box_drawing::light::HORIZONTAL,
-            box_drawing::light::DOWN_LEFT,
-            box_drawing::light::VERTICAL,
-        )
+    let text_width = UnicodeWidthStr::width(strip_ansi_codes(text).as_ref());
+    let line_width = match *line_width {
+        Width::Fixed(n) => max(n, text_width + 1),
+        Width::Variable => text_width + 1,
+    };
+    let fill_style = ansi_term::Style {
+        background: decoration_style.background.or(decoration_style.foreground),
+        ..ansi_term::Style::new()
     };
-    let horizontal_edge = horizontal.repeat(box_width);
-    write!(
-        writer,
-        "{}{}\n",
-        decoration_style.paint(&horizontal_edge),
-        decoration_style.paint(down_left),
-    )?;
     if text_style.is_raw {
         write!(writer, "{}", raw_text)?;
     } else {
+        let text_style = ansi_term::Style {
+            background: fill_style.background,
+            ..text_style.ansi_term_style
+        };
         write!(writer, "{}", text_style.paint(text))?;
     }
-    write!(
+    writeln!(
         writer,
-        "{}\n{}",
-        decoration_style.paint(vertical),
-        decoration_style.paint(&horizontal_edge),
+        "{}",
+        fill_style.paint(" ".repeat(line_width - text_width))
     )
 }
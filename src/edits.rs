@@ -12,6 +12,8 @@ use crate::align;
 /// minus and plus lines. `noop_deletions[i]` is the appropriate deletion operation tag to be used
 /// for `minus_lines[i]`; `noop_deletions` is guaranteed to be the same length as `minus_lines`.
 /// The equivalent statements hold for `plus_insertions` and `plus_lines`.
+/// Once the alignment tables computed have `max_alignment_cells` cells in total, no more are
+/// computed: the remaining lines are returned unpaired, so that they are styled as whole lines.
 pub fn infer_edits<'a, EditOperation>(
     minus_lines: Vec<&'a str>,
     plus_lines: Vec<&'a str>,
@@ -22,6 +24,7 @@ pub fn infer_edits<'a, EditOperation>(
     tokenization_regex: &Regex,
     max_line_distance: f64,
     max_line_distance_for_naively_paired_lines: f64,
+    max_alignment_cells: usize,
 ) -> (
     Vec<Vec<(EditOperation, &'a str)>>,  // annotated minus lines
     Vec<Vec<(EditOperation, &'a str)>>,  // annotated plus lines
@@ -36,14 +39,19 @@ where
     let mut line_alignment = Vec::<(Option<usize>, Option<usize>)>::new();
 
     let mut plus_index = 0; // plus lines emitted so far
+    let mut n_alignment_cells = 0; // cells in the alignment tables computed so far
 
     'minus_lines_loop: for (minus_index, minus_line) in minus_lines.iter().enumerate() {
         let mut considered = 0; // plus lines considered so far as match for minus_line
         for plus_line in &plus_lines[plus_index..] {
-            let alignment = align::Alignment::new(
-                tokenize(minus_line, tokenization_regex),
-                tokenize(plus_line, tokenization_regex),
-            );
+            let minus_tokens = tokenize(minus_line, tokenization_regex);
+            let plus_tokens = tokenize(plus_line, tokenization_regex);
+            n_alignment_cells += (minus_tokens.len() + 1) * (plus_tokens.len() + 1);
+            if n_alignment_cells > max_alignment_cells {
+                // Aligning any more lines would take too long.
+                break;
+            }
+            let alignment = align::Alignment::new(minus_tokens, plus_tokens);
             let (annotated_minus_line, annotated_plus_line, distance) = annotate(
                 alignment,
                 noop_deletions[minus_index],
@@ -720,6 +728,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_infer_edits_with_max_alignment_cells() {
+        // Each alignment table has (5 + 1) * (5 + 1) cells, so only the first pair is aligned.
+        let (minus_edits, plus_edits, line_alignment) = infer_edits(
+            vec![" aaa bbb", " ccc ddd"],
+            vec![" aaa bbx", " ccc ddx"],
+            vec![MinusNoop, MinusNoop],
+            Deletion,
+            vec![PlusNoop, PlusNoop],
+            Insertion,
+            &*DEFAULT_TOKENIZATION_REGEXP,
+            1.0,
+            0.0,
+            40,
+        );
+        assert!(minus_edits[0].iter().any(|(op, _)| *op == Deletion));
+        assert!(plus_edits[0].iter().any(|(op, _)| *op == Insertion));
+        assert_eq!(minus_edits[1], vec![(MinusNoop, " ccc ddd")]);
+        assert_eq!(plus_edits[1], vec![(PlusNoop, " ccc ddx")]);
+        assert_eq!(
+            line_alignment,
+            vec![(Some(0), Some(0)), (Some(1), None), (None, Some(1))]
+        );
+    }
+
     fn assert_edits(
         minus_lines: Vec<&str>,
         plus_lines: Vec<&str>,
@@ -740,6 +773,7 @@ mod tests {
             &*DEFAULT_TOKENIZATION_REGEXP,
             max_line_distance,
             0.0,
+            usize::MAX,
         );
         // compare_annotated_lines(actual_edits, expected_edits);
         // TODO: test line alignment
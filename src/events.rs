@@ -0,0 +1,507 @@
+use std::io::BufRead;
+
+use bytelines::ByteLines;
+use console::strip_ansi_codes;
+
+use crate::config::Config;
+use crate::parse;
+
+/// An element of the diff. Every event carries its input line, both without ANSI escape sequences
+/// (`line`) and as it was received (`raw_line`), so that a consumer which does not handle an event
+/// specially can emit the line unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A "commit <hash>" line.
+    CommitHeader { line: String, raw_line: String },
+    /// A line following the commit header, e.g. "Author: ..." or a line of the commit message.
+    CommitMeta { line: String, raw_line: String },
+    /// A line of file metadata, e.g. "diff --git ...", "index ...", or "--- a/file".
+    FileMeta { line: String, raw_line: String },
+    /// The "+++" or "rename to" line from which the paths of a file are known. A file that is
+    /// renamed with changes has both, but only the first is a file header; the second is file
+    /// metadata.
+    FileHeader {
+        minus_file: String,
+        plus_file: String,
+        change: FileChange,
+        line: String,
+        raw_line: String,
+    },
+    /// A line that stands in for the diff of a file, e.g. "Binary files ... differ",
+    /// "Submodule ...", or (diff -u) "Only in ...".
+    GenericFileHeader { line: String, raw_line: String },
+    /// A "@@ ... @@" line. There is a (line number, hunk length) pair for each side of the diff:
+    /// two, except for merge commits.
+    HunkHeader {
+        code_fragment: String,
+        line_numbers_and_hunk_lengths: Vec<(usize, usize)>,
+        line: String,
+        raw_line: String,
+    },
+    /// A line in a hunk.
+    Line {
+        kind: LineKind,
+        line: String,
+        raw_line: String,
+    },
+    /// A line that is not part of a commit, file, or hunk, e.g. material preceding the diff.
+    Other { line: String, raw_line: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineKind {
+    Minus,
+    Plus,
+    Zero,
+    /// Any other line in a hunk, e.g. "\ No newline at end of file".
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+    Renamed,
+}
+
+impl FileChange {
+    pub fn new(minus_file: &str, plus_file: &str) -> Self {
+        match (minus_file, plus_file) {
+            (minus_file, plus_file) if minus_file == plus_file => FileChange::Modified,
+            (_, "/dev/null") => FileChange::Removed,
+            ("/dev/null", _) => FileChange::Added,
+            _ => FileChange::Renamed,
+        }
+    }
+}
+
+impl Event {
+    pub fn line(&self) -> &str {
+        match self {
+            Event::CommitHeader { line, .. }
+            | Event::CommitMeta { line, .. }
+            | Event::FileMeta { line, .. }
+            | Event::FileHeader { line, .. }
+            | Event::GenericFileHeader { line, .. }
+            | Event::HunkHeader { line, .. }
+            | Event::Line { line, .. }
+            | Event::Other { line, .. } => line,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    GitDiff,     // Coming from a `git diff` command
+    DiffUnified, // Coming from a `diff -u` command
+    Unknown,
+}
+
+/// The part of the input that the parser is in.
+#[derive(Debug, PartialEq)]
+enum Section {
+    CommitMeta,
+    FileMeta,
+    Hunk,
+    Unknown,
+}
+
+/// An iterator over the events of the diff read from `lines`. Iteration ends at the end of the
+/// input, or at the first read error.
+pub struct Events<'a, I: BufRead> {
+    lines: ByteLines<I>,
+    config: &'a Config,
+    source: Source,
+    section: Section,
+    minus_file: String,
+    // The paths of the file header already emitted for the current file (#245).
+    file_header_paths: Option<(String, String)>,
+}
+
+impl<'a, I: BufRead> Events<'a, I> {
+    pub fn new(lines: ByteLines<I>, config: &'a Config) -> Self {
+        Self {
+            lines,
+            config,
+            source: Source::Unknown,
+            section: Section::Unknown,
+            minus_file: "".to_string(),
+            file_header_paths: None,
+        }
+    }
+
+    /// What is producing the input, as detected from the first recognizable line.
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    fn parse_line(&mut self, line: String, raw_line: String) -> Event {
+        if line.starts_with("commit ") {
+            self.section = Section::CommitMeta;
+            Event::CommitHeader { line, raw_line }
+        } else if line.starts_with("diff ") {
+            self.section = Section::FileMeta;
+            self.file_header_paths = None;
+            Event::FileMeta { line, raw_line }
+        } else if (self.section == Section::FileMeta || self.source == Source::DiffUnified)
+            && (line.starts_with("--- ") || line.starts_with("rename from "))
+        {
+            self.minus_file = self.get_file_path(&line);
+            if self.source == Source::DiffUnified {
+                self.section = Section::FileMeta;
+            }
+            Event::FileMeta { line, raw_line }
+        } else if (self.section == Section::FileMeta || self.source == Source::DiffUnified)
+            && (line.starts_with("+++ ") || line.starts_with("rename to "))
+        {
+            let plus_file = self.get_file_path(&line);
+            let file_header_paths = Some((self.minus_file.clone(), plus_file.clone()));
+            if self.file_header_paths == file_header_paths {
+                return Event::FileMeta { line, raw_line };
+            }
+            self.file_header_paths = file_header_paths;
+            Event::FileHeader {
+                minus_file: self.minus_file.clone(),
+                change: FileChange::new(&self.minus_file, &plus_file),
+                plus_file,
+                line,
+                raw_line,
+            }
+        } else if line.starts_with("@@") {
+            self.section = Section::Hunk;
+            let (code_fragment, line_numbers_and_hunk_lengths) = parse::parse_hunk_header(&line);
+            Event::HunkHeader {
+                code_fragment,
+                line_numbers_and_hunk_lengths,
+                line,
+                raw_line,
+            }
+        } else if self.source == Source::DiffUnified && line.starts_with("Only in ")
+            || line.starts_with("Submodule ")
+            || line.starts_with("Binary files ")
+        {
+            // 1. When comparing directories with diff -u, if filenames match between the
+            //    directories, the files themselves will be compared. However, if an equivalent
+            //    filename is not present, diff outputs a single line (Only in...) starting
+            //    indicating that the file is present in only one of the directories.
+            //
+            // 2. Git diff emits lines describing submodule state such as "Submodule x/y/z contains
+            //    untracked content"
+            //
+            // See https://github.com/dandavison/delta/issues/60#issuecomment-557485242 for a
+            // proposal for more robust parsing logic.
+            self.section = Section::FileMeta;
+            Event::GenericFileHeader { line, raw_line }
+        } else {
+            match self.section {
+                // A true hunk line should start with one of: '+', '-', ' '. However, all lines are
+                // hunk lines until one of the above starts a new section.
+                Section::Hunk => {
+                    let kind = match line.chars().next() {
+                        Some('-') => LineKind::Minus,
+                        Some('+') => LineKind::Plus,
+                        Some(' ') => LineKind::Zero,
+                        _ => LineKind::Other,
+                    };
+                    Event::Line {
+                        kind,
+                        line,
+                        raw_line,
+                    }
+                }
+                Section::CommitMeta => Event::CommitMeta { line, raw_line },
+                Section::FileMeta => Event::FileMeta { line, raw_line },
+                Section::Unknown => Event::Other { line, raw_line },
+            }
+        }
+    }
+
+    fn get_file_path(&self, line: &str) -> String {
+        parse::get_file_path_from_file_meta_line(
+            line,
+            self.source == Source::GitDiff,
+            &self.config.diff_prefixes,
+        )
+    }
+}
+
+impl<'a, I: BufRead> Iterator for Events<'a, I> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        let raw_line = String::from_utf8_lossy(self.lines.next()?.ok()?).into_owned();
+        let line = strip_ansi_codes(&raw_line).to_string();
+        let raw_line = if self.config.monochrome {
+            line.clone()
+        } else {
+            raw_line
+        };
+        if self.source == Source::Unknown {
+            self.source = detect_source(&line);
+        }
+        Some(self.parse_line(line, raw_line))
+    }
+}
+
+/// Try to detect what is producing the input for delta.
+///
+/// Currently can detect:
+/// * git diff
+/// * diff -u
+fn detect_source(line: &str) -> Source {
+    if line.starts_with("commit ") || line.starts_with("diff --git ") {
+        Source::GitDiff
+    } else if line.starts_with("diff -u")
+        || line.starts_with("diff -ru")
+        || line.starts_with("diff -r -u")
+        || line.starts_with("diff -U")
+        || line.starts_with("--- ")
+        || line.starts_with("Only in ")
+    {
+        Source::DiffUnified
+    } else {
+        Source::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::tests::integration_test_utils::integration_test_utils::make_config_from_args;
+
+    fn get_events(input: &str) -> Vec<Event> {
+        let config = make_config_from_args(&[]);
+        Events::new(ByteLines::new(BufReader::new(input.as_bytes())), &config).collect()
+    }
+
+    /// A summary of each event: its type, and the kind of a hunk line.
+    fn describe(events: &[Event]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                Event::CommitHeader { .. } => "CommitHeader".to_string(),
+                Event::CommitMeta { .. } => "CommitMeta".to_string(),
+                Event::FileMeta { .. } => "FileMeta".to_string(),
+                Event::FileHeader { .. } => "FileHeader".to_string(),
+                Event::GenericFileHeader { .. } => "GenericFileHeader".to_string(),
+                Event::HunkHeader { .. } => "HunkHeader".to_string(),
+                Event::Line { kind, .. } => format!("{:?}", kind),
+                Event::Other { .. } => "Other".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_events_git_diff() {
+        let events = get_events(include_str!("../etc/examples/205-highlight-bug.diff"));
+        assert_eq!(
+            describe(&events),
+            vec![
+                "FileMeta",
+                "FileMeta",
+                "FileMeta",
+                "FileHeader",
+                "HunkHeader",
+                "Minus",
+                "Plus",
+                "Zero",
+                "Minus",
+                "Plus",
+                "Plus",
+                "Plus",
+                "Plus",
+                "Plus",
+                "Other",
+                "Minus",
+                "Minus",
+                "Plus",
+                "Plus",
+            ]
+        );
+        assert_eq!(
+            events[3],
+            Event::FileHeader {
+                minus_file: "diff-test.txt".to_string(),
+                plus_file: "diff-test.txt".to_string(),
+                change: FileChange::Modified,
+                line: "+++ w/diff-test.txt".to_string(),
+                raw_line: "+++ w/diff-test.txt".to_string(),
+            }
+        );
+        assert_eq!(
+            events[4],
+            Event::HunkHeader {
+                code_fragment: "".to_string(),
+                line_numbers_and_hunk_lengths: vec![(1, 6), (1, 10)],
+                line: "@@ -1,6 +1,10 @@".to_string(),
+                raw_line: "@@ -1,6 +1,10 @@".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_events_added_file() {
+        let events = get_events(include_str!(
+            "../etc/examples/139-file-with-space-delimited-dash.diff"
+        ));
+        match &events[4] {
+            Event::FileHeader {
+                minus_file,
+                plus_file,
+                change,
+                ..
+            } => {
+                assert_eq!(minus_file, "/dev/null");
+                assert!(plus_file.starts_with("strange - file.txt"));
+                assert_eq!(*change, FileChange::Added);
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_events_retain_raw_line() {
+        let events = get_events(include_str!("../etc/examples/72-color-moved.diff"));
+        assert_eq!(
+            &describe(&events)[..5],
+            [
+                "FileMeta",
+                "FileMeta",
+                "FileMeta",
+                "FileHeader",
+                "HunkHeader"
+            ]
+        );
+        assert_eq!(
+            events[8],
+            Event::Line {
+                kind: LineKind::Minus,
+                line: "-2222 2222 2222 2222".to_string(),
+                raw_line: "\x1b[1;35m-2222 2222 2222 2222\x1b[m".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_events_merge_conflict() {
+        let events = get_events(include_str!("../etc/examples/125-merge-conflict-1.diff"));
+        let hunk_headers: Vec<&Event> = events
+            .iter()
+            .filter(|event| matches!(event, Event::HunkHeader { .. }))
+            .collect();
+        match hunk_headers[1] {
+            Event::HunkHeader {
+                code_fragment,
+                line_numbers_and_hunk_lengths,
+                ..
+            } => {
+                assert_eq!(code_fragment, " pub fn get_config<'a>");
+                assert_eq!(
+                    *line_numbers_and_hunk_lengths,
+                    vec![(78, 7), (40, 9), (84, 15)]
+                );
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_events_commit_and_renamed_file_with_changes() {
+        let events = get_events(
+            "\
+unrecognized preceding material
+commit 1234567890abcdef
+Author: A <a@example.com>
+
+    Rename
+
+diff --git a/old.rs b/new.rs
+similarity index 90%
+rename from old.rs
+rename to new.rs
+index 1111111..2222222 100644
+--- a/old.rs
++++ b/new.rs
+@@ -1 +1 @@
+-a
++b
+\\ No newline at end of file
+Binary files a/x and b/x differ
+",
+        );
+        assert_eq!(
+            describe(&events),
+            vec![
+                "Other",
+                "CommitHeader",
+                "CommitMeta",
+                "CommitMeta",
+                "CommitMeta",
+                "CommitMeta",
+                "FileMeta",
+                "FileMeta",
+                "FileMeta",
+                "FileHeader",
+                "FileMeta",
+                "FileMeta",
+                "FileMeta",
+                "HunkHeader",
+                "Minus",
+                "Plus",
+                "Other",
+                "GenericFileHeader",
+            ]
+        );
+        match &events[9] {
+            Event::FileHeader { change, .. } => assert_eq!(*change, FileChange::Renamed),
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_events_unified_diff() {
+        let events = get_events(
+            "\
+--- a/one.rs	2019-11-20 06:47:56.000000000 +0100
++++ b/one.rs	2019-11-20 06:47:56.000000000 +0100
+@@ -1 +1 @@
+-a
++b
+--- a/two.rs	2019-11-20 06:47:56.000000000 +0100
++++ b/two.rs	2019-11-20 06:47:56.000000000 +0100
+@@ -1 +1 @@
+ c
+Only in b: three.rs
+",
+        );
+        assert_eq!(
+            describe(&events),
+            vec![
+                "FileMeta",
+                "FileHeader",
+                "HunkHeader",
+                "Minus",
+                "Plus",
+                "FileMeta",
+                "FileHeader",
+                "HunkHeader",
+                "Zero",
+                "GenericFileHeader",
+            ]
+        );
+        match &events[6] {
+            Event::FileHeader {
+                minus_file,
+                plus_file,
+                ..
+            } => {
+                assert_eq!(minus_file, "a/two.rs");
+                assert_eq!(plus_file, "b/two.rs");
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+}
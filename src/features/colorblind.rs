@@ -0,0 +1,113 @@
+use crate::features::OptionValueFunction;
+
+/// Orange for removed lines and blue for added lines (from the Okabe-Ito palette), with emphasized
+/// sections also bold and underlined, and removed and added lines marked in the gutter.
+pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        (
+            "minus-style",
+            String,
+            None,
+            opt => if opt.computed.is_light_mode {"syntax #ffe6cc"} else {"syntax #4a2a00"}
+        ),
+        (
+            "minus-emph-style",
+            String,
+            None,
+            opt => if opt.computed.is_light_mode {
+                "syntax bold ul #ffbf80"
+            } else {
+                "syntax bold ul #8a4f00"
+            }
+        ),
+        (
+            "plus-style",
+            String,
+            None,
+            opt => if opt.computed.is_light_mode {"syntax #dbeaff"} else {"syntax #0b2f50"}
+        ),
+        (
+            "plus-emph-style",
+            String,
+            None,
+            opt => if opt.computed.is_light_mode {
+                "syntax bold ul #a3c8ff"
+            } else {
+                "syntax bold ul #1a5b94"
+            }
+        ),
+        (
+            "line-numbers-minus-style",
+            String,
+            None,
+            opt => if opt.computed.is_light_mode {"#d55e00"} else {"#e69f00"}
+        ),
+        (
+            "line-numbers-plus-style",
+            String,
+            None,
+            opt => if opt.computed.is_light_mode {"#0072b2"} else {"#56b4e9"}
+        ),
+        (
+            "minus-marker",
+            String,
+            None,
+            _opt => "-"
+        ),
+        (
+            "plus-marker",
+            String,
+            None,
+            _opt => "+"
+        )
+    ])
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::fs::remove_file;
+
+    use crate::tests::integration_test_utils::integration_test_utils;
+
+    #[test]
+    fn test_colorblind_defaults() {
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--colorblind"],
+            None,
+            None,
+        );
+        assert_eq!(opt.minus_style, "syntax #4a2a00");
+        assert_eq!(opt.plus_emph_style, "syntax bold ul #1a5b94");
+        assert_eq!(opt.minus_marker, "-");
+        assert_eq!(opt.plus_marker, "+");
+
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--colorblind", "--light"],
+            None,
+            None,
+        );
+        assert_eq!(opt.minus_style, "syntax #ffe6cc");
+        assert_eq!(opt.line_numbers_plus_style, "#0072b2");
+    }
+
+    #[test]
+    fn test_colorblind_markers_are_configurable() {
+        let git_config_contents = b"
+[delta]
+    colorblind = true
+    minus-marker = \"< \"
+    plus-marker = \"> \"
+";
+        let git_config_path = "delta__test_colorblind_markers_are_configurable.gitconfig";
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert_eq!(opt.minus_style, "syntax #4a2a00");
+        assert_eq!(opt.minus_marker, "< ");
+        assert_eq!(opt.plus_marker, "> ");
+
+        remove_file(git_config_path).unwrap();
+    }
+}
@@ -22,15 +22,89 @@ pub fn format_commit_line_with_osc8_commit_hyperlink<'a>(
     line: &'a str,
     config: &Config,
 ) -> Cow<'a, str> {
-    if let Some(GitConfigEntry::GitRemote(GitRemoteRepo::GitHubRepo(repo))) =
-        config.git_config_entries.get("remote.origin.url")
+    if let Some(GitConfigEntry::GitRemote(repo)) = config
+        .git_config_entries
+        .get(&format!("remote.{}.url", config.hyperlinks_remote))
     {
-        COMMIT_LINE_REGEX.replace(line, |captures: &Captures| {
-            format_commit_line_captures_with_osc8_commit_hyperlink(captures, repo)
-        })
-    } else {
-        Cow::from(line)
+        if repo
+            .format_commit_url("", config.hyperlinks_commit_link_format.as_deref())
+            .is_some()
+        {
+            return COMMIT_LINE_REGEX.replace(line, |captures: &Captures| {
+                format_commit_line_captures_with_osc8_commit_hyperlink(captures, repo, config)
+            });
+        }
+    }
+    Cow::from(line)
+}
+
+/// Link references to issues, pull requests, tickets, etc. in a commit metadata line (for example
+/// the commit message) to URLs, according to the --hyperlinks-issue-links rules and the default
+/// rules for the forge hosting the git remote. Where matches of different rules overlap, the match
+/// that starts first wins, and of those starting at the same position, the longest.
+pub fn format_commit_meta_line_with_osc8_issue_hyperlinks<'a>(
+    line: &'a str,
+    config: &Config,
+) -> Cow<'a, str> {
+    let mut links: Vec<(usize, usize, String)> = Vec::new();
+    for (regex, url_format) in &config.hyperlinks_issue_links {
+        for captures in regex.captures_iter(line) {
+            let whole_match = captures.get(0).unwrap();
+            if whole_match.start() == whole_match.end() {
+                continue;
+            }
+            let mut url = url_format.clone();
+            for i in 0..captures.len() {
+                url = url.replace(
+                    &format!("{{{}}}", i),
+                    captures.get(i).map(|m| m.as_str()).unwrap_or(""),
+                );
+            }
+            links.push((whole_match.start(), whole_match.end(), url));
+        }
+    }
+    if links.is_empty() {
+        return Cow::from(line);
+    }
+    links.sort_by_key(|(start, end, _)| (*start, std::cmp::Reverse(*end)));
+    let mut formatted_line = String::new();
+    let mut offset = 0;
+    for (start, end, url) in links {
+        if start < offset {
+            continue;
+        }
+        formatted_line.push_str(&line[offset..start]);
+        formatted_line.push_str(&format_osc8_hyperlink(&url, &line[start..end]));
+        offset = end;
     }
+    formatted_line.push_str(&line[offset..]);
+    Cow::from(formatted_line)
+}
+
+/// Create a hyperlink to the web page for `commit` on the forge hosting the git remote, displaying
+/// `text`. If there is no such page, return `text`.
+pub fn format_osc8_commit_hyperlink(commit: &str, text: &str, config: &Config) -> String {
+    if let Some(GitConfigEntry::GitRemote(repo)) = config
+        .git_config_entries
+        .get(&format!("remote.{}.url", config.hyperlinks_remote))
+    {
+        if let Some(url) =
+            repo.format_commit_url(commit, config.hyperlinks_commit_link_format.as_deref())
+        {
+            return format_osc8_hyperlink(&url, text);
+        }
+    }
+    text.to_string()
+}
+
+fn format_osc8_hyperlink(url: &str, text: &str) -> String {
+    format!(
+        "{osc}8;;{url}{st}{text}{osc}8;;{st}",
+        url = url,
+        text = text,
+        osc = "\x1b]",
+        st = "\x1b\\"
+    )
 }
 
 /// Create a file hyperlink to `path`, displaying `text`.
@@ -51,15 +125,9 @@ pub fn format_osc8_file_hyperlink<'a>(
         } else {
             url = url.replace("{line}", "")
         };
-        Cow::from(format!(
-            "{osc}8;;{url}{st}{text}{osc}8;;{st}",
-            url = url,
-            text = text,
-            osc = "\x1b]",
-            st = "\x1b\\"
-        ))
+        Cow::from(format_osc8_hyperlink(&url, text))
     } else {
-        Cow::from(relative_path)
+        Cow::from(text.to_string())
     }
 }
 
@@ -69,12 +137,15 @@ lazy_static! {
 
 fn format_commit_line_captures_with_osc8_commit_hyperlink<'a, 'b>(
     captures: &'a Captures,
-    github_repo: &'b str,
+    repo: &'b GitRemoteRepo,
+    config: &Config,
 ) -> String {
     let commit = captures.get(2).unwrap().as_str();
     format!(
         "{prefix}{osc}8;;{url}{st}{commit}{osc}8;;{st}{suffix}",
-        url = format_github_commit_url(commit, github_repo),
+        url = repo
+            .format_commit_url(commit, config.hyperlinks_commit_link_format.as_deref())
+            .unwrap_or_default(),
         commit = commit,
         prefix = captures.get(1).unwrap().as_str(),
         suffix = captures.get(3).unwrap().as_str(),
@@ -83,6 +154,178 @@ fn format_commit_line_captures_with_osc8_commit_hyperlink<'a, 'b>(
     )
 }
 
-fn format_github_commit_url(commit: &str, github_repo: &str) -> String {
-    format!("https://github.com/{}/commit/{}", github_repo, commit)
+#[cfg(test)]
+pub mod tests {
+    use std::fs::remove_file;
+
+    use super::*;
+    use crate::tests::integration_test_utils::integration_test_utils;
+
+    const COMMIT_LINE: &str = "commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (HEAD -> master)";
+
+    #[test]
+    fn test_commit_hyperlink_for_gitlab_remote() {
+        let git_config_contents = br#"
+[remote "origin"]
+    url = git@gitlab.com:group/project.git
+"#;
+        let git_config_path = "delta__test_commit_hyperlink_for_gitlab_remote.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--hyperlinks"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        let line = format_commit_line_with_osc8_commit_hyperlink(COMMIT_LINE, &config);
+        assert!(line.contains(
+            "https://gitlab.com/group/project/-/commit/94907c0f136f46dc46ffae2dc92dca9af7eb7c2e"
+        ));
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_commit_hyperlink_with_link_format_and_remote() {
+        let git_config_contents = br#"
+[remote "origin"]
+    url = https://github.com/someone/fork.git
+[remote "upstream"]
+    url = ssh://git@git.corp:2222/team/repo.git
+"#;
+        let git_config_path = "delta__test_commit_hyperlink_with_link_format_and_remote.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--hyperlinks", "--hyperlinks-remote", "upstream"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        // Without a link format, there is no way to link to commits on an unknown host.
+        assert_eq!(
+            format_commit_line_with_osc8_commit_hyperlink(COMMIT_LINE, &config),
+            COMMIT_LINE
+        );
+
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &[
+                "--hyperlinks",
+                "--hyperlinks-remote",
+                "upstream",
+                "--hyperlinks-commit-link-format",
+                "https://{host}/{repo}/commit/{commit}",
+            ],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        let line = format_commit_line_with_osc8_commit_hyperlink(COMMIT_LINE, &config);
+        assert!(line.contains(
+            "https://git.corp/team/repo/commit/94907c0f136f46dc46ffae2dc92dca9af7eb7c2e"
+        ));
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_commit_hyperlink_with_forge() {
+        let git_config_contents = br#"
+[remote "origin"]
+    url = git@gitlab.corp.com:group/project.git
+"#;
+        let git_config_path = "delta__test_commit_hyperlink_with_forge.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--hyperlinks"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert_eq!(
+            format_commit_line_with_osc8_commit_hyperlink(COMMIT_LINE, &config),
+            COMMIT_LINE
+        );
+
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--hyperlinks", "--hyperlinks-forge", "gitlab"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        let line = format_commit_line_with_osc8_commit_hyperlink(COMMIT_LINE, &config);
+        assert!(line.contains(
+            "https://gitlab.corp.com/group/project/-/commit/94907c0f136f46dc46ffae2dc92dca9af7eb7c2e"
+        ));
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_issue_hyperlinks() {
+        let git_config_contents = br#"
+[remote "origin"]
+    url = git@github.com:dandavison/delta.git
+"#;
+        let git_config_path = "delta__test_issue_hyperlinks.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &[
+                "--hyperlinks",
+                "--hyperlinks-issue-links",
+                "JIRA-[0-9]+=https://jira.local/browse/{0}",
+            ],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        let line = format_commit_meta_line_with_osc8_issue_hyperlinks(
+            "    Fix JIRA-567 (#1234). Fixes: org/repo#89",
+            &config,
+        );
+        assert_eq!(
+            line,
+            format!(
+                "    Fix {} ({}). Fixes: {}",
+                format_osc8_hyperlink("https://jira.local/browse/JIRA-567", "JIRA-567"),
+                format_osc8_hyperlink("https://github.com/dandavison/delta/issues/1234", "#1234"),
+                format_osc8_hyperlink("https://github.com/org/repo/issues/89", "org/repo#89"),
+            )
+        );
+        assert_eq!(
+            format_commit_meta_line_with_osc8_issue_hyperlinks("    No references", &config),
+            "    No references"
+        );
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_issue_hyperlinks_with_escaped_space_and_equals() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--hyperlinks",
+            "--hyperlinks-issue-links",
+            r"ticket\x20\x3D\s([0-9]+)=https://tickets.local/show?id={1}",
+        ]);
+        let line =
+            format_commit_meta_line_with_osc8_issue_hyperlinks("    See ticket = 42", &config);
+        assert_eq!(
+            line,
+            format!(
+                "    See {}",
+                format_osc8_hyperlink("https://tickets.local/show?id=42", "ticket = 42")
+            )
+        );
+    }
+
+    #[test]
+    fn test_issue_hyperlinks_for_gitlab_merge_requests() {
+        let git_config_contents = br#"
+[remote "origin"]
+    url = https://gitlab.com/group/project.git
+"#;
+        let git_config_path = "delta__test_issue_hyperlinks_for_gitlab_merge_requests.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--hyperlinks"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        let line = format_commit_meta_line_with_osc8_issue_hyperlinks("    See !42", &config);
+        assert_eq!(
+            line,
+            format!(
+                "    See {}",
+                format_osc8_hyperlink(
+                    "https://gitlab.com/group/project/-/merge_requests/42",
+                    "!42"
+                )
+            )
+        );
+        remove_file(git_config_path).unwrap();
+    }
 }
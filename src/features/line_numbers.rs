@@ -549,6 +549,17 @@ pub mod tests {
         assert_eq!(lines.next().unwrap(), "     ⋮10000│bb = 2");
     }
 
+    #[test]
+    fn test_hyperlinks_outside_git_workdir() {
+        // Without a workdir there is nothing to link to, so the line numbers are displayed as usual.
+        let config = make_config_from_args(&["--line-numbers", "--hyperlinks"]);
+        let output = run_delta(TWO_PLUS_LINES_DIFF, &config);
+        let output = strip_ansi_codes(&output);
+        let mut lines = output.lines().skip(4);
+        assert_eq!(lines.next().unwrap(), "    ⋮ 1  │a = 1");
+        assert_eq!(lines.next().unwrap(), "    ⋮ 2  │b = 2");
+    }
+
     #[test]
     fn test_color_only() {
         let config = make_config_from_args(&["--line-numbers", "--color-only"]);
@@ -30,6 +30,10 @@ pub fn make_builtin_features() -> HashMap<String, BuiltinFeature> {
             "color-only".to_string(),
             color_only::make_feature().into_iter().collect(),
         ),
+        (
+            "colorblind".to_string(),
+            colorblind::make_feature().into_iter().collect(),
+        ),
         (
             "diff-highlight".to_string(),
             diff_highlight::make_feature().into_iter().collect(),
@@ -57,6 +61,12 @@ pub fn make_builtin_features() -> HashMap<String, BuiltinFeature> {
         ),
     ]
     .into_iter()
+    .chain(themes::THEMES.iter().map(|theme| {
+        (
+            theme.name.to_string(),
+            (theme.make_feature)().into_iter().collect(),
+        )
+    }))
     .collect()
 }
 
@@ -83,6 +93,7 @@ macro_rules! builtin_feature {
 }
 
 pub mod color_only;
+pub mod colorblind;
 pub mod diff_highlight;
 pub mod diff_so_fancy;
 pub mod hyperlinks;
@@ -90,6 +101,7 @@ pub mod line_numbers;
 pub mod navigate;
 pub mod raw;
 pub mod side_by_side;
+pub mod themes;
 
 #[cfg(test)]
 pub mod tests {
@@ -98,16 +110,22 @@ pub mod tests {
 
     use crate::cli;
     use crate::features::make_builtin_features;
+    use crate::features::themes::THEMES;
     use crate::tests::integration_test_utils::integration_test_utils::make_options_from_args_and_git_config;
 
     #[test]
     fn test_builtin_features_have_flags_and_these_set_features() {
+        // Themes are enabled by name only, e.g. `features = github-dark`.
         let builtin_features = make_builtin_features();
+        let flag_features: Vec<&String> = builtin_features
+            .keys()
+            .filter(|name| !THEMES.iter().any(|theme| theme.name == name.as_str()))
+            .collect();
         let mut args = vec!["delta".to_string()];
-        args.extend(builtin_features.keys().map(|s| format!("--{}", s)));
-        let opt = cli::Opt::from_iter_and_git_config(args, &mut None);
+        args.extend(flag_features.iter().map(|s| format!("--{}", s)));
+        let opt = cli::Opt::from_iter_and_git_config(args, &mut None).unwrap();
         let features: HashSet<&str> = opt.features.split_whitespace().collect();
-        for feature in builtin_features.keys() {
+        for feature in flag_features {
             assert!(features.contains(feature.as_str()))
         }
     }
@@ -79,6 +79,12 @@ pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
             usize,
             None,
             _opt => 0
+        ),
+        (
+            "max-line-length",
+            usize,
+            None,
+            _opt => 0
         )
     ])
 }
@@ -83,11 +83,7 @@ pub fn paint_minus_and_plus_lines_side_by_side<'a>(
                 None => &State::HunkMinus(None),
             },
             line_numbers_data,
-            if config.keep_plus_minus_markers {
-                "-"
-            } else {
-                ""
-            },
+            &config.minus_marker,
             background_color_extends_to_terminal_width,
             config,
         ));
@@ -100,11 +96,7 @@ pub fn paint_minus_and_plus_lines_side_by_side<'a>(
                 None => &State::HunkPlus(None),
             },
             line_numbers_data,
-            if config.keep_plus_minus_markers {
-                "+"
-            } else {
-                ""
-            },
+            &config.plus_marker,
             background_color_extends_to_terminal_width,
             config,
         ));
@@ -489,4 +481,22 @@ pub mod tests {
         assert_eq!("│ 1  │a = 1         │ 1  │a = 1", lines.next().unwrap());
         assert_eq!("│ 2  │b = 2         │ 2  │bb = 2", lines.next().unwrap());
     }
+
+    #[test]
+    fn test_one_minus_one_plus_line_with_markers() {
+        let config = make_config_from_args(&[
+            "--side-by-side",
+            "--width",
+            "40",
+            "--minus-marker",
+            "◀",
+            "--plus-marker",
+            "▶",
+        ]);
+        let output = run_delta(ONE_MINUS_ONE_PLUS_LINE_DIFF, &config);
+        let output = strip_ansi_codes(&output);
+        let mut lines = output.lines().skip(4);
+        assert_eq!("│ 1  │ a = 1        │ 1  │ a = 1", lines.next().unwrap());
+        assert_eq!("│ 2  │◀b = 2        │ 2  │▶bb = 2", lines.next().unwrap());
+    }
 }
@@ -0,0 +1,190 @@
+use crate::features::OptionValueFunction;
+
+/// A theme is a builtin feature that sets colors only. Each theme is designed for either a light or
+/// a dark terminal background; `delta --show-themes` uses this to filter the themes it displays.
+pub struct Theme {
+    pub name: &'static str,
+    pub is_light: bool,
+    pub make_feature: fn() -> Vec<(String, OptionValueFunction)>,
+}
+
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "github-light",
+        is_light: true,
+        make_feature: make_github_light_feature,
+    },
+    Theme {
+        name: "github-dark",
+        is_light: false,
+        make_feature: make_github_dark_feature,
+    },
+    Theme {
+        name: "colorblind-light",
+        is_light: true,
+        make_feature: make_colorblind_light_feature,
+    },
+    Theme {
+        name: "colorblind-dark",
+        is_light: false,
+        make_feature: make_colorblind_dark_feature,
+    },
+    Theme {
+        name: "high-contrast-light",
+        is_light: true,
+        make_feature: make_high_contrast_light_feature,
+    },
+    Theme {
+        name: "high-contrast-dark",
+        is_light: false,
+        make_feature: make_high_contrast_dark_feature,
+    },
+];
+
+/// Colors of GitHub's light-mode diff view.
+fn make_github_light_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        ("minus-style", String, None, _opt => "syntax #ffebe9"),
+        ("minus-emph-style", String, None, _opt => "syntax #ffc1bc"),
+        ("plus-style", String, None, _opt => "syntax #e6ffec"),
+        ("plus-emph-style", String, None, _opt => "syntax #abf2bc"),
+        ("commit-style", String, None, _opt => "bold #0969da"),
+        ("file-style", String, None, _opt => "bold #24292f"),
+        ("file-decoration-style", String, None, _opt => "#d0d7de ul"),
+        ("hunk-header-decoration-style", String, None, _opt => "#54aeff box"),
+        ("line-numbers-minus-style", String, None, _opt => "#cf222e"),
+        ("line-numbers-plus-style", String, None, _opt => "#1a7f37"),
+        ("line-numbers-zero-style", String, None, _opt => "#8c959f")
+    ])
+}
+
+/// Colors of GitHub's dark-mode diff view.
+fn make_github_dark_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        ("minus-style", String, None, _opt => "syntax #3f1d22"),
+        ("minus-emph-style", String, None, _opt => "syntax #792e2e"),
+        ("plus-style", String, None, _opt => "syntax #12361f"),
+        ("plus-emph-style", String, None, _opt => "syntax #1f6f3a"),
+        ("commit-style", String, None, _opt => "bold #58a6ff"),
+        ("file-style", String, None, _opt => "bold #e6edf3"),
+        ("file-decoration-style", String, None, _opt => "#30363d ul"),
+        ("hunk-header-decoration-style", String, None, _opt => "#388bfd box"),
+        ("line-numbers-minus-style", String, None, _opt => "#f85149"),
+        ("line-numbers-plus-style", String, None, _opt => "#3fb950"),
+        ("line-numbers-zero-style", String, None, _opt => "#6e7681")
+    ])
+}
+
+/// Orange for removed lines and blue for added lines, which remain distinguishable under the
+/// common forms of color blindness (the colors are taken from the Okabe-Ito palette).
+fn make_colorblind_light_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        ("minus-style", String, None, _opt => "syntax #ffe6cc"),
+        ("minus-emph-style", String, None, _opt => "syntax bold #ffbf80"),
+        ("plus-style", String, None, _opt => "syntax #dbeaff"),
+        ("plus-emph-style", String, None, _opt => "syntax bold #a3c8ff"),
+        ("line-numbers-minus-style", String, None, _opt => "#d55e00"),
+        ("line-numbers-plus-style", String, None, _opt => "#0072b2")
+    ])
+}
+
+/// The dark-background counterpart of colorblind-light.
+fn make_colorblind_dark_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        ("minus-style", String, None, _opt => "syntax #4a2a00"),
+        ("minus-emph-style", String, None, _opt => "syntax bold #8a4f00"),
+        ("plus-style", String, None, _opt => "syntax #0b2f50"),
+        ("plus-emph-style", String, None, _opt => "syntax bold #1a5b94"),
+        ("line-numbers-minus-style", String, None, _opt => "#e69f00"),
+        ("line-numbers-plus-style", String, None, _opt => "#56b4e9")
+    ])
+}
+
+/// Black text on saturated backgrounds, with emphasized sections also underlined.
+fn make_high_contrast_light_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        ("minus-style", String, None, _opt => "black #ffc8c8"),
+        ("minus-emph-style", String, None, _opt => "bold ul black #ff8080"),
+        ("plus-style", String, None, _opt => "black #c8ffc8"),
+        ("plus-emph-style", String, None, _opt => "bold ul black #70e070"),
+        ("zero-style", String, None, _opt => "black"),
+        ("commit-style", String, None, _opt => "bold black"),
+        ("file-style", String, None, _opt => "bold blue"),
+        ("file-decoration-style", String, None, _opt => "bold blue box"),
+        ("hunk-header-decoration-style", String, None, _opt => "bold blue box"),
+        ("line-numbers-minus-style", String, None, _opt => "bold #b00000"),
+        ("line-numbers-plus-style", String, None, _opt => "bold #006000"),
+        ("line-numbers-zero-style", String, None, _opt => "black")
+    ])
+}
+
+/// White text on saturated backgrounds, with emphasized sections also underlined.
+fn make_high_contrast_dark_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        ("minus-style", String, None, _opt => "white #700000"),
+        ("minus-emph-style", String, None, _opt => "bold ul white #c00000"),
+        ("plus-style", String, None, _opt => "white #005000"),
+        ("plus-emph-style", String, None, _opt => "bold ul white #008000"),
+        ("zero-style", String, None, _opt => "white"),
+        ("commit-style", String, None, _opt => "bold white"),
+        ("file-style", String, None, _opt => "bold yellow"),
+        ("file-decoration-style", String, None, _opt => "bold yellow box"),
+        ("hunk-header-decoration-style", String, None, _opt => "bold cyan box"),
+        ("line-numbers-minus-style", String, None, _opt => "bold #ff5f5f"),
+        ("line-numbers-plus-style", String, None, _opt => "bold #5fff5f"),
+        ("line-numbers-zero-style", String, None, _opt => "white")
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_file;
+
+    use crate::features::make_builtin_features;
+    use crate::features::themes::THEMES;
+    use crate::tests::integration_test_utils::integration_test_utils;
+
+    #[test]
+    fn test_themes_are_builtin_features() {
+        let builtin_features = make_builtin_features();
+        for theme in THEMES {
+            assert!(builtin_features.contains_key(theme.name));
+        }
+    }
+
+    #[test]
+    fn test_theme_sets_styles() {
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--features", "github-dark"],
+            None,
+            None,
+        );
+        assert_eq!(opt.minus_style, "syntax #3f1d22");
+        assert_eq!(opt.plus_emph_style, "syntax #1f6f3a");
+        assert_eq!(opt.line_numbers_plus_style, "#3fb950");
+
+        let config =
+            integration_test_utils::make_config_from_args(&["--features", "high-contrast-dark"]);
+        assert!(config.minus_emph_style.ansi_term_style.is_underline);
+        assert!(config.plus_emph_style.ansi_term_style.is_bold);
+    }
+
+    #[test]
+    fn test_theme_is_overridden_by_git_config() {
+        let git_config_contents = b"
+[delta]
+    features = colorblind-light
+    plus-style = blue
+";
+        let git_config_path = "delta__test_theme_is_overridden_by_git_config.gitconfig";
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert_eq!(opt.minus_style, "syntax #ffe6cc");
+        assert_eq!(opt.plus_style, "blue");
+
+        remove_file(git_config_path).unwrap();
+    }
+}
@@ -14,3 +14,17 @@ pub fn format_raw_line<'a>(line: &'a str, config: &Config) -> Cow<'a, str> {
         Cow::from(line)
     }
 }
+
+/// As format_raw_line, but also link references to issues etc. (commit metadata lines only).
+pub fn format_raw_commit_meta_line<'a>(line: &'a str, config: &Config) -> Cow<'a, str> {
+    if config.hyperlinks && atty::is(atty::Stream::Stdout) {
+        let line =
+            features::hyperlinks::format_commit_meta_line_with_osc8_issue_hyperlinks(line, config);
+        Cow::from(
+            features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(&line, config)
+                .into_owned(),
+        )
+    } else {
+        Cow::from(line)
+    }
+}
@@ -0,0 +1,138 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+// Sentinel values returned by libgit2 for attributes that are set (`attr`) or unset (`-attr`).
+const GIT_ATTR_TRUE: &str = "[internal]__TRUE__";
+const GIT_ATTR_FALSE: &str = "[internal]__FALSE__";
+
+/// Access to the gitattributes (.gitattributes files, .git/info/attributes, and the index) of the
+/// repository that the diff is being viewed in.
+pub struct GitAttributes {
+    // A repository may not be used from several threads at once; files are painted in parallel.
+    repo: Mutex<git2::Repository>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GitAttributeValue {
+    Set,
+    Unset,
+    Value(String),
+}
+
+impl GitAttributes {
+    pub fn try_create(workdir: &Path) -> Option<Self> {
+        git2::Repository::open(workdir)
+            .ok()
+            .map(|repo| Self {
+                repo: Mutex::new(repo),
+            })
+    }
+
+    /// Return the value of attribute `name` for the file at `path` (relative to the repository
+    /// root), or None if the attribute is unspecified.
+    pub fn get(&self, path: &str, name: &str) -> Option<GitAttributeValue> {
+        match self
+            .repo
+            .lock()
+            .unwrap()
+            .get_attr(Path::new(path), name, git2::AttrCheckFlags::default())
+        {
+            Ok(Some(GIT_ATTR_TRUE)) => Some(GitAttributeValue::Set),
+            Ok(Some(GIT_ATTR_FALSE)) => Some(GitAttributeValue::Unset),
+            Ok(Some(value)) => Some(GitAttributeValue::Value(value.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Is the file marked as generated (`linguist-generated`) or as not to be diffed (`-diff`)?
+    pub fn is_generated(&self, path: &str) -> bool {
+        let is_true = |value| match value {
+            Some(GitAttributeValue::Set) => true,
+            Some(GitAttributeValue::Value(value)) => value == "true",
+            _ => false,
+        };
+        is_true(self.get(path, "linguist-generated"))
+            || self.get(path, "diff") == Some(GitAttributeValue::Unset)
+    }
+
+    /// Return the name of the diff driver assigned to the file by a `diff=<driver>` attribute.
+    pub fn get_diff_driver(&self, path: &str) -> Option<String> {
+        match self.get(path, "diff") {
+            Some(GitAttributeValue::Value(driver)) => Some(driver),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Create a git repository in a temporary directory, containing a .gitattributes file with
+    /// the supplied contents.
+    pub fn make_repo_with_gitattributes(name: &str, gitattributes: &str) -> PathBuf {
+        let workdir = std::env::temp_dir().join(format!("delta__test_{}", name));
+        let _ = fs::remove_dir_all(&workdir);
+        git2::Repository::init(&workdir).unwrap();
+        fs::write(workdir.join(".gitattributes"), gitattributes).unwrap();
+        workdir
+    }
+
+    #[test]
+    fn test_get() {
+        let workdir = make_repo_with_gitattributes(
+            "git_attributes_get",
+            "*.pb.go linguist-generated\n*.bin -diff\nJenkinsfile diff=groovy\n",
+        );
+        let git_attributes = GitAttributes::try_create(&workdir).unwrap();
+        assert_eq!(
+            git_attributes.get("x/y.pb.go", "linguist-generated"),
+            Some(GitAttributeValue::Set)
+        );
+        assert_eq!(
+            git_attributes.get("data.bin", "diff"),
+            Some(GitAttributeValue::Unset)
+        );
+        assert_eq!(
+            git_attributes.get("Jenkinsfile", "diff"),
+            Some(GitAttributeValue::Value("groovy".to_string()))
+        );
+        assert_eq!(git_attributes.get("src/main.rs", "diff"), None);
+        fs::remove_dir_all(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_is_generated() {
+        let workdir = make_repo_with_gitattributes(
+            "git_attributes_is_generated",
+            "*.pb.go linguist-generated\nvendor/** linguist-generated=true\n*.bin -diff\n\
+             keep.pb.go -linguist-generated\n",
+        );
+        let git_attributes = GitAttributes::try_create(&workdir).unwrap();
+        assert!(git_attributes.is_generated("api/service.pb.go"));
+        assert!(git_attributes.is_generated("vendor/lib/x.c"));
+        assert!(git_attributes.is_generated("data.bin"));
+        assert!(!git_attributes.is_generated("keep.pb.go"));
+        assert!(!git_attributes.is_generated("src/main.rs"));
+        fs::remove_dir_all(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_get_diff_driver() {
+        let workdir = make_repo_with_gitattributes(
+            "git_attributes_get_diff_driver",
+            "Jenkinsfile diff=groovy\n*.bin -diff\n",
+        );
+        let git_attributes = GitAttributes::try_create(&workdir).unwrap();
+        assert_eq!(
+            git_attributes.get_diff_driver("ci/Jenkinsfile"),
+            Some("groovy".to_string())
+        );
+        assert_eq!(git_attributes.get_diff_driver("data.bin"), None);
+        assert_eq!(git_attributes.get_diff_driver("src/main.rs"), None);
+        fs::remove_dir_all(workdir).unwrap();
+    }
+}
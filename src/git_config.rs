@@ -1,6 +1,5 @@
 #[cfg(test)]
 use std::path::Path;
-use std::process;
 
 use git2;
 
@@ -11,7 +10,9 @@ pub struct GitConfig {
 }
 
 impl GitConfig {
-    pub fn try_create() -> Option<Self> {
+    /// Return the git config of the repository containing the current directory, or the user's
+    /// git config if there is no such repository, or None if neither can be opened.
+    pub fn try_create() -> Result<Option<Self>, String> {
         let repo = match std::env::current_dir() {
             Ok(dir) => git2::Repository::discover(dir).ok(),
             _ => None,
@@ -22,17 +23,16 @@ impl GitConfig {
         };
         match config {
             Some(mut config) => {
-                let config = config.snapshot().unwrap_or_else(|err| {
-                    eprintln!("Failed to read git config: {}", err);
-                    process::exit(1)
-                });
-                Some(Self {
+                let config = config
+                    .snapshot()
+                    .map_err(|err| format!("Failed to read git config: {}", err))?;
+                Ok(Some(Self {
                     config,
                     repo,
                     enabled: true,
-                })
+                }))
             }
-            None => None,
+            None => Ok(None),
         }
     }
 
@@ -45,6 +45,22 @@ impl GitConfig {
         }
     }
 
+    /// Return the (name, value) pairs of all entries whose names match `regex`.
+    pub fn get_entries_matching(&self, regex: &str) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        if !self.enabled {
+            return entries;
+        }
+        if let Ok(config_entries) = self.config.entries(Some(regex)) {
+            for entry in (&config_entries).flatten() {
+                if let (Some(name), Some(value)) = (entry.name(), entry.value()) {
+                    entries.push((name.to_string(), value.to_string()));
+                }
+            }
+        }
+        entries
+    }
+
     pub fn get<T>(&self, key: &str) -> Option<T>
     where
         T: GitConfigGet,
@@ -12,28 +12,244 @@ pub enum GitConfigEntry {
     Style(String),
     GitRemote(GitRemoteRepo),
     Path(PathBuf),
+    Bool(bool),
+    String(String),
 }
 
-#[derive(Clone, Debug)]
-pub enum GitRemoteRepo {
-    GitHubRepo(String),
+/// The software hosting a git remote, which determines the form of commit URLs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GitForge {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+    SourceHut,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GitRemoteRepo {
+    pub forge: GitForge,
+    pub host: String,
+    pub repo: String,
 }
 
 lazy_static! {
-    static ref GITHUB_REMOTE_URL: Regex = Regex::new(r"github\.com[:/]([^/]+)/(.+)\.git").unwrap();
+    // Matches https://host/repo.git, ssh://user@host:port/repo.git, git://host/repo, and the
+    // scp-like syntax user@host:repo.git.
+    static ref GIT_REMOTE_URL: Regex = Regex::new(
+        r"^(?:[a-z][a-z0-9+.-]*://)?(?:[^@/]+@)?([A-Za-z0-9][A-Za-z0-9.-]*)(?::[0-9]+)?[:/]/?(.+?)(?:\.git)?/?$"
+    )
+    .unwrap();
+}
+
+impl GitRemoteRepo {
+    /// Return the URL of the web page for `commit`, or None if the forge is not known. The
+    /// placeholders "{host}", "{repo}" and "{commit}" in `commit_link_format`, if supplied, are
+    /// replaced by the remote's host, the repository path, and the commit hash.
+    pub fn format_commit_url(
+        &self,
+        commit: &str,
+        commit_link_format: Option<&str>,
+    ) -> Option<String> {
+        let format = match (commit_link_format, self.forge) {
+            (Some(format), _) => format,
+            (None, GitForge::GitHub) | (None, GitForge::Gitea) | (None, GitForge::SourceHut) => {
+                "https://{host}/{repo}/commit/{commit}"
+            }
+            (None, GitForge::GitLab) => "https://{host}/{repo}/-/commit/{commit}",
+            (None, GitForge::Bitbucket) => "https://{host}/{repo}/commits/{commit}",
+            (None, GitForge::Other) => return None,
+        };
+        Some(
+            format
+                .replace("{host}", &self.host)
+                .replace("{repo}", &self.repo)
+                .replace("{commit}", commit),
+        )
+    }
+
+    /// Return (regex, URL) rules linking the forge's issue and pull/merge request references
+    /// (e.g. "#123") to their web pages. In the URLs, "{1}", "{2}", etc. are placeholders for the
+    /// regex capture groups.
+    pub fn issue_link_rules(&self) -> Vec<(String, String)> {
+        let rules: Vec<(&str, &str)> = match self.forge {
+            GitForge::GitHub | GitForge::Gitea => vec![
+                (
+                    r"\b([\w.-]+/[\w.-]+)#([0-9]+)\b",
+                    "https://{host}/{1}/issues/{2}",
+                ),
+                (r"\B#([0-9]+)\b", "https://{host}/{repo}/issues/{1}"),
+            ],
+            GitForge::GitLab => vec![
+                (
+                    r"\b([\w.-]+/[\w.-]+)#([0-9]+)\b",
+                    "https://{host}/{1}/-/issues/{2}",
+                ),
+                (r"\B#([0-9]+)\b", "https://{host}/{repo}/-/issues/{1}"),
+                (
+                    r"\B!([0-9]+)\b",
+                    "https://{host}/{repo}/-/merge_requests/{1}",
+                ),
+            ],
+            GitForge::Bitbucket => vec![(r"\B#([0-9]+)\b", "https://{host}/{repo}/issues/{1}")],
+            GitForge::SourceHut | GitForge::Other => vec![],
+        };
+        rules
+            .into_iter()
+            .map(|(regex, url)| {
+                (
+                    regex.to_string(),
+                    url.replace("{host}", &self.host)
+                        .replace("{repo}", &self.repo),
+                )
+            })
+            .collect()
+    }
+}
+
+impl GitForge {
+    /// Return the forge serving the public host `host`. Self-hosted forges are not recognized:
+    /// see --hyperlinks-forge.
+    fn from_host(host: &str) -> Self {
+        match host {
+            "github.com" => GitForge::GitHub,
+            "gitlab.com" => GitForge::GitLab,
+            "bitbucket.org" => GitForge::Bitbucket,
+            "codeberg.org" | "gitea.com" => GitForge::Gitea,
+            "sr.ht" => GitForge::SourceHut,
+            host if host.ends_with(".sr.ht") => GitForge::SourceHut,
+            _ => GitForge::Other,
+        }
+    }
+}
+
+impl FromStr for GitForge {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(GitForge::GitHub),
+            "gitlab" => Ok(GitForge::GitLab),
+            "bitbucket" => Ok(GitForge::Bitbucket),
+            "gitea" => Ok(GitForge::Gitea),
+            "sourcehut" => Ok(GitForge::SourceHut),
+            _ => Err(format!("Not a recognized forge: {}", s).into()),
+        }
+    }
 }
 
 impl FromStr for GitRemoteRepo {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(caps) = GITHUB_REMOTE_URL.captures(s) {
-            Ok(Self::GitHubRepo(format!(
-                "{user}/{repo}",
-                user = caps.get(1).unwrap().as_str(),
-                repo = caps.get(2).unwrap().as_str()
-            )))
+        if let Some(caps) = GIT_REMOTE_URL.captures(s.trim()) {
+            let host = caps.get(1).unwrap().as_str().to_lowercase();
+            Ok(Self {
+                forge: GitForge::from_host(&host),
+                host,
+                repo: caps.get(2).unwrap().as_str().to_string(),
+            })
         } else {
-            Err("Not a GitHub repo.".into())
+            Err(format!("Not a recognized git remote URL: {}", s).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> GitRemoteRepo {
+        GitRemoteRepo::from_str(url).unwrap()
+    }
+
+    #[test]
+    fn test_parse_github_urls() {
+        for url in &[
+            "https://github.com/dandavison/delta.git",
+            "https://github.com/dandavison/delta",
+            "git@github.com:dandavison/delta.git",
+            "ssh://git@github.com/dandavison/delta.git",
+            "ssh://git@github.com:22/dandavison/delta",
+        ] {
+            assert_eq!(
+                parse(url),
+                GitRemoteRepo {
+                    forge: GitForge::GitHub,
+                    host: "github.com".to_string(),
+                    repo: "dandavison/delta".to_string(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_other_forge_urls() {
+        let gitlab = parse("git@gitlab.com:group/subgroup/project.git");
+        assert_eq!(gitlab.forge, GitForge::GitLab);
+        assert_eq!(gitlab.repo, "group/subgroup/project");
+        assert_eq!(
+            parse("https://user@bitbucket.org/team/repo.git").forge,
+            GitForge::Bitbucket
+        );
+        assert_eq!(
+            parse("https://codeberg.org/user/repo").forge,
+            GitForge::Gitea
+        );
+        assert_eq!(parse("https://gitea.com/user/repo").forge, GitForge::Gitea);
+        let sourcehut = parse("git@git.sr.ht:~user/repo");
+        assert_eq!(sourcehut.forge, GitForge::SourceHut);
+        assert_eq!(sourcehut.repo, "~user/repo");
+        let other = parse("ssh://git@git.corp:2222/team/repo.git");
+        assert_eq!(other.forge, GitForge::Other);
+        assert_eq!(other.host, "git.corp");
+        assert_eq!(other.repo, "team/repo");
+        assert!(GitRemoteRepo::from_str("/home/user/repo").is_err());
+    }
+
+    #[test]
+    fn test_self_hosted_forges_are_not_recognized_by_name() {
+        for url in &[
+            "git@github.corp.com:team/repo.git",
+            "https://gitlab.example.com/group/project",
+            "https://bitbucket.internal/team/repo",
+            "https://gitea.example.com/user/repo",
+            "https://notgithub.com/user/repo",
+        ] {
+            assert_eq!(parse(url).forge, GitForge::Other, "{}", url);
         }
+        assert_eq!(GitForge::from_str("GitLab").unwrap(), GitForge::GitLab);
+        assert!(GitForge::from_str("gitlabs").is_err());
+    }
+
+    #[test]
+    fn test_format_commit_url() {
+        let commit = "94907c0f136f46dc46ffae2dc92dca9af7eb7c2e";
+        assert_eq!(
+            parse("git@github.com:dandavison/delta.git").format_commit_url(commit, None),
+            Some(format!(
+                "https://github.com/dandavison/delta/commit/{}",
+                commit
+            ))
+        );
+        assert_eq!(
+            parse("git@gitlab.com:group/project.git").format_commit_url(commit, None),
+            Some(format!(
+                "https://gitlab.com/group/project/-/commit/{}",
+                commit
+            ))
+        );
+        assert_eq!(
+            parse("git@bitbucket.org:team/repo.git").format_commit_url(commit, None),
+            Some(format!(
+                "https://bitbucket.org/team/repo/commits/{}",
+                commit
+            ))
+        );
+        let other = parse("git@git.corp:team/repo.git");
+        assert_eq!(other.format_commit_url(commit, None), None);
+        assert_eq!(
+            other.format_commit_url(commit, Some("https://{host}/{repo}/commit/{commit}")),
+            Some(format!("https://git.corp/team/repo/commit/{}", commit))
+        );
     }
 }
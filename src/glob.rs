@@ -0,0 +1,176 @@
+use std::fmt;
+
+use regex::Regex;
+
+use crate::errors::*;
+
+/// A shell-style glob pattern matched against file paths.
+///
+/// Supported syntax:
+///   `*`      matches any sequence of characters other than `/`
+///   `**`     matches any sequence of characters, including `/`
+///   `?`      matches a single character other than `/`
+///   `[abc]`  matches one character in the set (`[!abc]` or `[^abc]` to negate)
+///   `{a,b}`  matches any of the comma-separated alternatives
+///
+/// As in .gitignore, a pattern that contains no `/` is matched against the file name only, so
+/// that `*.lock` matches `Cargo.lock` and `vendor/foo/Cargo.lock` alike. Otherwise the pattern is
+/// matched against the whole path, relative to the repository root.
+#[derive(Clone, Debug)]
+pub struct Glob {
+    pattern: String,
+    regex: Regex,
+    match_file_name_only: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let match_file_name_only = !pattern.contains('/');
+        let regex_string = glob_to_regex_string(pattern.trim_start_matches('/'))?;
+        let regex = Regex::new(&regex_string)
+            .map_err(|err| format!("Invalid glob pattern: {}: {}", pattern, err))?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+            match_file_name_only,
+        })
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        if self.match_file_name_only {
+            self.regex.is_match(path.rsplit('/').next().unwrap_or(path))
+        } else {
+            self.regex.is_match(path)
+        }
+    }
+}
+
+impl fmt::Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Parse a whitespace-separated list of glob patterns.
+pub fn parse_globs(patterns: &str) -> Result<Vec<Glob>> {
+    patterns.split_whitespace().map(Glob::new).collect()
+}
+
+fn glob_to_regex_string(pattern: &str) -> Result<String> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    let mut in_alternation = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        // `**/` matches zero or more leading directories.
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                } else {
+                    regex.push_str("[^/]*");
+                }
+            }
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if let Some(&negation) = chars.peek() {
+                    if negation == '!' || negation == '^' {
+                        chars.next();
+                        regex.push('^');
+                    }
+                }
+                let mut closed = false;
+                for c in &mut chars {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                if !closed {
+                    return Err(format!("Invalid glob pattern: {}: unclosed '['", pattern).into());
+                }
+                regex.push(']');
+            }
+            '{' if !in_alternation => {
+                in_alternation = true;
+                regex.push_str("(?:");
+            }
+            ',' if in_alternation => regex.push('|'),
+            '}' if in_alternation => {
+                in_alternation = false;
+                regex.push(')');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    if in_alternation {
+        return Err(format!("Invalid glob pattern: {}: unclosed '{{'", pattern).into());
+    }
+    regex.push('$');
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, path: &str) -> bool {
+        Glob::new(pattern).unwrap().is_match(path)
+    }
+
+    #[test]
+    fn test_glob_without_slash_matches_file_name() {
+        assert!(is_match("*.lock", "Cargo.lock"));
+        assert!(is_match("*.lock", "vendor/foo/Cargo.lock"));
+        assert!(!is_match("*.lock", "Cargo.lock.orig"));
+        assert!(is_match("Cargo.????", "a/Cargo.toml"));
+    }
+
+    #[test]
+    fn test_glob_with_slash_matches_path() {
+        assert!(is_match("vendor/*", "vendor/x.rs"));
+        assert!(!is_match("vendor/*", "vendor/a/x.rs"));
+        assert!(!is_match("vendor/*", "src/vendor/x.rs"));
+        assert!(is_match("/vendor/*", "vendor/x.rs"));
+        assert!(is_match("vendor/**", "vendor/a/b/x.rs"));
+        assert!(is_match("**/generated/*.pb.go", "generated/x.pb.go"));
+        assert!(is_match("**/generated/*.pb.go", "a/b/generated/x.pb.go"));
+    }
+
+    #[test]
+    fn test_glob_character_classes_and_alternation() {
+        assert!(is_match("*.[ch]", "x.c"));
+        assert!(is_match("*.[ch]", "x.h"));
+        assert!(!is_match("*.[!ch]", "x.h"));
+        assert!(is_match("*.{lock,sum}", "go.sum"));
+        assert!(is_match("*.{lock,sum}", "yarn.lock"));
+        assert!(!is_match("*.{lock,sum}", "go.mod"));
+        assert!(is_match("a+b.txt", "a+b.txt"));
+        assert!(!is_match("a.txt", "abtxt"));
+    }
+
+    #[test]
+    fn test_invalid_globs() {
+        assert!(Glob::new("*.[ch").is_err());
+        assert!(Glob::new("*.{lock,sum").is_err());
+    }
+
+    #[test]
+    fn test_parse_globs() {
+        let globs = parse_globs("  *.lock   vendor/** ").unwrap();
+        assert_eq!(
+            globs.iter().map(|g| g.to_string()).collect::<Vec<_>>(),
+            vec!["*.lock", "vendor/**"]
+        );
+    }
+}
@@ -0,0 +1,251 @@
+/// Standalone HTML output (--output-format=html).
+///
+/// The HTML is produced from exactly the output that delta would write to a terminal: the style
+/// sections painted by `Painter::paint_line` (syntax highlighting superimposed with diff styles),
+/// the decorations drawn by `draw`, and the hyperlinks of the hyperlinks feature are all emitted as
+/// ANSI escape sequences, and these are translated here to inline CSS and `<a>` tags. Thus line
+/// numbers, side-by-side panels and decorations look the same as they do in the terminal.
+use std::io::{BufRead, Write};
+
+use bytelines::ByteLines;
+
+use crate::ansi::render::{css_color, escape, render, Renderer, State};
+use crate::config::Config;
+use crate::delta::delta;
+
+/// Run delta on `lines`, writing a standalone HTML page to `writer`.
+pub fn delta_html<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<()>
+where
+    I: BufRead + Send,
+{
+    let mut output = Vec::new();
+    delta(lines, &mut output, config)?;
+    writer.write_all(ansi_to_html(&String::from_utf8_lossy(&output), config).as_bytes())
+}
+
+/// Convert text containing ANSI escape sequences, as emitted by delta, to an HTML page.
+pub fn ansi_to_html(ansi: &str, config: &Config) -> String {
+    let mut renderer = HtmlRenderer::new();
+    let state = render(ansi, &mut renderer, config);
+    if !renderer.line.is_empty() {
+        renderer.end_line(&state);
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>delta</title>
+<style>
+body {{ margin: 0; }}
+.delta {{ font-family: monospace; line-height: 1.3; padding: 1em;{page_style} }}
+.line {{ display: flex; white-space: pre; min-height: 1.3em; }}
+.fill {{ flex-grow: 1; }}
+a {{ color: inherit; }}
+</style>
+</head>
+<body>
+<div class="delta">
+{lines}</div>
+</body>
+</html>
+"#,
+        page_style = [
+            state
+                .page_foreground
+                .map(|color| format!(" color: {};", css_color(color))),
+            state
+                .page_background
+                .map(|color| format!(" background-color: {};", css_color(color))),
+        ]
+        .iter()
+        .flatten()
+        .map(|s| s.as_str())
+        .collect::<String>(),
+        lines = renderer.html
+    )
+}
+
+struct HtmlRenderer {
+    /// Completed lines.
+    html: String,
+    /// The HTML of the current line so far.
+    line: String,
+    /// Text not yet emitted, all of which has the current style.
+    text: String,
+}
+
+impl HtmlRenderer {
+    fn new() -> Self {
+        Self {
+            html: String::new(),
+            line: String::new(),
+            text: String::new(),
+        }
+    }
+
+    fn css(state: &State) -> String {
+        let (foreground, background) = state.effective_colors();
+        let mut declarations = Vec::new();
+        if let Some(color) = foreground {
+            declarations.push(format!("color: {}", css_color(color)));
+        }
+        if let Some(color) = background {
+            declarations.push(format!("background-color: {}", css_color(color)));
+        }
+        if state.style.is_bold {
+            declarations.push("font-weight: bold".to_string());
+        }
+        if state.style.is_dimmed {
+            declarations.push("opacity: 0.6".to_string());
+        }
+        if state.style.is_italic {
+            declarations.push("font-style: italic".to_string());
+        }
+        if state.style.is_hidden {
+            declarations.push("visibility: hidden".to_string());
+        }
+        let text_decorations = state.text_decorations();
+        if !text_decorations.is_empty() {
+            declarations.push(format!("text-decoration: {}", text_decorations.join(" ")));
+        }
+        declarations.join("; ")
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn print(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    /// Emit the pending text as a span with the current style.
+    fn flush_text(&mut self, state: &State) {
+        if self.text.is_empty() {
+            return;
+        }
+        let text = escape(&self.text);
+        let css = Self::css(state);
+        if css.is_empty() {
+            self.line.push_str(&format!("<span>{}</span>", text));
+        } else {
+            self.line
+                .push_str(&format!("<span style=\"{}\">{}</span>", css, text));
+        }
+        self.text.clear();
+    }
+
+    fn end_line(&mut self, state: &State) {
+        if state.link.is_some() {
+            self.line.push_str("</a>");
+        }
+        self.html
+            .push_str(&format!("<div class=\"line\">{}</div>\n", self.line));
+        self.line.clear();
+        if let Some(url) = &state.link {
+            self.line.push_str(&format!("<a href=\"{}\">", escape(url)));
+        }
+    }
+
+    fn fill_line(&mut self, state: &State) {
+        if let Some(background) = state.effective_colors().1 {
+            self.line.push_str(&format!(
+                "<span class=\"fill\" style=\"background-color: {}\"></span>",
+                css_color(background)
+            ));
+        }
+    }
+
+    fn set_link(&mut self, previous_link: Option<&str>, state: &State) {
+        if previous_link.is_some() {
+            self.line.push_str("</a>");
+        }
+        if let Some(url) = &state.link {
+            self.line.push_str(&format!("<a href=\"{}\">", escape(url)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::ansi::render::tests::DIFF;
+    use crate::tests::integration_test_utils::integration_test_utils::make_config_from_args;
+
+    #[test]
+    fn test_delta_html() {
+        let config = make_config_from_args(&[
+            "--output-format",
+            "html",
+            "--syntax-theme",
+            "none",
+            "--side-by-side",
+            "--line-numbers",
+            "--minus-style",
+            "#ff0000",
+            "--plus-style",
+            "#00ff00",
+        ]);
+        let mut output = Vec::new();
+        delta_html(
+            ByteLines::new(BufReader::new(DIFF.as_bytes())),
+            &mut output,
+            &config,
+        )
+        .unwrap();
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(!html.contains('\x1b'));
+        assert!(html.contains(concat!(
+            "<span style=\"color: #ff0000\">a </span>",
+            "<span style=\"background-color: #901011\">&lt;</span>",
+            "<span style=\"color: #ff0000\"> b</span>"
+        )));
+        assert!(html.contains("<span style=\"background-color: #006000\">&gt;</span>"));
+        assert!(html.contains("<div class=\"line\">"));
+    }
+
+    #[test]
+    fn test_ansi_to_html_styles() {
+        let config = make_config_from_args(&["--syntax-theme", "none"]);
+        let html = ansi_to_html(
+            "a\x1b[1;31mb<\x1b[0m\x1b[48;2;1;2;3mc\x1b[0K\x1b[0m\n",
+            &config,
+        );
+        assert!(html.contains(concat!(
+            "<div class=\"line\"><span>a</span>",
+            "<span style=\"color: #ce0000; font-weight: bold\">b&lt;</span>",
+            "<span style=\"background-color: #010203\">c</span>",
+            "<span class=\"fill\" style=\"background-color: #010203\"></span></div>\n"
+        )));
+    }
+
+    #[test]
+    fn test_ansi_to_html_incremental_styles() {
+        let config = make_config_from_args(&["--syntax-theme", "none"]);
+        let html = ansi_to_html("\x1b[31;4ma\x1b[24mb\x1b[39;53mc\x1b[m\n", &config);
+        assert!(html.contains(concat!(
+            "<span style=\"color: #ce0000; text-decoration: underline\">a</span>",
+            "<span style=\"color: #ce0000\">b</span>",
+            "<span style=\"text-decoration: overline\">c</span>",
+        )));
+    }
+
+    #[test]
+    fn test_ansi_to_html_hyperlinks() {
+        let config = make_config_from_args(&["--syntax-theme", "none"]);
+        let html = ansi_to_html(
+            "\x1b]8;;https://example.com/?a=1&b=2\x1b\\link\x1b]8;;\x1b\\ text\n",
+            &config,
+        );
+        assert!(html.contains(
+            "<a href=\"https://example.com/?a=1&amp;b=2\"><span>link</span></a><span> text</span>"
+        ));
+    }
+}
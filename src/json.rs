@@ -0,0 +1,515 @@
+/// JSON output (--output-format=json).
+///
+/// Instead of rendering the diff, delta emits a stream of records describing it, one JSON object
+/// per line: a "commit" record for each commit, a "file" record for each file, a "hunk" record for
+/// each hunk, and a "line" record for each line in a hunk. Line records carry the within-line edits
+/// inferred by `edits::infer_edits`: the line's text is divided into emph (changed) and non-emph
+/// sections, and removed and added lines that were paired with each other by the edit inference
+/// give the line number of their counterpart. With --json-syntax-scopes, line records also divide
+/// the text into sections labeled with the stack of syntax scopes assigned by syntect.
+use std::io::{self, BufRead, Write};
+
+use bytelines::ByteLines;
+use serde::Serialize;
+use syntect::easy::ScopeRegionIterator;
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference};
+
+use crate::config::Config;
+use crate::edits;
+use crate::events::{Event, Events, LineKind};
+use crate::paint::Painter;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Commit {
+        hash: String,
+    },
+    File {
+        minus_file: String,
+        plus_file: String,
+    },
+    Hunk {
+        minus_start: usize,
+        minus_count: usize,
+        plus_start: usize,
+        plus_count: usize,
+        code_fragment: String,
+    },
+    Line {
+        state: LineState,
+        minus_line_number: Option<usize>,
+        plus_line_number: Option<usize>,
+        /// For a removed or added line paired with a line on the other side by the edit inference,
+        /// the line number of that line.
+        paired_line_number: Option<usize>,
+        text: String,
+        sections: Vec<EditSection>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        syntax_sections: Option<Vec<SyntaxSection>>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineState {
+    Minus,
+    Plus,
+    Zero,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EditSection {
+    pub text: String,
+    pub emph: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SyntaxSection {
+    pub text: String,
+    pub scopes: Vec<String>,
+}
+
+/// The edit operations with which `edits::infer_edits` annotates line sections.
+#[derive(Clone, Copy, PartialEq)]
+enum Emph {
+    Emph,
+    NonEmph,
+}
+
+/// Run delta on `lines`, writing JSON records to `writer`.
+pub fn delta_json<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<()>
+where
+    I: BufRead,
+{
+    parse_records(lines, config, &mut |record| {
+        serde_json::to_writer(&mut *writer, &record)?;
+        writeln!(writer)
+    })
+}
+
+/// Parse the diff in `lines`, passing each record to `handle_record` in turn.
+pub fn parse_records<I>(
+    lines: ByteLines<I>,
+    config: &Config,
+    handle_record: &mut dyn FnMut(Record) -> std::io::Result<()>,
+) -> std::io::Result<()>
+where
+    I: BufRead,
+{
+    let mut sink = io::sink();
+    let mut emitter = RecordEmitter::new(handle_record, &mut sink, config);
+
+    for event in Events::new(lines, config) {
+        match event {
+            Event::Line {
+                kind: LineKind::Minus,
+                line,
+                ..
+            } => emitter.minus_lines.push(line[1..].to_string()),
+            Event::Line {
+                kind: LineKind::Plus,
+                line,
+                ..
+            } => emitter.plus_lines.push(line[1..].to_string()),
+            Event::Line {
+                kind: LineKind::Zero,
+                line,
+                ..
+            } => {
+                emitter.flush()?;
+                emitter.emit_zero_line(&line[1..])?;
+            }
+            Event::Line {
+                kind: LineKind::Other,
+                line,
+                ..
+            } => {
+                emitter.flush()?;
+                // An unchanged empty line whose leading space has been lost, e.g. by an editor.
+                // Other lines, such as "\ No newline at end of file", are not recorded.
+                if line.is_empty() {
+                    emitter.emit_zero_line("")?;
+                }
+            }
+            Event::CommitHeader { line, .. } => {
+                emitter.flush()?;
+                let hash = line.split_whitespace().nth(1).unwrap_or("").to_string();
+                emitter.emit(Record::Commit { hash })?;
+            }
+            Event::FileHeader {
+                minus_file,
+                plus_file,
+                ..
+            } => {
+                emitter.flush()?;
+                emitter.set_syntax_for_file(&plus_file);
+                emitter.emit(Record::File {
+                    minus_file,
+                    plus_file,
+                })?;
+            }
+            Event::HunkHeader {
+                code_fragment,
+                line_numbers_and_hunk_lengths,
+                ..
+            } => {
+                emitter.flush()?;
+                emitter.start_hunk(&code_fragment, &line_numbers_and_hunk_lengths)?;
+            }
+            _ => emitter.flush()?,
+        }
+    }
+    emitter.flush()
+}
+
+struct RecordEmitter<'a> {
+    handle_record: &'a mut dyn FnMut(Record) -> std::io::Result<()>,
+    config: &'a Config,
+    // The Painter is used only to determine the syntax of a file in the same way as when the diff
+    // is rendered.
+    painter: Painter<'a>,
+    // The syntax parser state, if syntax scopes are requested.
+    parse_state: Option<(ParseState, ScopeStack)>,
+    minus_line_number: usize,
+    plus_line_number: usize,
+    minus_lines: Vec<String>,
+    plus_lines: Vec<String>,
+}
+
+impl<'a> RecordEmitter<'a> {
+    fn new(
+        handle_record: &'a mut dyn FnMut(Record) -> std::io::Result<()>,
+        sink: &'a mut dyn Write,
+        config: &'a Config,
+    ) -> Self {
+        Self {
+            handle_record,
+            config,
+            painter: Painter::new(sink, config),
+            parse_state: None,
+            minus_line_number: 0,
+            plus_line_number: 0,
+            minus_lines: Vec::new(),
+            plus_lines: Vec::new(),
+        }
+    }
+
+    fn emit(&mut self, record: Record) -> std::io::Result<()> {
+        (self.handle_record)(record)
+    }
+
+    fn set_syntax_for_file(&mut self, path: &str) {
+        self.painter.set_syntax_for_file(path);
+    }
+
+    fn start_hunk(
+        &mut self,
+        code_fragment: &str,
+        line_numbers_and_hunk_lengths: &[(usize, usize)],
+    ) -> std::io::Result<()> {
+        let (minus_start, minus_count) = line_numbers_and_hunk_lengths[0];
+        let (plus_start, plus_count) = *line_numbers_and_hunk_lengths.last().unwrap();
+        self.minus_line_number = minus_start;
+        self.plus_line_number = plus_start;
+        if self.config.json_syntax_scopes {
+            self.parse_state = Some((ParseState::new(self.syntax()), ScopeStack::new()));
+        }
+        self.emit(Record::Hunk {
+            minus_start,
+            minus_count,
+            plus_start,
+            plus_count,
+            code_fragment: code_fragment.trim().to_string(),
+        })
+    }
+
+    fn syntax(&self) -> &'a SyntaxReference {
+        self.painter.syntax
+    }
+
+    fn emit_zero_line(&mut self, text: &str) -> std::io::Result<()> {
+        let record = Record::Line {
+            state: LineState::Zero,
+            minus_line_number: Some(self.minus_line_number),
+            plus_line_number: Some(self.plus_line_number),
+            paired_line_number: None,
+            text: text.to_string(),
+            sections: vec![EditSection {
+                text: text.to_string(),
+                emph: false,
+            }],
+            syntax_sections: self.get_syntax_sections(text),
+        };
+        self.minus_line_number += 1;
+        self.plus_line_number += 1;
+        self.emit(record)
+    }
+
+    /// Infer the edits between the buffered removed and added lines, and emit their records.
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.minus_lines.is_empty() && self.plus_lines.is_empty() {
+            return Ok(());
+        }
+        let minus_lines = std::mem::take(&mut self.minus_lines);
+        let plus_lines = std::mem::take(&mut self.plus_lines);
+        // As in Painter::prepare, a leading space stands in for the removed -/+ character.
+        let prepared_minus_lines: Vec<String> =
+            minus_lines.iter().map(|l| format!(" {}", l)).collect();
+        let prepared_plus_lines: Vec<String> =
+            plus_lines.iter().map(|l| format!(" {}", l)).collect();
+        let (minus_sections, plus_sections, line_alignment) = edits::infer_edits(
+            prepared_minus_lines.iter().map(|l| l.as_str()).collect(),
+            prepared_plus_lines.iter().map(|l| l.as_str()).collect(),
+            vec![Emph::NonEmph; minus_lines.len()],
+            Emph::Emph,
+            vec![Emph::NonEmph; plus_lines.len()],
+            Emph::Emph,
+            &self.config.tokenization_regex,
+            self.config.max_line_distance,
+            self.config.max_line_distance_for_naively_paired_lines,
+            self.config.max_alignment_cells,
+        );
+
+        let minus_start = self.minus_line_number;
+        let plus_start = self.plus_line_number;
+        let mut minus_paired_line_numbers = vec![None; minus_lines.len()];
+        let mut plus_paired_line_numbers = vec![None; plus_lines.len()];
+        for (minus_index, plus_index) in line_alignment {
+            if let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) {
+                minus_paired_line_numbers[minus_index] = Some(plus_start + plus_index);
+                plus_paired_line_numbers[plus_index] = Some(minus_start + minus_index);
+            }
+        }
+
+        for (i, (text, sections)) in minus_lines.iter().zip(minus_sections).enumerate() {
+            let record = Record::Line {
+                state: LineState::Minus,
+                minus_line_number: Some(minus_start + i),
+                plus_line_number: None,
+                paired_line_number: minus_paired_line_numbers[i],
+                text: text.clone(),
+                sections: make_edit_sections(sections),
+                syntax_sections: self.get_syntax_sections(text),
+            };
+            self.emit(record)?;
+        }
+        for (i, (text, sections)) in plus_lines.iter().zip(plus_sections).enumerate() {
+            let record = Record::Line {
+                state: LineState::Plus,
+                minus_line_number: None,
+                plus_line_number: Some(plus_start + i),
+                paired_line_number: plus_paired_line_numbers[i],
+                text: text.clone(),
+                sections: make_edit_sections(sections),
+                syntax_sections: self.get_syntax_sections(text),
+            };
+            self.emit(record)?;
+        }
+        self.minus_line_number += minus_lines.len();
+        self.plus_line_number += plus_lines.len();
+        Ok(())
+    }
+
+    /// Divide the line into sections labeled with syntax scopes. As when highlighting, lines are
+    /// parsed in the order in which they are emitted, starting afresh at each hunk.
+    fn get_syntax_sections(&mut self, text: &str) -> Option<Vec<SyntaxSection>> {
+        let (parse_state, scope_stack) = self.parse_state.as_mut()?;
+        // Many syntax definitions require the newline in order to parse correctly.
+        let line = format!("{}\n", text);
+        let ops = parse_state.parse_line(&line, &self.config.syntax_set);
+        let mut sections: Vec<SyntaxSection> = Vec::new();
+        for (section_text, op) in ScopeRegionIterator::new(&ops, &line) {
+            scope_stack.apply(op);
+            let section_text = section_text.trim_end_matches('\n');
+            if !section_text.is_empty() {
+                let scopes = scope_stack
+                    .as_slice()
+                    .iter()
+                    .map(|scope| scope.build_string())
+                    .collect();
+                match sections.last_mut() {
+                    Some(last) if last.scopes == scopes => last.text.push_str(section_text),
+                    _ => sections.push(SyntaxSection {
+                        text: section_text.to_string(),
+                        scopes,
+                    }),
+                }
+            }
+        }
+        Some(sections)
+    }
+}
+
+/// Convert the sections annotated by the edit inference to output sections, dropping the leading
+/// space that stood in for the -/+ character.
+fn make_edit_sections(sections: Vec<(Emph, &str)>) -> Vec<EditSection> {
+    let mut edit_sections: Vec<EditSection> = Vec::new();
+    let mut is_leading_space_dropped = false;
+    for (emph, text) in sections {
+        // The first section may be empty, e.g. when text is inserted at the start of the line.
+        let text = if !is_leading_space_dropped && !text.is_empty() {
+            is_leading_space_dropped = true;
+            &text[1..]
+        } else {
+            text
+        };
+        let emph = emph == Emph::Emph;
+        match edit_sections.last_mut() {
+            Some(last) if last.emph == emph => last.text.push_str(text),
+            _ if text.is_empty() => {}
+            _ => edit_sections.push(EditSection {
+                text: text.to_string(),
+                emph,
+            }),
+        }
+    }
+    edit_sections
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::tests::integration_test_utils::integration_test_utils::make_config_from_args;
+
+    fn run_delta_json(input: &str, args: &[&str]) -> Vec<Value> {
+        let config = make_config_from_args(args);
+        let mut output = Vec::new();
+        delta_json(
+            ByteLines::new(BufReader::new(input.as_bytes())),
+            &mut output,
+            &config,
+        )
+        .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_records() {
+        let records = run_delta_json(DIFF, &["--output-format", "json"]);
+        assert_eq!(records.len(), 8);
+        assert_eq!(
+            records[0],
+            json!({"type": "commit", "hash": "94907c0f136f46dc46ffae2dc92dca9af7eb7c2e"})
+        );
+        assert_eq!(
+            records[1],
+            json!({"type": "file", "minus_file": "src/lib.rs", "plus_file": "src/lib.rs"})
+        );
+        assert_eq!(
+            records[2],
+            json!({
+                "type": "hunk",
+                "minus_start": 10,
+                "minus_count": 3,
+                "plus_start": 10,
+                "plus_count": 4,
+                "code_fragment": "fn f() {"
+            })
+        );
+        assert_eq!(records[3]["state"], "zero");
+        assert_eq!(records[3]["minus_line_number"], 10);
+        assert_eq!(records[3]["plus_line_number"], 10);
+        assert_eq!(
+            records[4],
+            json!({
+                "type": "line",
+                "state": "minus",
+                "minus_line_number": 11,
+                "plus_line_number": null,
+                "paired_line_number": 11,
+                "text": "    let x = 1;",
+                "sections": [
+                    {"text": "    let ", "emph": false},
+                    {"text": "x", "emph": true},
+                    {"text": " = 1;", "emph": false},
+                ]
+            })
+        );
+        assert_eq!(records[5]["state"], "plus");
+        assert_eq!(records[5]["plus_line_number"], 11);
+        assert_eq!(records[5]["paired_line_number"], 11);
+        assert_eq!(
+            records[5]["sections"][1],
+            json!({"text": "y", "emph": true})
+        );
+        // An added line not paired with a removed line.
+        assert_eq!(records[6]["state"], "plus");
+        assert_eq!(records[6]["plus_line_number"], 12);
+        assert_eq!(records[6]["paired_line_number"], Value::Null);
+        assert_eq!(records[7]["minus_line_number"], 12);
+        assert_eq!(records[7]["plus_line_number"], 13);
+    }
+
+    #[test]
+    fn test_json_syntax_scopes() {
+        let records = run_delta_json(DIFF, &["--output-format", "json"]);
+        assert!(records[4].get("syntax_sections").is_none());
+
+        let records = run_delta_json(DIFF, &["--output-format", "json", "--json-syntax-scopes"]);
+        let syntax_sections = records[4]["syntax_sections"].as_array().unwrap();
+        let text: String = syntax_sections
+            .iter()
+            .map(|section| section["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(text, "    let x = 1;");
+        assert!(syntax_sections.iter().any(|section| {
+            section["scopes"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("storage.type.rust"))
+        }));
+    }
+
+    #[test]
+    fn test_json_edit_at_start_of_line() {
+        let records = run_delta_json(
+            "\
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-fn detect_source(line: &str) -> Source {
++pub fn detect_source(line: &str) -> Source {
+",
+            &["--output-format", "json"],
+        );
+        let text: String = records[3]["sections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|section| section["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(text, "pub fn detect_source(line: &str) -> Source {");
+    }
+
+    const DIFF: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu Jun 4 09:42:49 2020 -0400
+
+    Change variable name
+
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,3 +10,4 @@ fn f() {
+     let a = 0;
+-    let x = 1;
++    let y = 1;
++    let z = 2;
+ }
+";
+}
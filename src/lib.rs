@@ -0,0 +1,331 @@
+//! Delta as a library, for programs such as editors and TUIs that embed delta's rendering of diffs.
+//!
+//! A [`Config`] is built with [`ConfigBuilder`], using the same option names and values as the
+//! delta executable, but without parsing a command line:
+//!
+//! ```
+//! # fn main() -> git_delta::errors::Result<()> {
+//! let config = git_delta::ConfigBuilder::new()
+//!     .option("syntax-theme", "GitHub")
+//!     .flag("line-numbers")
+//!     .build()?;
+//! let diff = "\
+//! --- a/a.txt
+//! +++ b/a.txt
+//! @@ -1 +1 @@
+//! -hello
+//! +world
+//! ";
+//! let output = git_delta::render_str(diff, &config)?;
+//! assert!(output.contains("world"));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`render`] and [`render_str`] produce the same output as the delta executable, in the output
+//! format of the config (ANSI, HTML, SVG, or JSON). [`parse_records`] gives typed access to the
+//! commit, file, hunk and line records that make up the JSON output format, and [`events`] to the
+//! unpainted events from which all output formats are produced.
+//!
+//! The items in the `internal` module are public only for the use of the delta executable; they
+//! are not part of the stable API.
+
+extern crate bitflags;
+
+#[macro_use]
+extern crate error_chain;
+
+mod align;
+mod ansi;
+mod bat;
+mod cli;
+mod color;
+mod commit_meta;
+mod config;
+mod delta;
+mod draw;
+mod edits;
+mod env;
+mod events;
+mod features;
+mod format;
+mod git_attributes;
+mod git_config;
+mod git_config_entry;
+mod glob;
+mod html;
+mod json;
+mod options;
+mod paint;
+mod parallel;
+mod parse;
+mod parse_style;
+mod style;
+mod svg;
+mod syntect_color;
+mod terminal_background;
+mod tests;
+
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+
+use bytelines::ByteLines;
+
+pub use crate::cli::OutputFormat;
+pub use crate::config::Config;
+pub use crate::events::{Event, Events, FileChange, LineKind};
+pub use crate::json::{EditSection, LineState, Record, SyntaxSection};
+
+pub mod errors {
+    error_chain! {
+        foreign_links {
+            Clap(::structopt::clap::Error);
+            Io(::std::io::Error);
+            SyntectError(::syntect::LoadingError);
+            ParseIntError(::std::num::ParseIntError);
+        }
+    }
+}
+
+/// Builds a [`Config`] from options, named as on the delta command line or in the `[delta]`
+/// section of gitconfig. An invalid option name or value is returned as an error by
+/// [`build`](ConfigBuilder::build).
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    args: Vec<String>,
+    read_git_config: bool,
+}
+
+impl ConfigBuilder {
+    /// A builder for the default configuration. Unlike the delta executable, it does not read the
+    /// user's git config unless `read_git_config(true)` is used, and it does not query the terminal
+    /// for its background color unless the "detect-dark-light" option is set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set an option that takes a value, e.g. `.option("minus-style", "red bold")`.
+    pub fn option(mut self, name: &str, value: &str) -> Self {
+        self.args.push(format!("--{}", name));
+        self.args.push(value.to_string());
+        self
+    }
+
+    /// Set a boolean option, e.g. `.flag("side-by-side")`.
+    pub fn flag(mut self, name: &str) -> Self {
+        self.args.push(format!("--{}", name));
+        self
+    }
+
+    /// Activate features: a space-separated list of builtin features, themes, or features defined
+    /// in git config.
+    pub fn features(self, features: &str) -> Self {
+        self.option("features", features)
+    }
+
+    pub fn output_format(self, output_format: OutputFormat) -> Self {
+        let value = match output_format {
+            OutputFormat::Ansi => "ansi",
+            OutputFormat::Html => "html",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Json => "json",
+        };
+        self.option("output-format", value)
+    }
+
+    /// The width of decorations and of the side-by-side panels. If this is not set then, as for
+    /// the delta executable, the width of the terminal is used.
+    pub fn width(self, width: usize) -> Self {
+        self.option("width", &width.to_string())
+    }
+
+    /// Whether to also read options from the user's git config, as the delta executable does.
+    pub fn read_git_config(mut self, read_git_config: bool) -> Self {
+        self.read_git_config = read_git_config;
+        self
+    }
+
+    pub fn build(self) -> errors::Result<Config> {
+        let mut git_config = if self.read_git_config {
+            git_config::GitConfig::try_create()?
+        } else {
+            None
+        };
+        let mut args = vec!["delta".to_string()];
+        if !self.args.iter().any(|arg| arg == "--detect-dark-light") {
+            args.extend(vec!["--detect-dark-light".to_string(), "never".to_string()]);
+        }
+        args.extend(self.args);
+        let opt = cli::Opt::from_iter_safe_and_git_config(args, &mut git_config)?;
+        Ok(Config::try_from(opt)?)
+    }
+}
+
+/// Render the diff read from `reader` to `writer`, in the output format of `config`. The reader is
+/// read on a separate thread, so that, if it is slow, the output painted so far can be written
+/// while waiting for it.
+pub fn render<R>(reader: R, writer: &mut dyn Write, config: &Config) -> io::Result<()>
+where
+    R: BufRead + Send,
+{
+    let lines = ByteLines::new(reader);
+    match config.output_format {
+        OutputFormat::Ansi => delta::delta(lines, writer, config),
+        OutputFormat::Html => html::delta_html(lines, writer, config),
+        OutputFormat::Svg => svg::delta_svg(lines, writer, config),
+        OutputFormat::Json => json::delta_json(lines, writer, config),
+    }
+}
+
+/// Render `diff`, in the output format of `config`.
+pub fn render_str(diff: &str, config: &Config) -> io::Result<String> {
+    let mut output = Vec::new();
+    render(diff.as_bytes(), &mut output, config)?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Parse the diff read from `reader` into the records of the JSON output format, passing each
+/// record to `handle_record` in turn.
+pub fn parse_records<R, F>(reader: R, config: &Config, mut handle_record: F) -> io::Result<()>
+where
+    R: BufRead,
+    F: FnMut(Record) -> io::Result<()>,
+{
+    json::parse_records(ByteLines::new(reader), config, &mut handle_record)
+}
+
+/// Parse the diff read from `reader` into events: commit, file and hunk headers, hunk lines, and
+/// the lines between them.
+pub fn events<R>(reader: R, config: &Config) -> Events<'_, R>
+where
+    R: BufRead,
+{
+    Events::new(ByteLines::new(reader), config)
+}
+
+/// Items used by the delta executable. They are not part of the stable API.
+#[doc(hidden)]
+pub mod internal {
+    pub use crate::bat::assets::{build_cache, clear_cache, list_languages, HighlightingAssets};
+    pub use crate::bat::output::{OutputType, PagingMode};
+    pub use crate::cli::{InspectRawLines, Opt, Width};
+    pub use crate::delta::delta;
+    pub use crate::features::themes::THEMES;
+    pub use crate::git_config::GitConfig;
+    pub use crate::options::theme::is_light_syntax_theme;
+}
+
+#[cfg(test)]
+mod lib_tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-hello world
++hello there
+";
+
+    #[test]
+    fn test_config_builder() {
+        let config = ConfigBuilder::new()
+            .features("side-by-side")
+            .option("minus-style", "bold")
+            .flag("line-numbers")
+            .width(100)
+            .build()
+            .unwrap();
+        assert!(config.side_by_side);
+        assert!(config.line_numbers);
+        assert!(config.minus_style.ansi_term_style.is_bold);
+        assert_eq!(config.output_format, OutputFormat::Ansi);
+    }
+
+    #[test]
+    fn test_config_builder_errors() {
+        let error = ConfigBuilder::new()
+            .option("minus-style", "nocolor")
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "Invalid color or style attribute: nocolor");
+        let error = ConfigBuilder::new()
+            .flag("no-such-option")
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("--no-such-option"));
+        assert!(!error.to_string().contains('\x1b'));
+        assert!(ConfigBuilder::new()
+            .option("detect-dark-light", "sometimes")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_render_str() {
+        let config = ConfigBuilder::new()
+            .option("syntax-theme", "none")
+            .option("color", "never")
+            .option("file-style", "omit")
+            .option("hunk-header-style", "omit")
+            .build()
+            .unwrap();
+        assert_eq!(
+            render_str(DIFF, &config).unwrap(),
+            "hello [-world-]\nhello {+there+}\n"
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let events: Vec<Event> = events(DIFF.as_bytes(), &config).collect();
+        assert_eq!(events.len(), 7);
+        assert_eq!(
+            events[6],
+            Event::Line {
+                kind: LineKind::Plus,
+                line: "+hello there".to_string(),
+                raw_line: "+hello there".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let mut records = Vec::new();
+        parse_records(DIFF.as_bytes(), &config, |record| {
+            records.push(record);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(
+            records[0],
+            Record::File {
+                minus_file: "a.txt".to_string(),
+                plus_file: "a.txt".to_string(),
+            }
+        );
+        match &records[3] {
+            Record::Line {
+                state, sections, ..
+            } => {
+                assert_eq!(*state, LineState::Plus);
+                assert_eq!(
+                    sections[1],
+                    EditSection {
+                        text: "there".to_string(),
+                        emph: true
+                    }
+                );
+            }
+            record => panic!("Unexpected record: {:?}", record),
+        }
+    }
+}
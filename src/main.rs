@@ -1,58 +1,25 @@
-extern crate bitflags;
-
-#[macro_use]
-extern crate error_chain;
-
-mod align;
-mod ansi;
-mod bat;
-mod cli;
-mod color;
-mod config;
-mod delta;
-mod draw;
-mod edits;
-mod env;
-mod features;
-mod format;
-mod git_config;
-mod git_config_entry;
-mod options;
-mod paint;
-mod parse;
-mod parse_style;
-mod style;
-mod syntect_color;
-mod tests;
-
+use std::convert::TryFrom;
 use std::io::{self, ErrorKind, Read, Write};
 use std::path::PathBuf;
 use std::process;
 
 use ansi_term;
 use atty;
-use bytelines::ByteLinesReader;
+use error_chain::ChainedError;
 use itertools::Itertools;
 use structopt::StructOpt;
 
-use crate::bat::assets::{list_languages, HighlightingAssets};
-use crate::bat::output::{OutputType, PagingMode};
-use crate::delta::delta;
-use crate::options::theme::is_light_syntax_theme;
-
-pub mod errors {
-    error_chain! {
-        foreign_links {
-            Io(::std::io::Error);
-            SyntectError(::syntect::LoadingError);
-            ParseIntError(::std::num::ParseIntError);
-        }
-    }
-}
+use git_delta::internal::{
+    build_cache, clear_cache, delta, is_light_syntax_theme, list_languages, GitConfig,
+    HighlightingAssets, InspectRawLines, Opt, OutputType, PagingMode, Width, THEMES,
+};
+use git_delta::{Config, OutputFormat};
 
 fn main() -> std::io::Result<()> {
     let assets = HighlightingAssets::new();
-    let opt = cli::Opt::from_args_and_git_config(&mut git_config::GitConfig::try_create(), assets);
+    let mut git_config = GitConfig::try_create().unwrap_or_else(|message| exit_with(&message));
+    let opt = Opt::from_args_and_git_config(&mut git_config, assets)
+        .unwrap_or_else(|message| exit_with(&message));
 
     if opt.list_languages {
         list_languages()?;
@@ -63,10 +30,31 @@ fn main() -> std::io::Result<()> {
     } else if opt.show_syntax_themes {
         show_syntax_themes()?;
         process::exit(0);
+    } else if opt.show_themes {
+        show_themes(opt.light, opt.dark)?;
+        process::exit(0);
+    } else if opt.build_cache || opt.clear_cache {
+        let result = if opt.clear_cache {
+            clear_cache()
+        } else {
+            Ok(())
+        }
+        .and_then(|_| {
+            if opt.build_cache {
+                build_cache(opt.cache_source.as_deref())
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(error) = result {
+            eprintln!("{}", error.display_chain());
+            process::exit(1);
+        }
+        process::exit(0);
     }
 
     let _show_config = opt.show_config;
-    let config = config::Config::from(opt);
+    let config = Config::try_from(opt).unwrap_or_else(|message| exit_with(&message));
 
     if _show_config {
         show_config(&config);
@@ -82,7 +70,9 @@ fn main() -> std::io::Result<()> {
     let mut output_type = OutputType::from_mode(config.paging_mode, None, &config).unwrap();
     let mut writer = output_type.handle().unwrap();
 
-    if let Err(error) = delta(io::stdin().lock().byte_lines(), &mut writer, &config) {
+    // Stdin is read on a separate thread, which its lock may not be sent to.
+    let stdin = io::BufReader::new(io::stdin());
+    if let Err(error) = git_delta::render(stdin, &mut writer, &config) {
         match error.kind() {
             ErrorKind::BrokenPipe => process::exit(0),
             _ => eprintln!("{}", error),
@@ -91,11 +81,17 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
+/// Report an invalid option value, or a git config that could not be read, and exit.
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
 /// Run `diff -u` on the files provided on the command line and display the output.
 fn diff(
     minus_file: Option<&PathBuf>,
     plus_file: Option<&PathBuf>,
-    config: &config::Config,
+    config: &Config,
 ) -> std::io::Result<()> {
     use std::io::BufReader;
     let die = || {
@@ -113,8 +109,8 @@ fn diff(
 
     let mut output_type = OutputType::from_mode(config.paging_mode, None, &config).unwrap();
     let mut writer = output_type.handle().unwrap();
-    if let Err(error) = delta(
-        BufReader::new(diff_process.unwrap().stdout.unwrap()).byte_lines(),
+    if let Err(error) = git_delta::render(
+        BufReader::new(diff_process.unwrap().stdout.unwrap()),
         &mut writer,
         &config,
     ) {
@@ -126,10 +122,16 @@ fn diff(
     Ok(())
 }
 
-fn show_config(config: &config::Config) {
+fn show_config(config: &Config) {
     // styles first
     println!(
         "    commit-style                  = {commit_style}
+    commit-author-style           = {commit_author_style}
+    commit-date-style             = {commit_date_style}
+    commit-subject-style          = {commit_subject_style}
+    commit-branch-style           = {commit_branch_style}
+    commit-remote-branch-style    = {commit_remote_branch_style}
+    commit-tag-style              = {commit_tag_style}
     file-style                    = {file_style}
     hunk-header-style             = {hunk_header_style}
     minus-style                   = {minus_style}
@@ -143,6 +145,12 @@ fn show_config(config: &config::Config) {
     plus-empty-line-marker-style  = {plus_empty_line_marker_style}
     whitespace-error-style        = {whitespace_error_style}",
         commit_style = config.commit_style.to_painted_string(),
+        commit_author_style = config.commit_author_style.to_painted_string(),
+        commit_date_style = config.commit_date_style.to_painted_string(),
+        commit_subject_style = config.commit_subject_style.to_painted_string(),
+        commit_branch_style = config.commit_branch_style.to_painted_string(),
+        commit_remote_branch_style = config.commit_remote_branch_style.to_painted_string(),
+        commit_tag_style = config.commit_tag_style.to_painted_string(),
         file_style = config.file_style.to_painted_string(),
         hunk_header_style = config.hunk_header_style.to_painted_string(),
         minus_emph_style = config.minus_emph_style.to_painted_string(),
@@ -159,11 +167,17 @@ fn show_config(config: &config::Config) {
     // Everything else
     println!(
         "    24-bit-color                  = {true_color}
+    commit-hash-length            = {commit_hash_length}
+    exclude                       = {exclude}
+    expand-generated-files        = {expand_generated_files}
     file-added-label              = {file_added_label}
     file-modified-label           = {file_modified_label}
     file-removed-label            = {file_removed_label}
     file-renamed-label            = {file_renamed_label}",
         true_color = config.true_color,
+        commit_hash_length = config.commit_hash_length,
+        exclude = format_option_value(config.exclude_globs.iter().join(" ")),
+        expand_generated_files = config.expand_generated_files,
         file_added_label = format_option_value(&config.file_added_label),
         file_modified_label = format_option_value(&config.file_modified_label),
         file_removed_label = format_option_value(&config.file_removed_label),
@@ -175,18 +189,43 @@ fn show_config(config: &config::Config) {
     );
     if config.hyperlinks {
         println!(
-            "    hyperlinks-file-link-format   = {hyperlinks_file_link_format}",
+            "    hyperlinks-commit-link-format = {hyperlinks_commit_link_format}
+    hyperlinks-file-link-format   = {hyperlinks_file_link_format}
+    hyperlinks-forge              = {hyperlinks_forge}
+    hyperlinks-issue-links        = {hyperlinks_issue_links}
+    hyperlinks-remote             = {hyperlinks_remote}",
+            hyperlinks_commit_link_format = format_option_value(
+                config
+                    .hyperlinks_commit_link_format
+                    .as_deref()
+                    .unwrap_or("")
+            ),
             hyperlinks_file_link_format = format_option_value(&config.hyperlinks_file_link_format),
+            hyperlinks_forge = format_option_value(&config.hyperlinks_forge),
+            hyperlinks_issue_links = format_option_value(
+                config
+                    .hyperlinks_issue_links
+                    .iter()
+                    .map(|(regex, url)| format!("{}={}", regex, url))
+                    .join(" ")
+            ),
+            hyperlinks_remote = format_option_value(&config.hyperlinks_remote),
         )
     }
     println!(
-        "    inspect-raw-lines             = {inspect_raw_lines}
-    keep-plus-minus-markers       = {keep_plus_minus_markers}",
+        "    include                       = {include}
+    inspect-raw-lines             = {inspect_raw_lines}
+    keep-plus-minus-markers       = {keep_plus_minus_markers}
+    minus-marker                  = {minus_marker}
+    plus-marker                   = {plus_marker}",
+        include = format_option_value(config.include_globs.iter().join(" ")),
         inspect_raw_lines = match config.inspect_raw_lines {
-            cli::InspectRawLines::True => "true",
-            cli::InspectRawLines::False => "false",
+            InspectRawLines::True => "true",
+            InspectRawLines::False => "false",
         },
         keep_plus_minus_markers = config.keep_plus_minus_markers,
+        minus_marker = format_option_value(&config.minus_marker),
+        plus_marker = format_option_value(&config.plus_marker),
     );
     println!(
         "    line-numbers                  = {line_numbers}",
@@ -212,32 +251,57 @@ fn show_config(config: &config::Config) {
     }
     println!(
         "    max-line-distance             = {max_line_distance}
+    max-line-length               = {max_line_length}
     navigate                      = {navigate}
+    output-format                 = {output_format}
     paging                        = {paging_mode}
+    relative-dates                = {relative_dates}
+    relative-paths                = {relative_paths}
     side-by-side                  = {side_by_side}
+    summarize-hidden-files        = {summarize_hidden_files}
+    syntax-map                    = {syntax_map}
     syntax-theme                  = {syntax_theme}
     width                         = {width}
     tabs                          = {tab_width}
-    word-diff-regex               = {tokenization_regex}",
+    word-diff-regex               = {tokenization_regex}
+    workers                       = {workers}",
         max_line_distance = config.max_line_distance,
+        max_line_length = config.max_line_length,
         navigate = config.navigate,
+        output_format = match config.output_format {
+            OutputFormat::Ansi => "ansi",
+            OutputFormat::Html => "html",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Json => "json",
+        },
         paging_mode = match config.paging_mode {
             PagingMode::Always => "always",
             PagingMode::Never => "never",
             PagingMode::QuitIfOneScreen => "auto",
         },
+        relative_dates = config.relative_dates,
+        relative_paths = config.relative_paths,
         side_by_side = config.side_by_side,
+        summarize_hidden_files = config.summarize_hidden_files,
+        syntax_map = format_option_value(
+            config
+                .syntax_map
+                .iter()
+                .map(|(glob, syntax_name)| format!("{}={}", glob, syntax_name))
+                .join(" ")
+        ),
         syntax_theme = config
             .syntax_theme
             .clone()
             .map(|t| t.name.unwrap_or("none".to_string()))
             .unwrap_or("none".to_string()),
         width = match config.decorations_width {
-            cli::Width::Fixed(width) => width.to_string(),
-            cli::Width::Variable => "variable".to_string(),
+            Width::Fixed(width) => width.to_string(),
+            Width::Variable => "variable".to_string(),
         },
         tab_width = config.tab_width,
         tokenization_regex = format_option_value(&config.tokenization_regex.to_string()),
+        workers = config.workers,
     );
 }
 
@@ -260,12 +324,12 @@ where
 }
 
 fn show_syntax_themes() -> std::io::Result<()> {
-    let mut opt = cli::Opt::from_args();
+    let mut opt = Opt::from_args();
     let assets = HighlightingAssets::new();
     let mut output_type = OutputType::from_mode(
         PagingMode::QuitIfOneScreen,
         None,
-        &config::Config::from(cli::Opt::default()),
+        &Config::try_from(Opt::default()).unwrap_or_else(|message| exit_with(&message)),
     )
     .unwrap();
     let mut writer = output_type.handle().unwrap();
@@ -283,13 +347,41 @@ fn show_syntax_themes() -> std::io::Result<()> {
 }
 
 fn _show_syntax_themes(
-    mut opt: cli::Opt,
+    mut opt: Opt,
     is_light_mode: bool,
     writer: &mut dyn Write,
 ) -> std::io::Result<()> {
     use bytelines::ByteLines;
     use std::io::BufReader;
-    let input = if !atty::is(atty::Stream::Stdin) {
+    let input = read_demo_input()?;
+
+    opt.computed.is_light_mode = is_light_mode;
+    let mut config = Config::try_from(opt).unwrap_or_else(|message| exit_with(&message));
+    let title_style = ansi_term::Style::new().bold();
+    let assets = HighlightingAssets::new();
+
+    for syntax_theme in assets
+        .theme_set
+        .themes
+        .iter()
+        .filter(|(t, _)| is_light_syntax_theme(t) == is_light_mode)
+        .map(|(t, _)| t)
+    {
+        writeln!(writer, "\n\nTheme: {}\n", title_style.paint(syntax_theme))?;
+        config.syntax_theme = Some(assets.theme_set.themes[syntax_theme.as_str()].clone());
+        if let Err(error) = delta(ByteLines::new(BufReader::new(&input[0..])), writer, &config) {
+            match error.kind() {
+                ErrorKind::BrokenPipe => process::exit(0),
+                _ => eprintln!("{}", error),
+            }
+        };
+    }
+    Ok(())
+}
+
+/// Return the diff supplied on standard input, or an example diff if there is none.
+fn read_demo_input() -> std::io::Result<Vec<u8>> {
+    Ok(if !atty::is(atty::Stream::Stdin) {
         let mut buf = Vec::new();
         io::stdin().lock().read_to_end(&mut buf)?;
         buf
@@ -310,23 +402,44 @@ index f38589a..0f1bb83 100644
 +    println!(\"The cube of {:.2} is {:.2}.\", num, result);
 "
         .to_vec()
-    };
+    })
+}
 
-    opt.computed.is_light_mode = is_light_mode;
-    let mut config = config::Config::from(opt);
+fn show_themes(light: bool, dark: bool) -> std::io::Result<()> {
+    use bytelines::ByteLines;
+    use std::io::BufReader;
+    let input = read_demo_input()?;
+    let mut output_type = OutputType::from_mode(
+        PagingMode::QuitIfOneScreen,
+        None,
+        &Config::try_from(Opt::default()).unwrap_or_else(|message| exit_with(&message)),
+    )
+    .unwrap();
+    let mut writer = output_type.handle().unwrap();
     let title_style = ansi_term::Style::new().bold();
-    let assets = HighlightingAssets::new();
 
-    for syntax_theme in assets
-        .theme_set
-        .themes
+    for theme in THEMES
         .iter()
-        .filter(|(t, _)| is_light_syntax_theme(t) == is_light_mode)
-        .map(|(t, _)| t)
+        .filter(|theme| !(light && !theme.is_light || dark && theme.is_light))
     {
-        writeln!(writer, "\n\nTheme: {}\n", title_style.paint(syntax_theme))?;
-        config.syntax_theme = Some(assets.theme_set.themes[syntax_theme.as_str()].clone());
-        if let Err(error) = delta(ByteLines::new(BufReader::new(&input[0..])), writer, &config) {
+        writeln!(writer, "\n\nTheme: {}\n", title_style.paint(theme.name))?;
+        // Git config is not used, so that each theme is displayed as shipped.
+        let opt = Opt::from_iter_and_git_config(
+            &[
+                "delta",
+                "--features",
+                theme.name,
+                if theme.is_light { "--light" } else { "--dark" },
+            ],
+            &mut None,
+        )
+        .unwrap_or_else(|message| exit_with(&message));
+        let config = Config::try_from(opt).unwrap_or_else(|message| exit_with(&message));
+        if let Err(error) = delta(
+            ByteLines::new(BufReader::new(&input[0..])),
+            &mut writer,
+            &config,
+        ) {
             match error.kind() {
                 ErrorKind::BrokenPipe => process::exit(0),
                 _ => eprintln!("{}", error),
@@ -1,9 +1,9 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::process;
-use std::result::Result;
 use std::str::FromStr;
 
 use console::Term;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
 use structopt::clap;
 
 use crate::bat::assets::HighlightingAssets;
@@ -11,7 +11,6 @@ use crate::bat::output::PagingMode;
 use crate::cli;
 use crate::config;
 use crate::env;
-use crate::errors::*;
 use crate::features;
 use crate::git_config;
 use crate::git_config_entry::{self, GitConfigEntry};
@@ -43,10 +42,12 @@ macro_rules! set_options {
             option_names.extend(&[
                 "diff-highlight", // Does not exist as a flag on config
                 "diff-so-fancy", // Does not exist as a flag on config
+                "colorblind",    // Does not exist as a flag on config
                 "features",  // Processed differently
                 // Set prior to the rest
                 "no-gitconfig",
                 "dark",
+                "detect-dark-light",
                 "light",
                 "syntax-theme",
             ]);
@@ -67,12 +68,11 @@ pub fn set_options(
     git_config: &mut Option<git_config::GitConfig>,
     arg_matches: &clap::ArgMatches,
     assets: HighlightingAssets,
-) {
+) -> Result<(), String> {
     if let Some(git_config) = git_config {
         if opt.no_gitconfig {
             git_config.enabled = false;
         }
-        set_git_config_entries(opt, git_config);
     }
 
     let option_names = cli::Opt::get_option_names();
@@ -89,12 +89,12 @@ pub fn set_options(
     let features = gather_features(opt, &builtin_features, git_config);
     opt.features = features.join(" ");
 
-    set_widths(opt, git_config, arg_matches, &option_names);
+    set_widths(opt, git_config, arg_matches, &option_names)?;
 
     // Set light, dark, and syntax-theme.
-    set_true_color(opt);
-    set__light__dark__syntax_theme__options(opt, git_config, arg_matches, &option_names);
-    theme::set__is_light_mode__syntax_theme__syntax_set(opt, assets);
+    set_true_color(opt)?;
+    set__light__dark__syntax_theme__options(opt, git_config, arg_matches, &option_names)?;
+    theme::set__is_light_mode__syntax_theme__syntax_set(opt, assets)?;
 
     // HACK: make minus-line styles have syntax-highlighting iff side-by-side.
     if features.contains(&"side-by-side".to_string()) {
@@ -124,9 +124,19 @@ pub fn set_options(
 
     set_options!(
         [
+            color,
             color_only,
+            commit_author_style,
+            commit_branch_style,
+            commit_date_style,
             commit_decoration_style,
+            commit_hash_length,
+            commit_remote_branch_style,
             commit_style,
+            commit_subject_style,
+            commit_tag_style,
+            exclude,
+            expand_generated_files,
             file_added_label,
             file_decoration_style,
             file_modified_label,
@@ -136,18 +146,28 @@ pub fn set_options(
             hunk_header_decoration_style,
             hunk_header_style,
             hyperlinks,
+            hyperlinks_commit_link_format,
             hyperlinks_file_link_format,
+            hyperlinks_forge,
+            hyperlinks_issue_links,
+            hyperlinks_remote,
+            include,
             inspect_raw_lines,
+            json_syntax_scopes,
             keep_plus_minus_markers,
             max_line_distance,
+            max_line_length,
             // Hack: minus-style must come before minus-*emph-style because the latter default
             // dynamically to the value of the former.
             minus_style,
             minus_emph_style,
+            minus_emph_delimiters,
             minus_empty_line_marker_style,
+            minus_marker,
             minus_non_emph_style,
             minus_non_emph_style,
             navigate,
+            output_format,
             line_numbers,
             line_numbers_left_format,
             line_numbers_left_style,
@@ -161,15 +181,26 @@ pub fn set_options(
             // dynamically to the value of the former.
             plus_style,
             plus_emph_style,
+            plus_emph_delimiters,
             plus_empty_line_marker_style,
+            plus_marker,
             plus_non_emph_style,
             raw,
+            relative_dates,
+            relative_paths,
             side_by_side,
+            summarize_hidden_files,
+            syntax_map,
+            syntax_theme_background_emph_strength,
+            syntax_theme_background_min_contrast,
+            syntax_theme_background_strength,
+            syntax_theme_backgrounds,
             tab_width,
             tokenization_regex,
             true_color,
             whitespace_error_style,
             width,
+            workers,
             zero_style
         ],
         opt,
@@ -180,15 +211,29 @@ pub fn set_options(
         true
     );
 
-    opt.computed.inspect_raw_lines =
-        cli::InspectRawLines::from_str(&opt.inspect_raw_lines).unwrap();
-    opt.computed.paging_mode = parse_paging_mode(&opt.paging_mode);
+    // This comes after set_options! since the remote used for hyperlinks is an option.
+    if let Some(git_config) = git_config {
+        set_git_config_entries(opt, git_config)?;
+    }
+
+    expand_palette_references(opt, git_config)?;
+
+    opt.computed.inspect_raw_lines = cli::InspectRawLines::from_str(&opt.inspect_raw_lines)?;
+    opt.computed.paging_mode = parse_paging_mode(&opt.paging_mode)?;
+    opt.computed.output_format = parse_output_format(&opt.output_format)?;
+    if opt.computed.output_format != cli::OutputFormat::Ansi {
+        // Colors in HTML and SVG are always RGB, and output other than ANSI is not paged.
+        opt.computed.true_color = true;
+        opt.computed.paging_mode = PagingMode::Never;
+    }
+    set_monochrome(opt)?;
 
     // --color-only is used for interactive.diffFilter (git add -p) and side-by-side cannot be used
     // there (does not emit lines in 1-1 correspondence with raw git output). See #274.
     if opt.color_only {
         opt.side_by_side = false;
     }
+    Ok(())
 }
 
 #[allow(non_snake_case)]
@@ -197,15 +242,16 @@ fn set__light__dark__syntax_theme__options(
     git_config: &mut Option<git_config::GitConfig>,
     arg_matches: &clap::ArgMatches,
     option_names: &HashMap<&str, &str>,
-) {
+) -> Result<(), String> {
     let validate_light_and_dark = |opt: &cli::Opt| {
         if opt.light && opt.dark {
-            eprintln!("--light and --dark cannot be used together.");
-            process::exit(1);
+            Err("--light and --dark cannot be used together.".to_string())
+        } else {
+            Ok(())
         }
     };
     let empty_builtin_features = HashMap::new();
-    validate_light_and_dark(&opt);
+    validate_light_and_dark(&opt)?;
     if !(opt.light || opt.dark) {
         set_options!(
             [dark, light],
@@ -217,9 +263,9 @@ fn set__light__dark__syntax_theme__options(
             false
         );
     }
-    validate_light_and_dark(&opt);
+    validate_light_and_dark(&opt)?;
     set_options!(
-        [syntax_theme],
+        [detect_dark_light, syntax_theme],
         opt,
         &empty_builtin_features,
         git_config,
@@ -227,6 +273,7 @@ fn set__light__dark__syntax_theme__options(
         option_names,
         false
     );
+    Ok(())
 }
 
 /// Features are processed differently from all other options. The role of this function is to
@@ -315,6 +362,9 @@ fn gather_features<'a>(
     if opt.diff_so_fancy {
         gather_builtin_features_recursively("diff-so-fancy", &mut features, &builtin_features, opt);
     }
+    if opt.colorblind {
+        gather_builtin_features_recursively("colorblind", &mut features, &builtin_features, opt);
+    }
     if opt.hyperlinks {
         gather_builtin_features_recursively("hyperlinks", &mut features, &builtin_features, opt);
     }
@@ -466,19 +516,39 @@ fn split_feature_string(features: &str) -> impl Iterator<Item = &str> {
     features.split_whitespace().rev()
 }
 
-fn set_true_color(opt: &mut cli::Opt) {
+fn set_true_color(opt: &mut cli::Opt) -> Result<(), String> {
     opt.computed.true_color = match opt.true_color.as_ref() {
         "always" => true,
         "never" => false,
         "auto" => is_truecolor_terminal(),
         _ => {
-            eprintln!(
+            return Err(format!(
                 "Invalid value for --24-bit-color option: {} (valid values are \"always\", \"never\", and \"auto\")",
                 opt.true_color
-            );
-            process::exit(1);
+            ));
+        }
+    };
+    Ok(())
+}
+
+fn set_monochrome(opt: &mut cli::Opt) -> Result<(), String> {
+    opt.computed.monochrome = match opt.color.as_ref() {
+        "always" => false,
+        "never" => true,
+        "auto" => env::get_env_var("NO_COLOR").is_some(),
+        _ => {
+            return Err(format!(
+                "Invalid value for --color option: {} (valid values are \"always\", \"never\", and \"auto\")",
+                opt.color
+            ));
         }
     };
+    // Lines identified by inspecting their raw colors (e.g. --color-moved) are emitted with the
+    // colors that git gave them.
+    if opt.computed.monochrome {
+        opt.computed.inspect_raw_lines = cli::InspectRawLines::False;
+    }
+    Ok(())
 }
 
 fn is_truecolor_terminal() -> bool {
@@ -488,34 +558,41 @@ fn is_truecolor_terminal() -> bool {
 }
 
 impl FromStr for cli::InspectRawLines {
-    type Err = Error;
+    type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "true" => Ok(Self::True),
             "false" => Ok(Self::False),
-            _ => {
-                eprintln!(
-                    r#"Invalid value for inspect-raw-lines option: {}. Valid values are "true", and "false"."#,
-                    s
-                );
-                process::exit(1);
-            }
+            _ => Err(format!(
+                r#"Invalid value for inspect-raw-lines option: {}. Valid values are "true", and "false"."#,
+                s
+            )),
         }
     }
 }
 
-fn parse_paging_mode(paging_mode_string: &str) -> PagingMode {
+fn parse_paging_mode(paging_mode_string: &str) -> Result<PagingMode, String> {
     match paging_mode_string.to_lowercase().as_str() {
-        "always" => PagingMode::Always,
-        "never" => PagingMode::Never,
-        "auto" => PagingMode::QuitIfOneScreen,
-        _ => {
-            eprintln!(
-                "Invalid value for --paging option: {} (valid values are \"always\", \"never\", and \"auto\")",
-                paging_mode_string
-            );
-            process::exit(1);
-        }
+        "always" => Ok(PagingMode::Always),
+        "never" => Ok(PagingMode::Never),
+        "auto" => Ok(PagingMode::QuitIfOneScreen),
+        _ => Err(format!(
+            "Invalid value for --paging option: {} (valid values are \"always\", \"never\", and \"auto\")",
+            paging_mode_string
+        )),
+    }
+}
+
+fn parse_output_format(output_format_string: &str) -> Result<cli::OutputFormat, String> {
+    match output_format_string.to_lowercase().as_str() {
+        "ansi" => Ok(cli::OutputFormat::Ansi),
+        "html" => Ok(cli::OutputFormat::Html),
+        "svg" => Ok(cli::OutputFormat::Svg),
+        "json" => Ok(cli::OutputFormat::Json),
+        _ => Err(format!(
+            "Invalid value for --output-format option: {} (valid values are \"ansi\", \"html\", \"svg\" and \"json\")",
+            output_format_string
+        )),
     }
 }
 
@@ -524,7 +601,7 @@ fn set_widths(
     git_config: &mut Option<git_config::GitConfig>,
     arg_matches: &clap::ArgMatches,
     option_names: &HashMap<&str, &str>,
-) {
+) -> Result<(), String> {
     // Allow one character in case e.g. `less --status-column` is in effect. See #41 and #10.
     opt.computed.available_terminal_width = (Term::stdout().size().1 - 1) as usize;
 
@@ -545,10 +622,9 @@ fn set_widths(
     {
         Some("variable") => (cli::Width::Variable, false),
         Some(width) => {
-            let width = width.parse().unwrap_or_else(|_| {
-                eprintln!("Could not parse width as a positive integer: {:?}", width);
-                process::exit(1);
-            });
+            let width = width
+                .parse()
+                .map_err(|_| format!("Could not parse width as a positive integer: {:?}", width))?;
             (cli::Width::Fixed(width), true)
         }
         None => (
@@ -559,9 +635,13 @@ fn set_widths(
     opt.computed.decorations_width = decorations_width;
     opt.computed.background_color_extends_to_terminal_width =
         background_color_extends_to_terminal_width;
+    Ok(())
 }
 
-fn set_git_config_entries(opt: &mut cli::Opt, git_config: &mut git_config::GitConfig) {
+fn set_git_config_entries(
+    opt: &mut cli::Opt,
+    git_config: &mut git_config::GitConfig,
+) -> Result<(), String> {
     // Styles
     for key in &["color.diff.old", "color.diff.new"] {
         if let Some(style_string) = git_config.get::<String>(key) {
@@ -570,17 +650,47 @@ fn set_git_config_entries(opt: &mut cli::Opt, git_config: &mut git_config::GitCo
         }
     }
 
+    // Booleans
+    for key in &["diff.mnemonicprefix", "diff.noprefix"] {
+        if let Some(value) = git_config.get::<bool>(key) {
+            opt.git_config_entries
+                .insert(key.to_string(), GitConfigEntry::Bool(value));
+        }
+    }
+
     // Strings
-    for key in &["remote.origin.url"] {
+    for key in &["diff.srcprefix", "diff.dstprefix"] {
         if let Some(string) = git_config.get::<String>(key) {
-            if let Ok(repo) = git_config_entry::GitRemoteRepo::from_str(&string) {
-                opt.git_config_entries
-                    .insert(key.to_string(), GitConfigEntry::GitRemote(repo));
+            opt.git_config_entries
+                .insert(key.to_string(), GitConfigEntry::String(string));
+        }
+    }
+
+    let key = format!("remote.{}.url", opt.hyperlinks_remote);
+    if let Some(string) = git_config.get::<String>(&key) {
+        if let Ok(mut repo) = git_config_entry::GitRemoteRepo::from_str(&string) {
+            if !opt.hyperlinks_forge.is_empty() {
+                repo.forge =
+                    git_config_entry::GitForge::from_str(&opt.hyperlinks_forge).map_err(|_| {
+                        format!(
+                            "Invalid value for --hyperlinks-forge option: {}. Valid values are \
+                             \"github\", \"gitlab\", \"bitbucket\", \"gitea\", and \"sourcehut\".",
+                            opt.hyperlinks_forge
+                        )
+                    })?;
             }
+            opt.git_config_entries
+                .insert(key, GitConfigEntry::GitRemote(repo));
         }
     }
 
     if let Some(repo) = &git_config.repo {
+        if let Ok(remotes) = repo.remotes() {
+            opt.git_config_entries.insert(
+                "delta.__remotes__".to_string(),
+                GitConfigEntry::String(remotes.iter().flatten().collect::<Vec<_>>().join(" ")),
+            );
+        }
         if let Some(workdir) = repo.workdir() {
             opt.git_config_entries.insert(
                 "delta.__workdir__".to_string(),
@@ -588,6 +698,87 @@ fn set_git_config_entries(opt: &mut cli::Opt, git_config: &mut git_config::GitCo
             );
         }
     }
+    Ok(())
+}
+
+/// Replace references to named colors ('$name') in style strings with their definitions in the
+/// [delta "palette"] section of git config.
+fn expand_palette_references(
+    opt: &mut cli::Opt,
+    git_config: &Option<git_config::GitConfig>,
+) -> Result<(), String> {
+    let palette: HashMap<String, String> = match git_config {
+        Some(git_config) => git_config
+            .get_entries_matching(r"^delta\.palette\.")
+            .into_iter()
+            .map(|(name, value)| (name["delta.palette.".len()..].to_lowercase(), value))
+            .collect(),
+        None => HashMap::new(),
+    };
+    for style_string in vec![
+        &mut opt.commit_author_style,
+        &mut opt.commit_branch_style,
+        &mut opt.commit_date_style,
+        &mut opt.commit_decoration_style,
+        &mut opt.commit_remote_branch_style,
+        &mut opt.commit_style,
+        &mut opt.commit_subject_style,
+        &mut opt.commit_tag_style,
+        &mut opt.file_decoration_style,
+        &mut opt.file_style,
+        &mut opt.hunk_header_decoration_style,
+        &mut opt.hunk_header_style,
+        &mut opt.line_numbers_left_style,
+        &mut opt.line_numbers_minus_style,
+        &mut opt.line_numbers_plus_style,
+        &mut opt.line_numbers_right_style,
+        &mut opt.line_numbers_zero_style,
+        &mut opt.minus_emph_style,
+        &mut opt.minus_empty_line_marker_style,
+        &mut opt.minus_non_emph_style,
+        &mut opt.minus_style,
+        &mut opt.plus_emph_style,
+        &mut opt.plus_empty_line_marker_style,
+        &mut opt.plus_non_emph_style,
+        &mut opt.plus_style,
+        &mut opt.whitespace_error_style,
+        &mut opt.zero_style,
+    ] {
+        if style_string.contains('$') {
+            *style_string = expand_palette_references_in_style_string(style_string, &palette)?;
+        }
+    }
+    Ok(())
+}
+
+fn expand_palette_references_in_style_string(
+    style_string: &str,
+    palette: &HashMap<String, String>,
+) -> Result<String, String> {
+    // The first unknown name is reported.
+    let mut unknown_name = None;
+    let expanded = PALETTE_REFERENCE.replace_all(style_string, |captures: &Captures| {
+        let name = captures[1].to_lowercase();
+        match palette.get(&name) {
+            Some(value) => value.to_string(),
+            None => {
+                unknown_name.get_or_insert(name);
+                "".to_string()
+            }
+        }
+    });
+    match unknown_name {
+        Some(name) => Err(format!(
+            "Unknown palette color: ${}. \
+             Define it in the [delta \"palette\"] section of your gitconfig.",
+            name
+        )),
+        None => Ok(expanded.to_string()),
+    }
+}
+
+lazy_static! {
+    static ref PALETTE_REFERENCE: Regex = Regex::new(r"\$([A-Za-z0-9_-]+)").unwrap();
 }
 
 #[cfg(test)]
@@ -605,12 +796,24 @@ pub mod tests {
         let git_config_contents = b"
 [delta]
     24-bit-color = never
+    color = never
     color-only = false
+    colorblind = true
+    commit-author-style = black black
+    commit-branch-style = black black
+    commit-date-style = black black
     commit-decoration-style = black black
+    commit-hash-length = 7
+    commit-remote-branch-style = black black
     commit-style = black black
+    commit-subject-style = black black
+    commit-tag-style = black black
     dark = false
+    detect-dark-light = never
     diff-highlight = true
     diff-so-fancy = true
+    exclude = xxxyyyzzz
+    expand-generated-files = true
     features = xxxyyyzzz
     file-added-label = xxxyyyzzz
     file-decoration-style = black black
@@ -620,6 +823,12 @@ pub mod tests {
     file-style = black black
     hunk-header-decoration-style = black black
     hunk-header-style = black black
+    hyperlinks-commit-link-format = xxxyyyzzz
+    hyperlinks-forge = xxxyyyzzz
+    hyperlinks-issue-links = xxxyyyzzz
+    hyperlinks-remote = xxxyyyzzz
+    include = xxxyyyzzz
+    json-syntax-scopes = true
     keep-plus-minus-markers = true
     light = true
     line-numbers = true
@@ -631,23 +840,38 @@ pub mod tests {
     line-numbers-right-style = black black
     line-numbers-zero-style = black black
     max-line-distance = 77
+    max-line-length = 77
+    minus-emph-delimiters = <..>
     minus-emph-style = black black
     minus-empty-line-marker-style = black black
+    minus-marker = xxxyyyzzz
     minus-non-emph-style = black black
     minus-style = black black
     navigate = true
+    output-format = html
     paging = never
+    plus-emph-delimiters = <..>
     plus-emph-style = black black
     plus-empty-line-marker-style = black black
+    plus-marker = xxxyyyzzz
     plus-non-emph-style = black black
     plus-style = black black
     raw = true
+    relative-dates = true
+    relative-paths = true
     side-by-side = true
+    summarize-hidden-files = true
+    syntax-map = xxxyyyzzz
     syntax-theme = xxxyyyzzz
+    syntax-theme-background-emph-strength = 0.5
+    syntax-theme-background-min-contrast = 1.5
+    syntax-theme-background-strength = 0.25
+    syntax-theme-backgrounds = true
     tabs = 77
     whitespace-error-style = black black
     width = 77
     word-diff-regex = xxxyyyzzz
+    workers = 3
     zero-style = black black
     # no-gitconfig
 ";
@@ -660,13 +884,25 @@ pub mod tests {
         );
 
         assert_eq!(opt.true_color, "never");
+        assert_eq!(opt.color, "never");
         assert_eq!(opt.color_only, false);
+        assert_eq!(opt.commit_author_style, "black black");
+        assert_eq!(opt.commit_branch_style, "black black");
+        assert_eq!(opt.commit_date_style, "black black");
         assert_eq!(opt.commit_decoration_style, "black black");
+        assert_eq!(opt.commit_hash_length, 7);
+        assert_eq!(opt.commit_remote_branch_style, "black black");
         assert_eq!(opt.commit_style, "black black");
+        assert_eq!(opt.commit_subject_style, "black black");
+        assert_eq!(opt.commit_tag_style, "black black");
         assert_eq!(opt.dark, false);
+        assert_eq!(opt.detect_dark_light, "never");
         // TODO: should set_options not be called on any feature flags?
         // assert_eq!(opt.diff_highlight, true);
         // assert_eq!(opt.diff_so_fancy, true);
+        // assert_eq!(opt.colorblind, true);
+        assert_eq!(opt.exclude, "xxxyyyzzz");
+        assert_eq!(opt.expand_generated_files, true);
         assert!(opt.features.split_whitespace().any(|s| s == "xxxyyyzzz"));
         assert_eq!(opt.file_added_label, "xxxyyyzzz");
         assert_eq!(opt.file_decoration_style, "black black");
@@ -676,6 +912,15 @@ pub mod tests {
         assert_eq!(opt.file_style, "black black");
         assert_eq!(opt.hunk_header_decoration_style, "black black");
         assert_eq!(opt.hunk_header_style, "black black");
+        assert_eq!(
+            opt.hyperlinks_commit_link_format,
+            Some("xxxyyyzzz".to_string())
+        );
+        assert_eq!(opt.hyperlinks_forge, "xxxyyyzzz");
+        assert_eq!(opt.hyperlinks_issue_links, "xxxyyyzzz");
+        assert_eq!(opt.hyperlinks_remote, "xxxyyyzzz");
+        assert_eq!(opt.include, "xxxyyyzzz");
+        assert_eq!(opt.json_syntax_scopes, true);
         assert_eq!(opt.keep_plus_minus_markers, true);
         assert_eq!(opt.light, true);
         assert_eq!(opt.line_numbers, true);
@@ -687,23 +932,38 @@ pub mod tests {
         assert_eq!(opt.line_numbers_right_style, "black black");
         assert_eq!(opt.line_numbers_zero_style, "black black");
         assert_eq!(opt.max_line_distance, 77 as f64);
+        assert_eq!(opt.max_line_length, 77);
+        assert_eq!(opt.minus_emph_delimiters, "<..>");
         assert_eq!(opt.minus_emph_style, "black black");
         assert_eq!(opt.minus_empty_line_marker_style, "black black");
+        assert_eq!(opt.minus_marker, "xxxyyyzzz");
         assert_eq!(opt.minus_non_emph_style, "black black");
         assert_eq!(opt.minus_style, "black black");
         assert_eq!(opt.navigate, true);
+        assert_eq!(opt.output_format, "html");
         assert_eq!(opt.paging_mode, "never");
+        assert_eq!(opt.plus_emph_delimiters, "<..>");
         assert_eq!(opt.plus_emph_style, "black black");
         assert_eq!(opt.plus_empty_line_marker_style, "black black");
+        assert_eq!(opt.plus_marker, "xxxyyyzzz");
         assert_eq!(opt.plus_non_emph_style, "black black");
         assert_eq!(opt.plus_style, "black black");
         assert_eq!(opt.raw, true);
+        assert_eq!(opt.relative_dates, true);
+        assert_eq!(opt.relative_paths, true);
         assert_eq!(opt.side_by_side, true);
+        assert_eq!(opt.summarize_hidden_files, true);
+        assert_eq!(opt.syntax_map, "xxxyyyzzz");
         assert_eq!(opt.syntax_theme, Some("xxxyyyzzz".to_string()));
+        assert_eq!(opt.syntax_theme_background_emph_strength, 0.5);
+        assert_eq!(opt.syntax_theme_background_min_contrast, 1.5);
+        assert_eq!(opt.syntax_theme_background_strength, 0.25);
+        assert_eq!(opt.syntax_theme_backgrounds, true);
         assert_eq!(opt.tab_width, 77);
         assert_eq!(opt.whitespace_error_style, "black black");
         assert_eq!(opt.width, Some("77".to_string()));
         assert_eq!(opt.tokenization_regex, "xxxyyyzzz");
+        assert_eq!(opt.workers, 3);
         assert_eq!(opt.zero_style, "black black");
 
         assert_eq!(opt.computed.paging_mode, PagingMode::Never);
@@ -732,4 +992,30 @@ pub mod tests {
 
         remove_file(git_config_path).unwrap();
     }
+
+    #[test]
+    fn test_palette_references_are_expanded() {
+        let git_config_contents = b"
+[delta]
+    plus-emph-style = syntax $Added-Strong
+    minus-style = $removed ul
+
+[delta \"palette\"]
+    added-strong = \"#004000\"
+    removed = red rgb(64, 0, 0)
+";
+        let git_config_path = "delta__test_palette_references_are_expanded.gitconfig";
+
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--zero-style", "$removed"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+
+        assert_eq!(opt.plus_emph_style, "syntax #004000");
+        assert_eq!(opt.minus_style, "red rgb(64, 0, 0) ul");
+        assert_eq!(opt.zero_style, "red rgb(64, 0, 0)");
+
+        remove_file(git_config_path).unwrap();
+    }
 }
@@ -5,24 +5,33 @@
 /// This module sets those options. If the light/dark background mode choice is not made explicitly
 /// by the user, it is determined by the classification of the syntax theme into light-background
 /// vs dark-background syntax themes. If the user didn't choose a syntax theme, a dark-background
-/// default is selected.
+/// default is selected, unless the terminal reports a light background color.
 use syntect::highlighting::ThemeSet;
 
 use crate::bat::assets::HighlightingAssets;
 use crate::cli;
 use crate::env;
+use crate::terminal_background;
 
 #[allow(non_snake_case)]
 pub fn set__is_light_mode__syntax_theme__syntax_set(
     opt: &mut cli::Opt,
     assets: HighlightingAssets,
-) {
+) -> Result<(), String> {
     let syntax_theme_name_from_bat_theme = env::get_env_var("BAT_THEME");
+    let should_detect_light_mode = should_detect_light_mode(opt)?;
     let (is_light_mode, syntax_theme_name) = get_is_light_mode_and_syntax_theme_name(
         opt.syntax_theme.as_ref(),
         syntax_theme_name_from_bat_theme.as_ref(),
         opt.light,
         &assets.theme_set,
+        || {
+            if should_detect_light_mode {
+                terminal_background::terminal_has_light_background()
+            } else {
+                None
+            }
+        },
     );
     opt.computed.is_light_mode = is_light_mode;
 
@@ -32,6 +41,24 @@ pub fn set__is_light_mode__syntax_theme__syntax_set(
         Some(assets.theme_set.themes[&syntax_theme_name].clone())
     };
     opt.computed.syntax_set = assets.syntax_set;
+    Ok(())
+}
+
+/// Should the terminal be asked whether its background is light, according to --dark and
+/// --detect-dark-light?
+fn should_detect_light_mode(opt: &cli::Opt) -> Result<bool, String> {
+    let should_detect = match opt.detect_dark_light.as_ref() {
+        "always" => true,
+        "never" => false,
+        "auto" => atty::is(atty::Stream::Stdout),
+        _ => {
+            return Err(format!(
+                "Invalid value for --detect-dark-light option: {} (valid values are \"always\", \"never\", and \"auto\")",
+                opt.detect_dark_light
+            ));
+        }
+    };
+    Ok(should_detect && !opt.dark)
 }
 
 pub fn is_light_syntax_theme(theme: &str) -> bool {
@@ -69,13 +96,15 @@ fn is_no_syntax_highlighting_syntax_theme_name(theme_name: &str) -> bool {
 /// 2. Light vs dark mode is specified by the `--light` or `--dark` options. If these aren't
 ///    supplied then it is inferred from the chosen theme.
 ///
-/// In the absence of other factors, the default assumes a dark terminal background.
+/// In the absence of other factors, light vs dark mode is determined by asking the terminal for
+/// its background color (see --detect-dark-light); if that fails, the default assumes a dark
+/// terminal background.
 ///
 /// Specifically, the rules are as follows:
 ///
 /// | --theme    | $BAT_THEME | --light/--dark | Behavior                                                                   |
 /// |------------|------------|----------------|----------------------------------------------------------------------------|
-/// | -          | -          | -              | default dark theme, dark mode                                              |
+/// | -          | -          | -              | default theme and mode for detected background color, else dark            |
 /// | some_theme | (IGNORED)  | -              | some_theme with light/dark mode inferred accordingly                       |
 /// | -          | BAT_THEME  | -              | BAT_THEME, with light/dark mode inferred accordingly                       |
 /// | -          | -          | yes            | default light/dark theme, light/dark mode                                  |
@@ -86,11 +115,15 @@ fn get_is_light_mode_and_syntax_theme_name(
     bat_theme_env_var: Option<&String>,
     light_mode_arg: bool,
     theme_set: &ThemeSet,
+    detect_light_mode: impl FnOnce() -> Option<bool>,
 ) -> (bool, String) {
     let theme_arg = valid_syntax_theme_name_or_none(theme_arg, theme_set);
     let bat_theme_env_var = valid_syntax_theme_name_or_none(bat_theme_env_var, theme_set);
     match (theme_arg, bat_theme_env_var, light_mode_arg) {
-        (None, None, false) => (false, DEFAULT_DARK_SYNTAX_THEME.to_string()),
+        (None, None, false) => match detect_light_mode() {
+            Some(true) => (true, DEFAULT_LIGHT_SYNTAX_THEME.to_string()),
+            _ => (false, DEFAULT_DARK_SYNTAX_THEME.to_string()),
+        },
         (Some(theme_name), _, false) => (is_light_syntax_theme(&theme_name), theme_name),
         (None, Some(theme_name), false) => (is_light_syntax_theme(&theme_name), theme_name),
         (None, None, true) => (true, DEFAULT_LIGHT_SYNTAX_THEME.to_string()),
@@ -248,4 +281,44 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_syntax_theme_backgrounds() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--syntax-theme",
+            "Solarized (dark)",
+            "--24-bit-color",
+            "always",
+            "--syntax-theme-backgrounds",
+        ]);
+        let theme_background = config
+            .syntax_theme
+            .as_ref()
+            .unwrap()
+            .settings
+            .background
+            .unwrap();
+        let (minus, minus_emph, plus, plus_emph) =
+            color::get_background_color_defaults_from_syntax_theme(
+                theme_background,
+                0.15,
+                0.35,
+                1.1,
+                true,
+            );
+        assert_eq!(config.minus_style.ansi_term_style.background, Some(minus));
+        assert_eq!(
+            config.minus_emph_style.ansi_term_style.background,
+            Some(minus_emph)
+        );
+        assert_eq!(config.plus_style.ansi_term_style.background, Some(plus));
+        assert_eq!(
+            config.plus_emph_style.ansi_term_style.background,
+            Some(plus_emph)
+        );
+        assert_ne!(
+            Some(minus),
+            Some(color::get_minus_background_color_default(false, true))
+        );
+    }
 }
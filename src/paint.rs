@@ -16,17 +16,56 @@ use crate::edits;
 use crate::features::line_numbers;
 use crate::features::side_by_side;
 use crate::paint::superimpose_style_sections::superimpose_style_sections;
+use crate::parse;
 use crate::style::Style;
 
+// Language names, as used by git's builtin diff drivers (see `git help gitattributes`) and in
+// vim/emacs modelines, that are not also a language name or file extension known to syntect.
+// Other names are looked up directly.
+const LANGUAGE_NAME_SYNTAX_TOKENS: &[(&str, &str)] = &[
+    ("bash", "sh"),
+    ("bibtex", "bib"),
+    ("csharp", "cs"),
+    ("elixir", "ex"),
+    ("fortran", "f"),
+    ("golang", "go"),
+    ("markdown", "md"),
+    ("objc", "m"),
+    ("pascal", "pas"),
+    ("perl", "pl"),
+    ("python", "py"),
+    ("ruby", "rb"),
+    ("rust", "rs"),
+    ("scheme", "scm"),
+    ("zsh", "sh"),
+];
+
+/// The number of lines at the start and end of a file in which modelines are looked for (vim's
+/// default 'modelines' setting).
+const MODELINE_LINES: usize = 5;
+
+lazy_static! {
+    // E.g. "# vim: set ft=python ts=4 :" or "/* vi: syntax=c */"
+    static ref VIM_MODELINE_REGEX: Regex =
+        Regex::new(r"\b(?:vi|vim|Vim|ex):.*?[ :](?:ft|filetype|syn|syntax)=([\w+#-]+)").unwrap();
+    // E.g. "# -*- mode: python; coding: utf-8 -*-" or ";; -*- lisp -*-"
+    static ref EMACS_MODELINE_REGEX: Regex =
+        Regex::new(r"-\*-\s*(?:[^;]*;\s*)*?(?:mode:\s*)?([\w+#-]+)\s*(?:;.*)?-\*-").unwrap();
+}
+
 pub struct Painter<'a> {
-    pub minus_lines: Vec<(String, State)>,
-    pub plus_lines: Vec<(String, State)>,
+    // Each line is stored with its truncation marker: see Painter::truncate.
+    pub minus_lines: Vec<(String, String, State)>,
+    pub plus_lines: Vec<(String, String, State)>,
     pub writer: &'a mut dyn Write,
     pub syntax: &'a SyntaxReference,
     pub highlighter: HighlightLines<'a>,
     pub config: &'a config::Config,
     pub output_buffer: String,
     pub line_numbers_data: line_numbers::LineNumbersData<'a>,
+    // The syntax could not be determined from the file path, so it may instead be inferred from
+    // the file contents.
+    pub infer_syntax_from_contents: bool,
 }
 
 impl<'a> Painter<'a> {
@@ -52,19 +91,129 @@ impl<'a> Painter<'a> {
             writer,
             config,
             line_numbers_data,
+            infer_syntax_from_contents: false,
         }
     }
 
     pub fn set_syntax(&mut self, extension: Option<&str>) {
         self.syntax = Painter::get_syntax(&self.config.syntax_set, extension);
+        self.infer_syntax_from_contents =
+            Painter::find_syntax_by_extension(&self.config.syntax_set, extension).is_none();
     }
 
-    fn get_syntax(syntax_set: &'a SyntaxSet, extension: Option<&str>) -> &'a SyntaxReference {
-        if let Some(extension) = extension {
-            if let Some(syntax) = syntax_set.find_syntax_by_extension(extension) {
-                return syntax;
+    /// Set the syntax used to highlight the file at `path`. In order of precedence, the syntax is
+    /// determined by a matching --syntax-map entry, by a `diff=<driver>` gitattribute, or by the
+    /// file extension.
+    /// If none of these succeeds then the syntax may subsequently be inferred from the file
+    /// contents: see `infer_syntax_from_hunk`.
+    pub fn set_syntax_for_file(&mut self, path: &str) {
+        let config = self.config;
+        let extension = parse::get_file_extension_from_file_meta_line_file_path(path);
+        match Painter::get_syntax_from_syntax_map(path, config)
+            .or_else(|| Painter::get_syntax_from_diff_driver(path, config))
+            .or_else(|| Painter::find_syntax_by_extension(&config.syntax_set, extension))
+        {
+            Some(syntax) => {
+                self.syntax = syntax;
+                self.infer_syntax_from_contents = false;
+            }
+            None => {
+                self.syntax = Painter::get_syntax(&config.syntax_set, None);
+                self.infer_syntax_from_contents = true;
             }
         }
+    }
+
+    /// If the syntax could not be determined from the file path, try to infer it from the lines
+    /// of the first hunk of the file, before any of them are painted: from a shebang or similar
+    /// marker if the hunk starts at the first line of the file, or else from a vim or emacs
+    /// modeline. As in vim, modelines are only looked for in the first and last few lines: those
+    /// of the file, and those of the hunk if it is complete, since the hunk may end at the end of
+    /// the file. Later hunks are not examined, so the whole file is highlighted using one syntax.
+    pub fn infer_syntax_from_hunk(
+        &mut self,
+        lines: &[&str],
+        is_complete: bool,
+        starts_at_first_line: bool,
+    ) {
+        if !self.infer_syntax_from_contents {
+            return;
+        }
+        self.infer_syntax_from_contents = false;
+        let syntax_set = &self.config.syntax_set;
+        // Drop the initial -/+/space character added by git.
+        let lines: Vec<&str> = lines
+            .iter()
+            .map(|line| line.get(1..).unwrap_or(""))
+            .collect();
+        let first_lines = if starts_at_first_line {
+            &lines[..lines.len().min(MODELINE_LINES)]
+        } else {
+            &[]
+        };
+        let last_lines = if is_complete {
+            &lines[lines.len().saturating_sub(MODELINE_LINES)..]
+        } else {
+            &[]
+        };
+        let syntax = first_lines
+            .first()
+            .and_then(|line| syntax_set.find_syntax_by_first_line(line))
+            .or_else(|| {
+                first_lines
+                    .iter()
+                    .chain(last_lines)
+                    .filter_map(|line| get_language_name_from_modeline(line))
+                    .find_map(|name| Painter::find_syntax_by_language_name(syntax_set, name))
+            });
+        if let Some(syntax) = syntax {
+            self.syntax = syntax;
+            self.set_highlighter();
+        }
+    }
+
+    fn get_syntax_from_syntax_map(
+        path: &str,
+        config: &'a config::Config,
+    ) -> Option<&'a SyntaxReference> {
+        config
+            .syntax_map
+            .iter()
+            .find(|(glob, _)| glob.is_match(path))
+            .and_then(|(_, syntax_name)| config.syntax_set.find_syntax_by_name(syntax_name))
+    }
+
+    fn get_syntax_from_diff_driver(
+        path: &str,
+        config: &'a config::Config,
+    ) -> Option<&'a SyntaxReference> {
+        let driver = config.git_attributes.as_ref()?.get_diff_driver(path)?;
+        Painter::find_syntax_by_language_name(&config.syntax_set, &driver)
+    }
+
+    fn find_syntax_by_language_name(
+        syntax_set: &'a SyntaxSet,
+        name: &str,
+    ) -> Option<&'a SyntaxReference> {
+        let token = LANGUAGE_NAME_SYNTAX_TOKENS
+            .iter()
+            .find(|(language_name, _)| language_name.eq_ignore_ascii_case(name))
+            .map(|(_, token)| *token)
+            .unwrap_or(name);
+        syntax_set.find_syntax_by_token(token)
+    }
+
+    fn find_syntax_by_extension(
+        syntax_set: &'a SyntaxSet,
+        extension: Option<&str>,
+    ) -> Option<&'a SyntaxReference> {
+        extension.and_then(|extension| syntax_set.find_syntax_by_extension(extension))
+    }
+
+    fn get_syntax(syntax_set: &'a SyntaxSet, extension: Option<&str>) -> &'a SyntaxReference {
+        if let Some(syntax) = Painter::find_syntax_by_extension(syntax_set, extension) {
+            return syntax;
+        }
         return syntax_set
             .find_syntax_by_extension("txt")
             .unwrap_or_else(|| {
@@ -78,6 +227,27 @@ impl<'a> Painter<'a> {
         };
     }
 
+    /// Truncate a line longer than --max-line-length, returning the truncated line and a
+    /// "… (N more chars)" marker for the rest of it (empty if the line is not truncated). This is
+    /// done before syntax highlighting and edit inference, whose cost grows with the length of the
+    /// line (see align::Alignment); the marker is added to the line's style sections afterwards
+    /// (see append_truncation_marker).
+    pub fn truncate<'l>(&self, line: &'l str) -> (&'l str, String) {
+        let max_line_length = self.config.max_line_length;
+        if max_line_length == 0 {
+            return (line, "".to_string());
+        }
+        // The first grapheme is the -/+/space character added by git.
+        let mut graphemes = line.grapheme_indices(true).skip(max_line_length + 1);
+        match graphemes.next() {
+            Some((i, _)) => {
+                let n_more_chars = 1 + graphemes.count();
+                (&line[..i], format!("… ({} more chars)", n_more_chars))
+            }
+            None => (line, "".to_string()),
+        }
+    }
+
     /// Replace initial -/+ character with ' ', expand tabs as spaces, and optionally terminate with
     /// newline.
     // Terminating with newline character is necessary for many of the sublime syntax definitions to
@@ -142,10 +312,10 @@ impl<'a> Painter<'a> {
             side_by_side::paint_minus_and_plus_lines_side_by_side(
                 minus_line_syntax_style_sections,
                 minus_line_diff_style_sections,
-                self.minus_lines.iter().map(|(_, state)| state).collect(),
+                self.minus_lines.iter().map(|(_, _, state)| state).collect(),
                 plus_line_syntax_style_sections,
                 plus_line_diff_style_sections,
-                self.plus_lines.iter().map(|(_, state)| state).collect(),
+                self.plus_lines.iter().map(|(_, _, state)| state).collect(),
                 line_alignment,
                 &mut self.output_buffer,
                 self.config,
@@ -157,15 +327,11 @@ impl<'a> Painter<'a> {
                 Painter::paint_lines(
                     minus_line_syntax_style_sections,
                     minus_line_diff_style_sections,
-                    self.minus_lines.iter().map(|(_, state)| state),
+                    self.minus_lines.iter().map(|(_, _, state)| state),
                     &mut self.output_buffer,
                     self.config,
                     &mut Some(&mut self.line_numbers_data),
-                    if self.config.keep_plus_minus_markers {
-                        "-"
-                    } else {
-                        ""
-                    },
+                    &self.config.minus_marker,
                     Some(self.config.minus_empty_line_marker_style),
                     None,
                 );
@@ -174,15 +340,11 @@ impl<'a> Painter<'a> {
                 Painter::paint_lines(
                     plus_line_syntax_style_sections,
                     plus_line_diff_style_sections,
-                    self.plus_lines.iter().map(|(_, state)| state),
+                    self.plus_lines.iter().map(|(_, _, state)| state),
                     &mut self.output_buffer,
                     self.config,
                     &mut Some(&mut self.line_numbers_data),
-                    if self.config.keep_plus_minus_markers {
-                        "+"
-                    } else {
-                        ""
-                    },
+                    &self.config.plus_marker,
                     Some(self.config.plus_empty_line_marker_style),
                     None,
                 );
@@ -194,19 +356,25 @@ impl<'a> Painter<'a> {
 
     pub fn paint_zero_line(&mut self, line: &str) {
         let state = State::HunkZero;
-        let prefix = if self.config.keep_plus_minus_markers && !line.is_empty() {
-            &line[..1]
-        } else {
-            ""
+        let prefix = match &self.config.zero_marker {
+            Some(zero_marker) => zero_marker.as_str(),
+            None if self.config.keep_plus_minus_markers && !line.is_empty() => &line[..1],
+            None => "",
         };
-        let lines = vec![(self.prepare(line, true), state.clone())];
+        let (line, truncation_marker) = self.truncate(line);
+        let lines = vec![(self.prepare(line, true), truncation_marker, state.clone())];
         let syntax_style_sections = Painter::get_syntax_style_sections_for_lines(
             &lines,
             &state,
             &mut self.highlighter,
             &self.config,
         );
-        let diff_style_sections = vec![(self.config.zero_style, lines[0].0.as_str())]; // TODO: compute style from state
+        let mut diff_style_sections = vec![(self.config.zero_style, lines[0].0.as_str())]; // TODO: compute style from state
+        append_truncation_marker(
+            &mut diff_style_sections,
+            &lines[0].1,
+            self.config.zero_style,
+        );
 
         if self.config.side_by_side {
             side_by_side::paint_zero_lines_side_by_side(
@@ -278,7 +446,7 @@ impl<'a> Painter<'a> {
                 );
             if should_right_fill_background_color {
                 Painter::right_fill_background_color(&mut line, fill_style);
-            } else if line_is_empty {
+            } else if line_is_empty && !config.monochrome {
                 if let Some(empty_line_style) = empty_line_style {
                     Painter::mark_empty_line(
                         &empty_line_style,
@@ -423,7 +591,22 @@ impl<'a> Painter<'a> {
                 handled_prefix = true;
             }
             if !text.is_empty() {
-                ansi_strings.push(section_style.paint(text));
+                // Without colors, within-line edits are marked using delimiters.
+                let delimiters = match state {
+                    State::HunkMinus(_) if config.monochrome && section_style.is_emph => {
+                        Some(&config.minus_emph_delimiters)
+                    }
+                    State::HunkPlus(_) if config.monochrome && section_style.is_emph => {
+                        Some(&config.plus_emph_delimiters)
+                    }
+                    _ => None,
+                };
+                match delimiters {
+                    Some((open, close)) => {
+                        ansi_strings.push(section_style.paint(format!("{}{}{}", open, text, close)))
+                    }
+                    None => ansi_strings.push(section_style.paint(text)),
+                }
                 is_empty = false;
             }
         }
@@ -461,55 +644,62 @@ impl<'a> Painter<'a> {
     }
 
     pub fn get_syntax_style_sections_for_lines<'s>(
-        lines: &'s Vec<(String, State)>,
+        lines: &'s Vec<(String, String, State)>,
         state: &State,
         highlighter: &mut HighlightLines,
         config: &config::Config,
     ) -> Vec<Vec<(SyntectStyle, &'s str)>> {
         let fake = !Painter::should_compute_syntax_highlighting(state, config);
         let mut line_sections = Vec::new();
-        for (line, _) in lines.iter() {
-            if fake {
-                line_sections.push(vec![(config.null_syntect_style, line.as_str())])
+        for (line, truncation_marker, _) in lines.iter() {
+            let mut this_line_sections = if fake {
+                vec![(config.null_syntect_style, line.as_str())]
             } else {
                 // The first character is a space injected by delta. See comment in
                 // Painter:::prepare.
                 let mut this_line_sections = highlighter.highlight(&line[1..], &config.syntax_set);
                 this_line_sections.insert(0, (config.null_syntect_style, &line[..1]));
-                line_sections.push(this_line_sections);
-            }
+                this_line_sections
+            };
+            append_truncation_marker(
+                &mut this_line_sections,
+                truncation_marker,
+                config.null_syntect_style,
+            );
+            line_sections.push(this_line_sections);
         }
         line_sections
     }
 
     /// Set background styles to represent diff for minus and plus lines in buffer.
     fn get_diff_style_sections<'b>(
-        minus_lines: &'b Vec<(String, State)>,
-        plus_lines: &'b Vec<(String, State)>,
+        minus_lines: &'b Vec<(String, String, State)>,
+        plus_lines: &'b Vec<(String, String, State)>,
         config: &config::Config,
     ) -> (
         Vec<Vec<(Style, &'b str)>>,
         Vec<Vec<(Style, &'b str)>>,
         Vec<(Option<usize>, Option<usize>)>,
     ) {
-        let (minus_lines, minus_styles): (Vec<&str>, Vec<Style>) = minus_lines
+        let (minus_line_strings, minus_styles): (Vec<&str>, Vec<Style>) = minus_lines
             .iter()
-            .map(|(s, t)| (s.as_str(), *config.get_style(&t)))
+            .map(|(s, _, t)| (s.as_str(), *config.get_style(&t)))
             .unzip();
-        let (plus_lines, plus_styles): (Vec<&str>, Vec<Style>) = plus_lines
+        let (plus_line_strings, plus_styles): (Vec<&str>, Vec<Style>) = plus_lines
             .iter()
-            .map(|(s, t)| (s.as_str(), *config.get_style(&t)))
+            .map(|(s, _, t)| (s.as_str(), *config.get_style(&t)))
             .unzip();
         let mut diff_sections = edits::infer_edits(
-            minus_lines,
-            plus_lines,
-            minus_styles,
+            minus_line_strings,
+            plus_line_strings,
+            minus_styles.clone(),
             config.minus_emph_style, // FIXME
-            plus_styles,
+            plus_styles.clone(),
             config.plus_emph_style, // FIXME
             &config.tokenization_regex,
             config.max_line_distance,
             config.max_line_distance_for_naively_paired_lines,
+            config.max_alignment_cells,
         );
 
         let minus_non_emph_style = if config.minus_non_emph_style != config.minus_emph_style {
@@ -528,6 +718,21 @@ impl<'a> Painter<'a> {
             Some(config.whitespace_error_style),
             plus_non_emph_style,
         );
+        // The truncation markers are added after edit inference, so that they are not part of any
+        // edit, and have the line's style.
+        for (line_sections, ((_, truncation_marker, _), style)) in diff_sections
+            .0
+            .iter_mut()
+            .zip(minus_lines.iter().zip(minus_styles))
+            .chain(
+                diff_sections
+                    .1
+                    .iter_mut()
+                    .zip(plus_lines.iter().zip(plus_styles)),
+            )
+        {
+            append_truncation_marker(line_sections, truncation_marker, style);
+        }
         diff_sections
     }
 
@@ -571,6 +776,18 @@ impl<'a> Painter<'a> {
 
 // edits::annotate doesn't return "coalesced" annotations (see comment there), so we can't assume
 // that `sections.len() > 1 <=> (multiple styles)`.
+/// Add the truncation marker of a line (see Painter::truncate) to its syntax or diff style sections,
+/// in place of the newline that terminates the line.
+fn append_truncation_marker<'s, S>(sections: &mut Vec<(S, &'s str)>, marker: &'s str, style: S) {
+    if marker.is_empty() {
+        return;
+    }
+    if let Some((_, text)) = sections.last_mut() {
+        *text = text.strip_suffix('\n').unwrap_or(text);
+    }
+    sections.push((style, marker));
+}
+
 fn style_sections_contain_more_than_one_style(sections: &Vec<(Style, &str)>) -> bool {
     if sections.len() > 1 {
         let (first_style, _) = sections[0];
@@ -598,6 +815,15 @@ fn is_whitespace_error(sections: &Vec<(Style, &str)>) -> bool {
         .any(|(_, s)| NON_WHITESPACE_REGEX.is_match(s))
 }
 
+/// Return the language name specified by a vim or emacs modeline in `line`, if any.
+fn get_language_name_from_modeline(line: &str) -> Option<&str> {
+    VIM_MODELINE_REGEX
+        .captures(line)
+        .or_else(|| EMACS_MODELINE_REGEX.captures(line))
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str())
+}
+
 mod superimpose_style_sections {
     use syntect::highlighting::Style as SyntectStyle;
 
@@ -706,7 +932,7 @@ mod superimpose_style_sections {
         use syntect::highlighting::FontStyle as SyntectFontStyle;
         use syntect::highlighting::Style as SyntectStyle;
 
-        use crate::style::{DecorationStyle, Style};
+        use crate::style::{DecorationStyle, ExtendedAttributes, Style};
 
         lazy_static! {
             static ref SYNTAX_STYLE: SyntectStyle = SyntectStyle {
@@ -728,6 +954,7 @@ mod superimpose_style_sections {
                 is_raw: false,
                 is_syntax_highlighted: true,
                 decoration_style: DecorationStyle::NoDecoration,
+                extended_attributes: ExtendedAttributes::default(),
             };
         }
         lazy_static! {
@@ -743,6 +970,7 @@ mod superimpose_style_sections {
                 is_raw: false,
                 is_syntax_highlighted: false,
                 decoration_style: DecorationStyle::NoDecoration,
+                extended_attributes: ExtendedAttributes::default(),
             };
         }
         lazy_static! {
@@ -758,6 +986,7 @@ mod superimpose_style_sections {
                 is_raw: false,
                 is_syntax_highlighted: true,
                 decoration_style: DecorationStyle::NoDecoration,
+                extended_attributes: ExtendedAttributes::default(),
             };
         }
 
@@ -817,3 +1046,29 @@ mod superimpose_style_sections {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_language_name_from_modeline() {
+        for (line, expected) in &[
+            ("# vim: set ft=python ts=4 :", Some("python")),
+            ("// vim: ft=javascript", Some("javascript")),
+            ("/* vi: syntax=c */", Some("c")),
+            ("# vim:ts=2:filetype=ruby:", Some("ruby")),
+            ("# -*- python -*-", Some("python")),
+            ("# -*- mode: sh; coding: utf-8 -*-", Some("sh")),
+            (
+                ";; -*- coding: utf-8; mode: emacs-lisp -*-",
+                Some("emacs-lisp"),
+            ),
+            ("# -*- coding: utf-8 -*-", None),
+            ("x = vim + ft", None),
+            ("", None),
+        ] {
+            assert_eq!(get_language_name_from_modeline(line), *expected, "{}", line);
+        }
+    }
+}
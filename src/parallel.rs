@@ -0,0 +1,395 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::config::Config;
+use crate::delta::{
+    is_commit_header, is_start_of_file_section, HiddenFile, HiddenFiles, Input, Inputs,
+    SectionPainter,
+};
+use crate::events::{Event, Source};
+
+/// A worker sends the output of a section to the main thread in chunks of at least this many
+/// bytes.
+const OUTPUT_CHUNK_SIZE: usize = 8192;
+
+/// The number of sections per worker that may be read before the output of the earliest of them
+/// has been written. This bounds how far reading gets ahead of a pager that is not being scrolled.
+const MAX_PENDING_SECTIONS_PER_WORKER: usize = 2;
+
+/// Paint the file sections of the diff (see `SectionPainter`) on a pool of worker threads, each
+/// with its own syntax highlighter.
+///
+/// The main thread sends the events of each section to a worker as they are read (see
+/// `delta::delta`). Meanwhile, it writes the output of the earliest section that has not yet been written, as
+/// it is painted, so that the first screen is not held up by the sections that follow it. The
+/// output of later sections is held until the sections before them have been written.
+pub fn delta_in_parallel(
+    inputs: Inputs,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<()> {
+    let (job_sender, job_receiver) = mpsc::channel();
+    let job_receiver = Mutex::new(job_receiver);
+    thread::scope(|scope| {
+        let job_receiver = &job_receiver;
+        let mut n_workers = 0;
+        // Workers are started as sections are read, so that a small diff uses few threads.
+        let mut start_worker = |n_pending_sections: usize| {
+            if n_workers < config.workers && n_workers < n_pending_sections {
+                n_workers += 1;
+                scope.spawn(move || work(job_receiver, config));
+            }
+        };
+        let mut output = OrderedOutput::new(writer, config);
+        // The job sender is dropped on return, so that the workers finish.
+        dispatch_sections(inputs, job_sender, &mut start_worker, &mut output)
+    })
+}
+
+struct Job {
+    source: Source,
+    messages: Receiver<Message>,
+    output: Sender<Output>,
+}
+
+enum Message {
+    Event(Event),
+    /// Send the output painted so far (see `SectionPainter::flush`), followed by
+    /// `Output::Flushed`.
+    Flush,
+}
+
+enum Output {
+    Text(Vec<u8>),
+    Flushed,
+    Finished(Option<HiddenFile>),
+}
+
+/// Read the events of the diff, sending each section to a worker, and write their output.
+fn dispatch_sections(
+    inputs: Inputs,
+    job_sender: Sender<Job>,
+    start_worker: &mut dyn FnMut(usize),
+    output: &mut OrderedOutput,
+) -> std::io::Result<()> {
+    let max_pending_sections = MAX_PENDING_SECTIONS_PER_WORKER * output.config.workers;
+    let mut message_sender: Option<Sender<Message>> = None;
+    for input in inputs {
+        let (event, source) = match input {
+            Input::Event(event, source) => (event, source),
+            Input::Idle => {
+                // The sections before the current one are complete, so all their output is
+                // written, waiting for the workers if necessary.
+                if let Some(message_sender) = message_sender.as_ref() {
+                    if message_sender.send(Message::Flush).is_ok() {
+                        output.write_flushed_output()?;
+                    }
+                }
+                output.writer.flush()?;
+                continue;
+            }
+        };
+        if message_sender.is_none() || is_start_of_file_section(event.line(), &source) {
+            while output.sections.len() >= max_pending_sections {
+                output.write_earliest_section()?;
+            }
+            let (section_message_sender, section_message_receiver) = mpsc::channel();
+            let (output_sender, output_receiver) = mpsc::channel();
+            output.sections.push_back(PendingSection {
+                is_commit: is_commit_header(&event),
+                output: output_receiver,
+                is_started: false,
+            });
+            start_worker(output.sections.len());
+            job_sender
+                .send(Job {
+                    source,
+                    messages: section_message_receiver,
+                    output: output_sender,
+                })
+                .map_err(|_| worker_error())?;
+            message_sender = Some(section_message_sender);
+        }
+        if let Some(message_sender) = message_sender.as_ref() {
+            // If the worker has stopped, the error is reported when its output is written.
+            message_sender.send(Message::Event(event)).ok();
+        }
+        output.write_available()?;
+    }
+    drop(message_sender);
+    while !output.sections.is_empty() {
+        output.write_earliest_section()?;
+    }
+    output.finish()
+}
+
+/// Paint sections until there are no more.
+fn work(job_receiver: &Mutex<Receiver<Job>>, config: &Config) {
+    loop {
+        let job = job_receiver.lock().unwrap().recv();
+        match job {
+            // An error means that the main thread has stopped writing output.
+            Ok(job) => paint_section(job, config).ok(),
+            Err(_) => return,
+        };
+    }
+}
+
+fn paint_section(job: Job, config: &Config) -> std::io::Result<()> {
+    let mut writer = ChunkWriter {
+        buffer: Vec::new(),
+        sender: &job.output,
+    };
+    let mut section = SectionPainter::new(&mut writer, job.source, config);
+    for message in job.messages {
+        match message {
+            Message::Event(event) => section.handle_event(event)?,
+            Message::Flush => {
+                section.flush()?;
+                job.output
+                    .send(Output::Flushed)
+                    .map_err(|_| worker_error())?;
+            }
+        }
+    }
+    let hidden_file = section.finish()?;
+    writer.flush()?;
+    job.output
+        .send(Output::Finished(hidden_file))
+        .map_err(|_| worker_error())
+}
+
+/// Sends the output written to it in chunks.
+struct ChunkWriter<'a> {
+    buffer: Vec<u8>,
+    sender: &'a Sender<Output>,
+}
+
+impl<'a> Write for ChunkWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= OUTPUT_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let text = std::mem::take(&mut self.buffer);
+            self.sender
+                .send(Output::Text(text))
+                .map_err(|_| worker_error())?;
+        }
+        Ok(())
+    }
+}
+
+/// A section whose output has not all been written.
+struct PendingSection {
+    is_commit: bool,
+    output: Receiver<Output>,
+    is_started: bool,
+}
+
+/// Writes the output of the sections in the order in which they were read.
+struct OrderedOutput<'a> {
+    writer: &'a mut dyn Write,
+    config: &'a Config,
+    sections: VecDeque<PendingSection>,
+    hidden_files: HiddenFiles,
+}
+
+impl<'a> OrderedOutput<'a> {
+    fn new(writer: &'a mut dyn Write, config: &'a Config) -> Self {
+        Self {
+            writer,
+            config,
+            sections: VecDeque::new(),
+            hidden_files: HiddenFiles::default(),
+        }
+    }
+
+    /// Write the output that has been painted so far, without waiting.
+    fn write_available(&mut self) -> std::io::Result<()> {
+        while let Some(section) = self.sections.front() {
+            match section.output.try_recv() {
+                Ok(output) => self.write_output(output)?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(worker_error()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the rest of the output of the earliest section, waiting for it to be painted.
+    fn write_earliest_section(&mut self) -> std::io::Result<()> {
+        let n_sections = self.sections.len();
+        while self.sections.len() == n_sections {
+            match self.sections[0].output.recv() {
+                Ok(output) => self.write_output(output)?,
+                Err(_) => return Err(worker_error()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the output of the sections up to the current one, which is the last, as far as it has
+    /// been flushed (see `Message::Flush`).
+    fn write_flushed_output(&mut self) -> std::io::Result<()> {
+        while self.sections.len() > 1 {
+            self.write_earliest_section()?;
+        }
+        loop {
+            match self.sections[0].output.recv() {
+                Ok(Output::Flushed) => return Ok(()),
+                Ok(output) => self.write_output(output)?,
+                Err(_) => return Err(worker_error()),
+            }
+        }
+    }
+
+    /// Write output of the earliest section, preceded, if it is the first, by the lines standing
+    /// in for the files hidden in the sections before it.
+    fn write_output(&mut self, output: Output) -> std::io::Result<()> {
+        let section = &mut self.sections[0];
+        if !section.is_started {
+            section.is_started = true;
+            let is_commit = section.is_commit;
+            self.hidden_files
+                .write(is_commit, self.writer, self.config)?;
+        }
+        match output {
+            Output::Text(text) => self.writer.write_all(&text),
+            Output::Flushed => Ok(()),
+            Output::Finished(hidden_file) => {
+                self.sections.pop_front();
+                self.hidden_files.add(hidden_file);
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.hidden_files.write(true, self.writer, self.config)
+    }
+}
+
+fn worker_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "A delta worker thread stopped unexpectedly.",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::integration_test_utils;
+
+    fn assert_parallel_output_is_sequential_output(input: &str, args: &[&str]) {
+        let sequential_config =
+            integration_test_utils::make_config_from_args(&[args, &["--workers", "1"]].concat());
+        let parallel_config =
+            integration_test_utils::make_config_from_args(&[args, &["--workers", "4"]].concat());
+        assert_eq!(parallel_config.workers, 4);
+        let sequential_output = integration_test_utils::run_delta(input, &sequential_config);
+        let parallel_output = integration_test_utils::run_delta(input, &parallel_config);
+        assert!(!sequential_output.is_empty());
+        assert_eq!(parallel_output, sequential_output);
+    }
+
+    #[test]
+    fn test_parallel_output_is_sequential_output() {
+        let input = [
+            GIT_LOG,
+            include_str!("../etc/examples/205-highlight-bug.diff"),
+            include_str!("../etc/examples/72-color-moved.diff"),
+            include_str!("../etc/examples/125-merge-conflict-1.diff"),
+        ]
+        .concat();
+        assert_parallel_output_is_sequential_output(&input, &[]);
+        assert_parallel_output_is_sequential_output(&input, &["--line-numbers"]);
+        assert_parallel_output_is_sequential_output(&input, &["--side-by-side"]);
+    }
+
+    #[test]
+    fn test_parallel_output_is_sequential_output_with_hidden_files() {
+        assert_parallel_output_is_sequential_output(GIT_LOG, &["--exclude", "*.lock"]);
+        assert_parallel_output_is_sequential_output(
+            GIT_LOG,
+            &["--exclude", "*.lock", "--summarize-hidden-files"],
+        );
+        assert_parallel_output_is_sequential_output(
+            GIT_LOG,
+            &["--include", "src/*", "--summarize-hidden-files"],
+        );
+    }
+
+    #[test]
+    fn test_parallel_output_is_sequential_output_for_unified_diff() {
+        let input = "\
+--- one.rs	2020-10-03 12:30:00.000000000 -0400
++++ two.rs	2020-10-03 12:31:00.000000000 -0400
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"Hello\");
++    println!(\"Hello, world\");
+ }
+Only in b: three.rs
+";
+        assert_parallel_output_is_sequential_output(input, &[]);
+    }
+
+    const GIT_LOG: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Sat Oct 3 12:30:00 2020 -0400
+
+    Bump dependencies
+
+diff --git a/Cargo.lock b/Cargo.lock
+index 3e3fd5d..b5f2a6e 100644
+--- a/Cargo.lock
++++ b/Cargo.lock
+@@ -1,3 +1,3 @@
+ [[package]]
+ name = \"adler\"
+-checksum = \"aaaa\"
++checksum = \"bbbb\"
+diff --git a/src/main.rs b/src/main.rs
+index 2f1fb53..b1e7a3a 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"Hello\");
++    println!(\"Hello, world\");
+ }
+diff --git a/src/lib.rs b/src/lib.rs
+index 2f1fb53..b1e7a3a 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ mod config;
++mod parallel;
+ mod delta;
+ mod parse;
+
+commit 1e5d2e7d8fb1a0ab6a4e4a6e5e7e0ea2e2f5b1c6
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Fri Oct 2 12:30:00 2020 -0400
+
+    Update lockfile
+
+diff --git a/Cargo.lock b/Cargo.lock
+index 9d8e4a1..f3a6e2b 100644
+--- a/Cargo.lock
++++ b/Cargo.lock
+@@ -1 +1 @@
+-version = 3
++version = 4
+";
+}
@@ -4,10 +4,11 @@ use std::borrow::Cow;
 use std::path::Path;
 
 use crate::config::Config;
+use crate::events::FileChange;
 use crate::features;
 
 // https://git-scm.com/docs/git-config#Documentation/git-config.txt-diffmnemonicPrefix
-const DIFF_PREFIXES: [&str; 6] = ["a/", "b/", "c/", "i/", "o/", "w/"];
+pub const DIFF_PREFIXES: [&str; 6] = ["a/", "b/", "c/", "i/", "o/", "w/"];
 
 /// Given input like
 /// "--- one.rs	2019-11-20 06:16:08.000000000 +0100"
@@ -19,7 +20,14 @@ pub fn get_file_extension_from_marker_line(line: &str) -> Option<&str> {
         .and_then(|file| file.split('.').last())
 }
 
-pub fn get_file_path_from_file_meta_line(line: &str, git_diff_name: bool) -> String {
+/// Return the file path from a "---", "+++", "rename from", or "rename to" line. If
+/// `git_diff_name` is true, the path is stripped of the first of `diff_prefixes` that it starts
+/// with (git's "a/" and "b/", or their alternatives; see `Config::diff_prefixes`).
+pub fn get_file_path_from_file_meta_line<S: AsRef<str>>(
+    line: &str,
+    git_diff_name: bool,
+    diff_prefixes: &[S],
+) -> String {
     match line {
         line if line.starts_with("rename from ") => {
             let offset = "rename from ".len();
@@ -33,10 +41,11 @@ pub fn get_file_path_from_file_meta_line(line: &str, git_diff_name: bool) -> Str
             let offset = 4;
             match &line[offset..] {
                 path if path == "/dev/null" => "/dev/null",
-                path if git_diff_name && DIFF_PREFIXES.iter().any(|s| path.starts_with(s)) => {
-                    &path[2..]
-                }
-                path if git_diff_name => &path,
+                path if git_diff_name => diff_prefixes
+                    .iter()
+                    .find(|prefix| path.starts_with(prefix.as_ref()))
+                    .map(|prefix| &path[prefix.as_ref().len()..])
+                    .unwrap_or(path),
                 path => path.split('\t').next().unwrap_or(""),
             }
         }
@@ -45,6 +54,55 @@ pub fn get_file_path_from_file_meta_line(line: &str, git_diff_name: bool) -> Str
     .to_string()
 }
 
+/// Return the (minus, plus) file paths from a "Binary files a/x and b/x differ" line, stripped of
+/// their diff prefixes. For a file marked in gitattributes as not to be diffed (`-diff`), this line
+/// is the whole of git's diff, and gives the only paths.
+pub fn get_file_paths_from_binary_files_line<S: AsRef<str>>(
+    line: &str,
+    diff_prefixes: &[S],
+) -> Option<(String, String)> {
+    let paths = line
+        .strip_prefix("Binary files ")?
+        .strip_suffix(" differ")?;
+    // A path may contain " and ", so the second path is taken to start at the first " and " that
+    // is followed by a diff prefix or /dev/null.
+    let (minus_path, plus_path) = paths
+        .match_indices(" and ")
+        .map(|(i, separator)| (&paths[..i], &paths[i + separator.len()..]))
+        .find(|(_, plus_path)| {
+            *plus_path == "/dev/null"
+                || diff_prefixes
+                    .iter()
+                    .any(|prefix| plus_path.starts_with(prefix.as_ref()))
+        })
+        .or_else(|| paths.split_once(" and "))?;
+    let get_path =
+        |path| get_file_path_from_file_meta_line(&format!("+++ {}", path), true, diff_prefixes);
+    Some((get_path(minus_path), get_path(plus_path)))
+}
+
+/// Express `path`, which is relative to the repository root, relative to the directory `cwd`,
+/// which is also relative to the repository root.
+pub fn relativize_path(path: &str, cwd: &str) -> String {
+    if path == "/dev/null" {
+        return path.to_string();
+    }
+    let mut path_components = path.split('/').peekable();
+    let mut cwd_components = cwd.split('/').filter(|c| !c.is_empty()).peekable();
+    while let (Some(p), Some(c)) = (path_components.peek(), cwd_components.peek()) {
+        if p != c {
+            break;
+        }
+        path_components.next();
+        cwd_components.next();
+    }
+    cwd_components
+        .map(|_| "..")
+        .chain(path_components)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 pub fn get_file_extension_from_file_meta_line_file_path(path: &str) -> Option<&str> {
     if path.is_empty() || path == "/dev/null" {
         None
@@ -53,9 +111,10 @@ pub fn get_file_extension_from_file_meta_line_file_path(path: &str) -> Option<&s
     }
 }
 
-pub fn get_file_change_description_from_file_paths(
+pub fn get_file_change_description(
     minus_file: &str,
     plus_file: &str,
+    change: FileChange,
     comparing: bool,
     config: &Config,
 ) -> String {
@@ -70,29 +129,33 @@ pub fn get_file_change_description_from_file_paths(
             }
         };
         let format_file = |file| {
+            let text = match &config.cwd_relative_to_repo_root {
+                Some(cwd) if config.relative_paths => Cow::from(relativize_path(file, cwd)),
+                _ => Cow::from(file),
+            };
             if config.hyperlinks {
-                features::hyperlinks::format_osc8_file_hyperlink(file, None, file, config)
+                features::hyperlinks::format_osc8_file_hyperlink(file, None, &text, config)
             } else {
-                Cow::from(file)
+                text
             }
         };
-        match (minus_file, plus_file) {
-            (minus_file, plus_file) if minus_file == plus_file => format!(
+        match change {
+            FileChange::Modified => format!(
                 "{}{}",
                 format_label(&config.file_modified_label),
                 format_file(minus_file)
             ),
-            (minus_file, "/dev/null") => format!(
+            FileChange::Removed => format!(
                 "{}{}",
                 format_label(&config.file_removed_label),
                 format_file(minus_file)
             ),
-            ("/dev/null", plus_file) => format!(
+            FileChange::Added => format!(
                 "{}{}",
                 format_label(&config.file_added_label),
                 format_file(plus_file)
             ),
-            (minus_file, plus_file) => format!(
+            FileChange::Renamed => format!(
                 "{}{} ⟶   {}",
                 format_label(&config.file_renamed_label),
                 format_file(minus_file),
@@ -226,21 +289,25 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_file_meta_line() {
         assert_eq!(
-            get_file_path_from_file_meta_line("--- /dev/null", true),
+            get_file_path_from_file_meta_line("--- /dev/null", true, &DIFF_PREFIXES),
             "/dev/null"
         );
         for prefix in &DIFF_PREFIXES {
             assert_eq!(
-                get_file_path_from_file_meta_line(&format!("--- {}src/delta.rs", prefix), true),
+                get_file_path_from_file_meta_line(
+                    &format!("--- {}src/delta.rs", prefix),
+                    true,
+                    &DIFF_PREFIXES
+                ),
                 "src/delta.rs"
             );
         }
         assert_eq!(
-            get_file_path_from_file_meta_line("--- src/delta.rs", true),
+            get_file_path_from_file_meta_line("--- src/delta.rs", true, &DIFF_PREFIXES),
             "src/delta.rs"
         );
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ src/delta.rs", true),
+            get_file_path_from_file_meta_line("+++ src/delta.rs", true, &DIFF_PREFIXES),
             "src/delta.rs"
         );
     }
@@ -248,23 +315,27 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_file_meta_line_containing_spaces() {
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ a/my src/delta.rs", true),
+            get_file_path_from_file_meta_line("+++ a/my src/delta.rs", true, &DIFF_PREFIXES),
             "my src/delta.rs"
         );
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ my src/delta.rs", true),
+            get_file_path_from_file_meta_line("+++ my src/delta.rs", true, &DIFF_PREFIXES),
             "my src/delta.rs"
         );
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ a/src/my delta.rs", true),
+            get_file_path_from_file_meta_line("+++ a/src/my delta.rs", true, &DIFF_PREFIXES),
             "src/my delta.rs"
         );
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ a/my src/my delta.rs", true),
+            get_file_path_from_file_meta_line("+++ a/my src/my delta.rs", true, &DIFF_PREFIXES),
             "my src/my delta.rs"
         );
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ b/my src/my enough/my delta.rs", true),
+            get_file_path_from_file_meta_line(
+                "+++ b/my src/my enough/my delta.rs",
+                true,
+                &DIFF_PREFIXES
+            ),
             "my src/my enough/my delta.rs"
         );
     }
@@ -272,7 +343,7 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_file_meta_line_rename() {
         assert_eq!(
-            get_file_path_from_file_meta_line("rename from nospace/file2.el", true),
+            get_file_path_from_file_meta_line("rename from nospace/file2.el", true, &DIFF_PREFIXES),
             "nospace/file2.el"
         );
     }
@@ -280,19 +351,80 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_file_meta_line_rename_containing_spaces() {
         assert_eq!(
-            get_file_path_from_file_meta_line("rename from with space/file1.el", true),
+            get_file_path_from_file_meta_line(
+                "rename from with space/file1.el",
+                true,
+                &DIFF_PREFIXES
+            ),
             "with space/file1.el"
         );
     }
 
+    #[test]
+    fn test_get_file_path_from_git_file_meta_line_with_alternate_prefixes() {
+        let no_prefixes: [&str; 0] = [];
+        assert_eq!(
+            get_file_path_from_file_meta_line("+++ a/src/delta.rs", true, &no_prefixes),
+            "a/src/delta.rs"
+        );
+        assert_eq!(
+            get_file_path_from_file_meta_line("+++ w/src/delta.rs", true, &["i/", "w/"]),
+            "src/delta.rs"
+        );
+        assert_eq!(
+            get_file_path_from_file_meta_line("--- old/src/delta.rs", true, &["old/", "new/"]),
+            "src/delta.rs"
+        );
+    }
+
+    #[test]
+    fn test_get_file_paths_from_binary_files_line() {
+        assert_eq!(
+            get_file_paths_from_binary_files_line(
+                "Binary files a/data.bin and b/data.bin differ",
+                &DIFF_PREFIXES
+            ),
+            Some(("data.bin".to_string(), "data.bin".to_string()))
+        );
+        assert_eq!(
+            get_file_paths_from_binary_files_line(
+                "Binary files /dev/null and b/x and y.bin differ",
+                &DIFF_PREFIXES
+            ),
+            Some(("/dev/null".to_string(), "x and y.bin".to_string()))
+        );
+        let no_prefixes: [&str; 0] = [];
+        assert_eq!(
+            get_file_paths_from_binary_files_line("Binary files x and y differ", &no_prefixes),
+            Some(("x".to_string(), "y".to_string()))
+        );
+        assert_eq!(
+            get_file_paths_from_binary_files_line(
+                "Submodule x contains untracked content",
+                &no_prefixes
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_relativize_path() {
+        assert_eq!(relativize_path("src/delta.rs", ""), "src/delta.rs");
+        assert_eq!(relativize_path("src/delta.rs", "src"), "delta.rs");
+        assert_eq!(relativize_path("src/tests/x.rs", "src"), "tests/x.rs");
+        assert_eq!(relativize_path("README.md", "src/tests"), "../../README.md");
+        assert_eq!(relativize_path("src/delta.rs", "src/tests"), "../delta.rs");
+        assert_eq!(relativize_path("/dev/null", "src"), "/dev/null");
+    }
+
     #[test]
     fn test_get_file_path_from_file_meta_line() {
         assert_eq!(
-            get_file_path_from_file_meta_line("--- src/delta.rs", false),
+            get_file_path_from_file_meta_line("--- src/delta.rs", false, &DIFF_PREFIXES),
             "src/delta.rs"
         );
         assert_eq!(
-            get_file_path_from_file_meta_line("+++ src/delta.rs", false),
+            get_file_path_from_file_meta_line("+++ src/delta.rs", false, &DIFF_PREFIXES),
             "src/delta.rs"
         );
     }
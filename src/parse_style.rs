@@ -1,10 +1,8 @@
-use std::process;
-
 use bitflags::bitflags;
 
 use crate::color;
 use crate::config::delta_unreachable;
-use crate::style::{DecorationStyle, Style};
+use crate::style::{BoxShape, DecorationStyle, ExtendedAttributes, Style, UnderlineShape};
 
 impl Style {
     /// Construct Style from style and decoration-style strings supplied on command line, together
@@ -17,22 +15,28 @@ impl Style {
         decoration_style_string: Option<&str>,
         true_color: bool,
         is_emph: bool,
-    ) -> Self {
-        let (ansi_term_style, is_omitted, is_raw, is_syntax_highlighted) =
-            parse_ansi_term_style(&style_string, default, true_color);
+    ) -> Result<Self, String> {
+        let (extended_attributes, style_string) =
+            extract_extended_attributes(style_string, true_color)?;
+        let (mut ansi_term_style, is_omitted, is_raw, is_syntax_highlighted) =
+            parse_ansi_term_style(&style_string, default, true_color)?;
+        if extended_attributes.underline_shape.is_some() {
+            ansi_term_style.is_underline = true;
+        }
         let decoration_style =
-            DecorationStyle::from_str(decoration_style_string.unwrap_or(""), true_color);
-        Self {
+            DecorationStyle::from_str(decoration_style_string.unwrap_or(""), true_color)?;
+        Ok(Self {
             ansi_term_style,
             is_emph,
             is_omitted,
             is_raw,
             is_syntax_highlighted,
             decoration_style,
-        }
+            extended_attributes,
+        })
     }
 
-    pub fn from_git_str(git_style_string: &str) -> Self {
+    pub fn from_git_str(git_style_string: &str) -> Result<Self, String> {
         Self::from_str(git_style_string, None, None, true, false)
     }
 
@@ -43,7 +47,7 @@ impl Style {
         decoration_style_string: Option<&str>,
         true_color: bool,
         is_emph: bool,
-    ) -> Self {
+    ) -> Result<Self, String> {
         let (special_attributes_from_style_string, style_string) =
             extract_special_decoration_attributes_from_non_decoration_style_string(style_string);
         let mut style = Style::from_str(
@@ -52,13 +56,13 @@ impl Style {
             decoration_style_string.as_deref(),
             true_color,
             is_emph,
-        );
+        )?;
         // TODO: box in this context resulted in box-with-underline for commit and file
         style.decoration_style = DecorationStyle::apply_special_decoration_attributes(
             &mut style,
             special_attributes_from_style_string,
-        );
-        style
+        )?;
+        Ok(style)
     }
 
     /// As from_str_with_handling_of_special_decoration_attributes but respecting an optional
@@ -70,24 +74,24 @@ impl Style {
         deprecated_foreground_color_arg: Option<&str>,
         true_color: bool,
         is_emph: bool,
-    ) -> Self {
+    ) -> Result<Self, String> {
         let mut style = Self::from_str_with_handling_of_special_decoration_attributes(
             style_string,
             default,
             decoration_style_string,
             true_color,
             is_emph,
-        );
+        )?;
         if let Some(s) = deprecated_foreground_color_arg {
             // The deprecated --{commit,file,hunk}-color args functioned to set the decoration
             // foreground color. In the case of file, it set the text foreground color also.
             let foreground_from_deprecated_arg =
-                parse_ansi_term_style(s, None, true_color).0.foreground;
+                parse_ansi_term_style(s, None, true_color)?.0.foreground;
             style.ansi_term_style.foreground = foreground_from_deprecated_arg;
             style.decoration_style = match style.decoration_style {
-                DecorationStyle::Box(mut ansi_term_style) => {
+                DecorationStyle::Box(mut ansi_term_style, shape) => {
                     ansi_term_style.foreground = foreground_from_deprecated_arg;
-                    DecorationStyle::Box(ansi_term_style)
+                    DecorationStyle::Box(ansi_term_style, shape)
                 }
                 DecorationStyle::Underline(mut ansi_term_style) => {
                     ansi_term_style.foreground = foreground_from_deprecated_arg;
@@ -101,22 +105,30 @@ impl Style {
                     ansi_term_style.foreground = foreground_from_deprecated_arg;
                     DecorationStyle::UnderOverline(ansi_term_style)
                 }
-                DecorationStyle::BoxWithUnderline(mut ansi_term_style) => {
+                DecorationStyle::BoxWithUnderline(mut ansi_term_style, shape) => {
+                    ansi_term_style.foreground = foreground_from_deprecated_arg;
+                    DecorationStyle::BoxWithUnderline(ansi_term_style, shape)
+                }
+                DecorationStyle::BoxWithOverline(mut ansi_term_style, shape) => {
                     ansi_term_style.foreground = foreground_from_deprecated_arg;
-                    DecorationStyle::BoxWithUnderline(ansi_term_style)
+                    DecorationStyle::BoxWithOverline(ansi_term_style, shape)
                 }
-                DecorationStyle::BoxWithOverline(mut ansi_term_style) => {
+                DecorationStyle::BoxWithUnderOverline(mut ansi_term_style, shape) => {
                     ansi_term_style.foreground = foreground_from_deprecated_arg;
-                    DecorationStyle::BoxWithOverline(ansi_term_style)
+                    DecorationStyle::BoxWithUnderOverline(ansi_term_style, shape)
                 }
-                DecorationStyle::BoxWithUnderOverline(mut ansi_term_style) => {
+                DecorationStyle::LeftBar(mut ansi_term_style) => {
                     ansi_term_style.foreground = foreground_from_deprecated_arg;
-                    DecorationStyle::BoxWithUnderOverline(ansi_term_style)
+                    DecorationStyle::LeftBar(ansi_term_style)
+                }
+                DecorationStyle::Banner(mut ansi_term_style) => {
+                    ansi_term_style.foreground = foreground_from_deprecated_arg;
+                    DecorationStyle::Banner(ansi_term_style)
                 }
                 DecorationStyle::NoDecoration => style.decoration_style,
             };
         }
-        style
+        Ok(style)
     }
 }
 
@@ -126,76 +138,100 @@ bitflags! {
         const BOX = 0b00000001;
         const OVERLINE = 0b00000010;
         const UNDERLINE = 0b00000100;
+        const LEFT_BAR = 0b00001000;
+        const BANNER = 0b00010000;
+        // Box shapes (only valid in combination with BOX)
+        const HEAVY = 0b00100000;
+        const ROUNDED = 0b01000000;
+        const DOUBLE = 0b10000000;
     }
 }
 
 impl DecorationStyle {
-    pub fn from_str(style_string: &str, true_color: bool) -> Self {
+    pub fn from_str(style_string: &str, true_color: bool) -> Result<Self, String> {
         let (special_attributes, style_string) =
             extract_special_decoration_attributes(&style_string);
         let (style, is_omitted, is_raw, is_syntax_highlighted) =
-            parse_ansi_term_style(&style_string, None, true_color);
+            parse_ansi_term_style(&style_string, None, true_color)?;
         if is_raw {
-            eprintln!("'raw' may not be used in a decoration style.");
-            process::exit(1);
+            return Err("'raw' may not be used in a decoration style.".to_string());
         };
         if is_syntax_highlighted {
-            eprintln!("'syntax' may not be used in a decoration style.");
-            process::exit(1);
+            return Err("'syntax' may not be used in a decoration style.".to_string());
         };
+        Ok(match special_attributes {
+            bits if bits == DecorationAttributes::EMPTY => DecorationStyle::NoDecoration,
+            bits => match Self::from_attributes(bits, style)? {
+                Some(decoration_style) => decoration_style,
+                None if is_omitted => DecorationStyle::NoDecoration,
+                None => {
+                    delta_unreachable("Unreachable code path reached in parse_decoration_style.")
+                }
+            },
+        })
+    }
+
+    /// Construct the decoration style specified by a (non-empty) set of special attributes, or
+    /// None if they do not specify a decoration style.
+    fn from_attributes(
+        special_attributes: DecorationAttributes,
+        style: ansi_term::Style,
+    ) -> Result<Option<Self>, String> {
         #[allow(non_snake_case)]
-        let (BOX, UL, OL, EMPTY) = (
+        let (BOX, UL, OL, LEFT_BAR, BANNER) = (
             DecorationAttributes::BOX,
             DecorationAttributes::UNDERLINE,
             DecorationAttributes::OVERLINE,
-            DecorationAttributes::EMPTY,
+            DecorationAttributes::LEFT_BAR,
+            DecorationAttributes::BANNER,
         );
-        match special_attributes {
-            bits if bits == EMPTY => DecorationStyle::NoDecoration,
-            bits if bits == BOX => DecorationStyle::Box(style),
-            bits if bits == UL => DecorationStyle::Underline(style),
-            bits if bits == OL => DecorationStyle::Overline(style),
-            bits if bits == UL | OL => DecorationStyle::UnderOverline(style),
-            bits if bits == BOX | UL => DecorationStyle::BoxWithUnderline(style),
-            bits if bits == BOX | OL => DecorationStyle::BoxWithOverline(style),
-            bits if bits == BOX | UL | OL => DecorationStyle::BoxWithUnderOverline(style),
-            _ if is_omitted => DecorationStyle::NoDecoration,
-            _ => delta_unreachable("Unreachable code path reached in parse_decoration_style."),
-        }
+        let box_shape = if special_attributes.contains(DecorationAttributes::ROUNDED) {
+            BoxShape::Rounded
+        } else if special_attributes.contains(DecorationAttributes::DOUBLE) {
+            BoxShape::Double
+        } else if special_attributes.contains(DecorationAttributes::HEAVY) || style.is_bold {
+            BoxShape::Heavy
+        } else {
+            BoxShape::Light
+        };
+        let bits = special_attributes
+            - (DecorationAttributes::HEAVY
+                | DecorationAttributes::ROUNDED
+                | DecorationAttributes::DOUBLE);
+        Ok(match bits {
+            bits if bits == BOX => Some(DecorationStyle::Box(style, box_shape)),
+            bits if bits == UL => Some(DecorationStyle::Underline(style)),
+            bits if bits == OL => Some(DecorationStyle::Overline(style)),
+            bits if bits == UL | OL => Some(DecorationStyle::UnderOverline(style)),
+            bits if bits == BOX | UL => Some(DecorationStyle::BoxWithUnderline(style, box_shape)),
+            bits if bits == BOX | OL => Some(DecorationStyle::BoxWithOverline(style, box_shape)),
+            bits if bits == BOX | UL | OL => {
+                Some(DecorationStyle::BoxWithUnderOverline(style, box_shape))
+            }
+            bits if bits == LEFT_BAR => Some(DecorationStyle::LeftBar(style)),
+            bits if bits == BANNER => Some(DecorationStyle::Banner(style)),
+            _ => {
+                return Err(
+                    "Invalid decoration style: 'left-bar' and 'banner' cannot be combined \
+                            with each other or with 'box', 'ul', or 'ol'."
+                        .to_string(),
+                );
+            }
+        })
     }
 
     fn apply_special_decoration_attributes(
         style: &mut Style,
         special_attributes: DecorationAttributes,
-    ) -> DecorationStyle {
-        let ansi_term_style = match style.decoration_style {
-            DecorationStyle::Box(ansi_term_style) => ansi_term_style,
-            DecorationStyle::Underline(ansi_term_style) => ansi_term_style,
-            DecorationStyle::Overline(ansi_term_style) => ansi_term_style,
-            DecorationStyle::UnderOverline(ansi_term_style) => ansi_term_style,
-            DecorationStyle::BoxWithUnderline(ansi_term_style) => ansi_term_style,
-            DecorationStyle::BoxWithOverline(ansi_term_style) => ansi_term_style,
-            DecorationStyle::BoxWithUnderOverline(ansi_term_style) => ansi_term_style,
-            DecorationStyle::NoDecoration => ansi_term::Style::new(),
-        };
-        #[allow(non_snake_case)]
-        let (BOX, UL, OL, EMPTY) = (
-            DecorationAttributes::BOX,
-            DecorationAttributes::UNDERLINE,
-            DecorationAttributes::OVERLINE,
-            DecorationAttributes::EMPTY,
-        );
-        match special_attributes {
-            bits if bits == EMPTY => style.decoration_style,
-            bits if bits == BOX => DecorationStyle::Box(ansi_term_style),
-            bits if bits == UL => DecorationStyle::Underline(ansi_term_style),
-            bits if bits == OL => DecorationStyle::Overline(ansi_term_style),
-            bits if bits == UL | OL => DecorationStyle::UnderOverline(ansi_term_style),
-            bits if bits == BOX | UL => DecorationStyle::BoxWithUnderline(ansi_term_style),
-            bits if bits == BOX | OL => DecorationStyle::BoxWithOverline(ansi_term_style),
-            bits if bits == BOX | UL | OL => DecorationStyle::BoxWithUnderOverline(ansi_term_style),
-            _ => DecorationStyle::NoDecoration,
-        }
+    ) -> Result<DecorationStyle, String> {
+        let ansi_term_style = style
+            .decoration_ansi_term_style()
+            .unwrap_or_else(ansi_term::Style::new);
+        Ok(match special_attributes {
+            bits if bits == DecorationAttributes::EMPTY => style.decoration_style,
+            bits => Self::from_attributes(bits, ansi_term_style)?
+                .unwrap_or(DecorationStyle::NoDecoration),
+        })
     }
 }
 
@@ -203,7 +239,7 @@ fn parse_ansi_term_style(
     s: &str,
     default: Option<Style>,
     true_color: bool,
-) -> (ansi_term::Style, bool, bool, bool) {
+) -> Result<(ansi_term::Style, bool, bool, bool), String> {
     let mut style = ansi_term::Style::new();
     let mut seen_foreground = false;
     let mut seen_background = false;
@@ -214,11 +250,7 @@ fn parse_ansi_term_style(
     let mut seen_omit = false;
     let mut seen_raw = false;
     let mut is_syntax_highlighted = false;
-    for word in s
-        .to_lowercase()
-        .split_whitespace()
-        .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
-    {
+    for word in split_style_string(s).iter().map(String::as_str) {
         if word == "blink" {
             style.is_blink = true;
         } else if word == "bold" {
@@ -249,30 +281,29 @@ fn parse_ansi_term_style(
                 style.foreground = default.and_then(|s| s.ansi_term_style.foreground);
                 is_syntax_highlighted = default.map(|s| s.is_syntax_highlighted).unwrap_or(false);
             } else {
-                style.foreground = color::parse_color(word, true_color);
+                style.foreground = color::parse_color(word, true_color)?;
             }
             seen_foreground = true;
         } else if !seen_background {
             if word == "syntax" {
-                eprintln!(
+                return Err(
                     "You have used the special color 'syntax' as a background color \
-                     (second color in a style string). It may only be used as a foreground \
-                     color (first color in a style string)."
+                            (second color in a style string). It may only be used as a \
+                            foreground color (first color in a style string)."
+                        .to_string(),
                 );
-                process::exit(1);
             } else if word == "auto" {
                 background_is_auto = true;
                 style.background = default.and_then(|s| s.ansi_term_style.background);
             } else {
-                style.background = color::parse_color(word, true_color);
+                style.background = color::parse_color(word, true_color)?;
             }
             seen_background = true;
         } else {
-            eprintln!(
+            return Err(format!(
                 "Invalid style string: {}. See the STYLES section of delta --help.",
                 s
-            );
-            process::exit(1);
+            ));
         }
     }
     if foreground_is_auto && background_is_auto {
@@ -283,7 +314,75 @@ fn parse_ansi_term_style(
             is_raw = default.map(|s| s.is_raw).unwrap_or(false);
         }
     }
-    (style, is_omitted, is_raw, is_syntax_highlighted)
+    Ok((style, is_omitted, is_raw, is_syntax_highlighted))
+}
+
+/// Split a style string into lowercase words, with surrounding quotes removed. A color function
+/// such as 'rgb(255, 0, 0)' is a single word even if it contains spaces.
+fn split_style_string(style_string: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut depth = 0;
+    for c in style_string.to_lowercase().chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => {
+                if !word.is_empty() {
+                    words.push(word.trim_matches(|c| c == '"' || c == '\'').to_string());
+                    word.clear();
+                }
+                continue;
+            }
+            _ => {}
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word.trim_matches(|c| c == '"' || c == '\'').to_string());
+    }
+    words
+}
+
+/// Extract the attributes that ansi_term::Style cannot represent ('ol', 'ul:curly',
+/// 'ul-color:yellow', etc) and return them along with the modified style string.
+fn extract_extended_attributes(
+    style_string: &str,
+    true_color: bool,
+) -> Result<(ExtendedAttributes, String), String> {
+    let mut attributes = ExtendedAttributes::default();
+    let mut new_style_string = Vec::new();
+    let words = split_style_string(style_string);
+    for token in words.iter().map(String::as_str) {
+        if token == "ol" || token == "overline" {
+            attributes.is_overline = true;
+        } else if let Some(shape) = token
+            .strip_prefix("ul:")
+            .or_else(|| token.strip_prefix("underline:"))
+        {
+            attributes.underline_shape = Some(match shape {
+                "double" => UnderlineShape::Double,
+                "curly" => UnderlineShape::Curly,
+                "dotted" => UnderlineShape::Dotted,
+                "dashed" => UnderlineShape::Dashed,
+                _ => {
+                    return Err(format!(
+                        "Invalid underline shape: {}. \
+                         Valid shapes are 'double', 'curly', 'dotted' and 'dashed'.",
+                        shape
+                    ));
+                }
+            });
+        } else if let Some(color) = token
+            .strip_prefix("ul-color:")
+            .or_else(|| token.strip_prefix("underline-color:"))
+        {
+            attributes.underline_color = color::parse_color(color, true_color)?;
+        } else {
+            new_style_string.push(token);
+        }
+    }
+    Ok((attributes, new_style_string.join(" ")))
 }
 
 /// Extract set of 'special decoration attributes' and return it along with modified style string.
@@ -306,13 +405,17 @@ fn _extract_special_decoration_attributes(
 ) -> (DecorationAttributes, String) {
     let mut attributes = DecorationAttributes::EMPTY;
     let mut new_style_string = Vec::new();
-    let style_string = style_string.to_lowercase();
-    for token in style_string
-        .split_whitespace()
-        .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
-    {
+    let words = split_style_string(style_string);
+    for token in words.iter().map(String::as_str) {
         match token {
             "box" => attributes |= DecorationAttributes::BOX,
+            "heavy-box" => attributes |= DecorationAttributes::BOX | DecorationAttributes::HEAVY,
+            "rounded-box" => {
+                attributes |= DecorationAttributes::BOX | DecorationAttributes::ROUNDED
+            }
+            "double-box" => attributes |= DecorationAttributes::BOX | DecorationAttributes::DOUBLE,
+            "left-bar" => attributes |= DecorationAttributes::LEFT_BAR,
+            "banner" => attributes |= DecorationAttributes::BANNER,
             token if token == "overline" || is_decoration_style_string && token == "ol" => {
                 attributes |= DecorationAttributes::OVERLINE
             }
@@ -337,11 +440,11 @@ mod tests {
     #[test]
     fn test_parse_ansi_term_style() {
         assert_eq!(
-            parse_ansi_term_style("", None, false),
+            parse_ansi_term_style("", None, false).unwrap(),
             (ansi_term::Style::new(), false, false, false)
         );
         assert_eq!(
-            parse_ansi_term_style("red", None, false),
+            parse_ansi_term_style("red", None, false).unwrap(),
             (
                 ansi_term::Style {
                     foreground: Some(ansi_term::Color::Fixed(
@@ -355,7 +458,7 @@ mod tests {
             )
         );
         assert_eq!(
-            parse_ansi_term_style("red green", None, false),
+            parse_ansi_term_style("red green", None, false).unwrap(),
             (
                 ansi_term::Style {
                     foreground: Some(ansi_term::Color::Fixed(
@@ -372,7 +475,7 @@ mod tests {
             )
         );
         assert_eq!(
-            parse_ansi_term_style("bold red underline green blink", None, false),
+            parse_ansi_term_style("bold red underline green blink", None, false).unwrap(),
             (
                 ansi_term::Style {
                     foreground: Some(ansi_term::Color::Fixed(
@@ -396,11 +499,11 @@ mod tests {
     #[test]
     fn test_parse_ansi_term_style_with_special_syntax_color() {
         assert_eq!(
-            parse_ansi_term_style("syntax", None, false),
+            parse_ansi_term_style("syntax", None, false).unwrap(),
             (ansi_term::Style::new(), false, false, true)
         );
         assert_eq!(
-            parse_ansi_term_style("syntax italic white hidden", None, false),
+            parse_ansi_term_style("syntax italic white hidden", None, false).unwrap(),
             (
                 ansi_term::Style {
                     background: Some(ansi_term::Color::Fixed(
@@ -416,7 +519,7 @@ mod tests {
             )
         );
         assert_eq!(
-            parse_ansi_term_style("bold syntax italic white hidden", None, false),
+            parse_ansi_term_style("bold syntax italic white hidden", None, false).unwrap(),
             (
                 ansi_term::Style {
                     background: Some(ansi_term::Color::Fixed(
@@ -437,12 +540,12 @@ mod tests {
     #[test]
     fn test_parse_ansi_term_style_with_special_omit_attribute() {
         assert_eq!(
-            parse_ansi_term_style("omit", None, false),
+            parse_ansi_term_style("omit", None, false).unwrap(),
             (ansi_term::Style::new(), true, false, false)
         );
         // It doesn't make sense for omit to be combined with anything else, but it is not an error.
         assert_eq!(
-            parse_ansi_term_style("omit syntax italic white hidden", None, false),
+            parse_ansi_term_style("omit syntax italic white hidden", None, false).unwrap(),
             (
                 ansi_term::Style {
                     background: Some(ansi_term::Color::Fixed(
@@ -462,12 +565,12 @@ mod tests {
     #[test]
     fn test_parse_ansi_term_style_with_special_raw_attribute() {
         assert_eq!(
-            parse_ansi_term_style("raw", None, false),
+            parse_ansi_term_style("raw", None, false).unwrap(),
             (ansi_term::Style::new(), false, true, false)
         );
         // It doesn't make sense for raw to be combined with anything else, but it is not an error.
         assert_eq!(
-            parse_ansi_term_style("raw syntax italic white hidden", None, false),
+            parse_ansi_term_style("raw syntax italic white hidden", None, false).unwrap(),
             (
                 ansi_term::Style {
                     background: Some(ansi_term::Color::Fixed(
@@ -530,7 +633,7 @@ mod tests {
     #[test]
     fn test_decoration_style_from_str_empty_string() {
         assert_eq!(
-            DecorationStyle::from_str("", true),
+            DecorationStyle::from_str("", true).unwrap(),
             DecorationStyle::NoDecoration,
         )
     }
@@ -538,16 +641,56 @@ mod tests {
     #[test]
     fn test_decoration_style_from_str() {
         assert_eq!(
-            DecorationStyle::from_str("ol red box bold green ul", true),
-            DecorationStyle::BoxWithUnderOverline(ansi_term::Style {
-                foreground: Some(ansi_term::Color::Fixed(1)),
-                background: Some(ansi_term::Color::Fixed(2)),
-                is_bold: true,
-                ..ansi_term::Style::new()
-            })
+            DecorationStyle::from_str("ol red box bold green ul", true).unwrap(),
+            DecorationStyle::BoxWithUnderOverline(
+                ansi_term::Style {
+                    foreground: Some(ansi_term::Color::Fixed(1)),
+                    background: Some(ansi_term::Color::Fixed(2)),
+                    is_bold: true,
+                    ..ansi_term::Style::new()
+                },
+                BoxShape::Heavy,
+            )
         )
     }
 
+    #[test]
+    fn test_decoration_style_from_str_box_shapes() {
+        let style = ansi_term::Style::new();
+        assert_eq!(
+            DecorationStyle::from_str("box", true).unwrap(),
+            DecorationStyle::Box(style, BoxShape::Light)
+        );
+        assert_eq!(
+            DecorationStyle::from_str("heavy-box", true).unwrap(),
+            DecorationStyle::Box(style, BoxShape::Heavy)
+        );
+        assert_eq!(
+            DecorationStyle::from_str("rounded-box ul", true).unwrap(),
+            DecorationStyle::BoxWithUnderline(style, BoxShape::Rounded)
+        );
+        assert_eq!(
+            DecorationStyle::from_str("double-box ol", true).unwrap(),
+            DecorationStyle::BoxWithOverline(style, BoxShape::Double)
+        );
+    }
+
+    #[test]
+    fn test_decoration_style_from_str_left_bar_and_banner() {
+        let blue = ansi_term::Style {
+            foreground: Some(ansi_term::Color::Fixed(4)),
+            ..ansi_term::Style::new()
+        };
+        assert_eq!(
+            DecorationStyle::from_str("blue left-bar", true).unwrap(),
+            DecorationStyle::LeftBar(blue)
+        );
+        assert_eq!(
+            DecorationStyle::from_str("banner blue", true).unwrap(),
+            DecorationStyle::Banner(blue)
+        );
+    }
+
     #[test]
     fn test_style_from_str() {
         let actual_style = Style::from_str(
@@ -556,7 +699,8 @@ mod tests {
             Some("ol red box bold green ul"),
             true,
             false,
-        );
+        )
+        .unwrap();
         let red_green_bold = ansi_term::Style {
             foreground: Some(ansi_term::Color::Fixed(1)),
             background: Some(ansi_term::Color::Fixed(2)),
@@ -567,21 +711,81 @@ mod tests {
             actual_style,
             Style {
                 ansi_term_style: red_green_bold,
-                decoration_style: DecorationStyle::BoxWithUnderOverline(red_green_bold),
+                decoration_style: DecorationStyle::BoxWithUnderOverline(
+                    red_green_bold,
+                    BoxShape::Heavy
+                ),
                 ..Style::new()
             }
         )
     }
 
+    #[test]
+    fn test_style_from_str_extended_attributes() {
+        let actual_style = Style::from_str(
+            "red ul:curly ul-color:yellow ol strike",
+            None,
+            None,
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            actual_style,
+            Style {
+                ansi_term_style: ansi_term::Style {
+                    foreground: Some(ansi_term::Color::Fixed(1)),
+                    is_underline: true,
+                    is_strikethrough: true,
+                    ..ansi_term::Style::new()
+                },
+                extended_attributes: ExtendedAttributes {
+                    underline_shape: Some(UnderlineShape::Curly),
+                    underline_color: Some(ansi_term::Color::Fixed(3)),
+                    is_overline: true,
+                },
+                ..Style::new()
+            }
+        );
+        assert_eq!(
+            Style::from_str("underline:dotted", None, None, true, false)
+                .unwrap()
+                .extended_attributes
+                .underline_shape,
+            Some(UnderlineShape::Dotted)
+        );
+    }
+
+    #[test]
+    fn test_style_from_str_with_color_functions() {
+        let style = Style::from_str(
+            "rgb(1, 2, 3) bold hsl(0, 100%, 50%)",
+            None,
+            None,
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            style.ansi_term_style,
+            ansi_term::Style {
+                foreground: Some(ansi_term::Color::RGB(1, 2, 3)),
+                background: Some(ansi_term::Color::RGB(255, 0, 0)),
+                is_bold: true,
+                ..ansi_term::Style::new()
+            }
+        );
+    }
+
     #[test]
     fn test_style_from_str_raw_with_box() {
-        let actual_style = Style::from_str("raw", None, Some("box"), true, false);
+        let actual_style = Style::from_str("raw", None, Some("box"), true, false).unwrap();
         let empty_ansi_term_style = ansi_term::Style::new();
         assert_eq!(
             actual_style,
             Style {
                 ansi_term_style: empty_ansi_term_style,
-                decoration_style: DecorationStyle::Box(empty_ansi_term_style),
+                decoration_style: DecorationStyle::Box(empty_ansi_term_style, BoxShape::Light),
                 is_raw: true,
                 ..Style::new()
             }
@@ -590,7 +794,8 @@ mod tests {
 
     #[test]
     fn test_style_from_str_decoration_style_only() {
-        let actual_style = Style::from_str("", None, Some("ol red box bold green ul"), true, false);
+        let actual_style =
+            Style::from_str("", None, Some("ol red box bold green ul"), true, false).unwrap();
         let red_green_bold = ansi_term::Style {
             foreground: Some(ansi_term::Color::Fixed(1)),
             background: Some(ansi_term::Color::Fixed(2)),
@@ -600,7 +805,10 @@ mod tests {
         assert_eq!(
             actual_style,
             Style {
-                decoration_style: DecorationStyle::BoxWithUnderOverline(red_green_bold),
+                decoration_style: DecorationStyle::BoxWithUnderOverline(
+                    red_green_bold,
+                    BoxShape::Heavy
+                ),
                 ..Style::new()
             }
         )
@@ -614,13 +822,17 @@ mod tests {
             Some("ol red box bold green ul"),
             true,
             false,
+        )
+        .unwrap();
+        let expected_decoration_style = DecorationStyle::BoxWithUnderOverline(
+            ansi_term::Style {
+                foreground: Some(ansi_term::Color::Fixed(1)),
+                background: Some(ansi_term::Color::Fixed(2)),
+                is_bold: true,
+                ..ansi_term::Style::new()
+            },
+            BoxShape::Heavy,
         );
-        let expected_decoration_style = DecorationStyle::BoxWithUnderOverline(ansi_term::Style {
-            foreground: Some(ansi_term::Color::Fixed(1)),
-            background: Some(ansi_term::Color::Fixed(2)),
-            is_bold: true,
-            ..ansi_term::Style::new()
-        });
         assert_eq!(
             actual_style,
             Style {
@@ -638,13 +850,14 @@ mod tests {
             Some("box"),
             true,
             false,
-        );
+        )
+        .unwrap();
         let empty_ansi_term_style = ansi_term::Style::new();
         assert_eq!(
             actual_style,
             Style {
                 ansi_term_style: empty_ansi_term_style,
-                decoration_style: DecorationStyle::Box(empty_ansi_term_style),
+                decoration_style: DecorationStyle::Box(empty_ansi_term_style, BoxShape::Light),
                 is_raw: true,
                 ..Style::new()
             }
@@ -654,15 +867,18 @@ mod tests {
     #[test]
     fn test_style_from_str_with_handling_of_special_decoration_attributes_and_respecting_deprecated_foreground_color_arg(
     ) {
-        let expected_decoration_style = DecorationStyle::BoxWithUnderOverline(ansi_term::Style {
-            foreground: Some(ansi_term::Color::Fixed(1)),
-            background: Some(ansi_term::Color::Fixed(2)),
-            is_bold: true,
-            ..ansi_term::Style::new()
-        });
+        let expected_decoration_style = DecorationStyle::BoxWithUnderOverline(
+            ansi_term::Style {
+                foreground: Some(ansi_term::Color::Fixed(1)),
+                background: Some(ansi_term::Color::Fixed(2)),
+                is_bold: true,
+                ..ansi_term::Style::new()
+            },
+            BoxShape::Heavy,
+        );
         let actual_style = Style::from_str_with_handling_of_special_decoration_attributes_and_respecting_deprecated_foreground_color_arg(
                 "", None, Some("ol red box bold green ul"), None, true, false
-            );
+            ).unwrap();
         assert_eq!(
             actual_style,
             Style {
@@ -682,13 +898,13 @@ mod tests {
             None,
             true,
             false,
-        );
+        ).unwrap();
         let empty_ansi_term_style = ansi_term::Style::new();
         assert_eq!(
             actual_style,
             Style {
                 ansi_term_style: empty_ansi_term_style,
-                decoration_style: DecorationStyle::Box(empty_ansi_term_style),
+                decoration_style: DecorationStyle::Box(empty_ansi_term_style, BoxShape::Light),
                 is_raw: true,
                 ..Style::new()
             }
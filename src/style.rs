@@ -15,20 +15,50 @@ pub struct Style {
     pub is_raw: bool,
     pub is_syntax_highlighted: bool,
     pub decoration_style: DecorationStyle,
+    pub extended_attributes: ExtendedAttributes,
+}
+
+/// Style attributes that are supported by many terminals but cannot be represented by
+/// ansi_term::Style.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExtendedAttributes {
+    pub underline_shape: Option<UnderlineShape>,
+    pub underline_color: Option<ansi_term::Color>,
+    pub is_overline: bool,
+}
+
+/// The shape of an underline (SGR 4:n).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnderlineShape {
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DecorationStyle {
-    Box(ansi_term::Style),
+    Box(ansi_term::Style, BoxShape),
     Underline(ansi_term::Style),
     Overline(ansi_term::Style),
     UnderOverline(ansi_term::Style),
-    BoxWithUnderline(ansi_term::Style),
-    BoxWithOverline(ansi_term::Style),
-    BoxWithUnderOverline(ansi_term::Style),
+    BoxWithUnderline(ansi_term::Style, BoxShape),
+    BoxWithOverline(ansi_term::Style, BoxShape),
+    BoxWithUnderOverline(ansi_term::Style, BoxShape),
+    LeftBar(ansi_term::Style),
+    Banner(ansi_term::Style),
     NoDecoration,
 }
 
+/// The line-drawing characters used for a box decoration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoxShape {
+    Light,
+    Heavy,
+    Rounded,
+    Double,
+}
+
 impl Style {
     pub fn new() -> Self {
         Self {
@@ -38,6 +68,7 @@ impl Style {
             is_raw: false,
             is_syntax_highlighted: false,
             decoration_style: DecorationStyle::NoDecoration,
+            extended_attributes: ExtendedAttributes::default(),
         }
     }
 
@@ -55,15 +86,19 @@ impl Style {
         }
     }
 
-    pub fn paint<'a, I, S: 'a + ToOwned + ?Sized>(
-        self,
-        input: I,
-    ) -> ansi_term::ANSIGenericString<'a, S>
+    /// Paint text with this style. Extended attributes are emitted as escape sequences within the
+    /// painted text, which turn them off again at the end of the text, so that they do not leak
+    /// into adjacent strings in an ansi_term::ANSIStrings.
+    pub fn paint<'a, I>(self, input: I) -> ansi_term::ANSIGenericString<'a, str>
     where
-        I: Into<Cow<'a, S>>,
-        <S as ToOwned>::Owned: fmt::Debug,
+        I: Into<Cow<'a, str>>,
     {
-        self.ansi_term_style.paint(input)
+        if self.extended_attributes == ExtendedAttributes::default() {
+            return self.ansi_term_style.paint(input);
+        }
+        let (prefix, suffix) = self.extended_attributes.sgr_prefix_and_suffix(&self);
+        self.ansi_term_style
+            .paint(format!("{}{}{}", prefix, input.into(), suffix))
     }
 
     pub fn get_background_color(&self) -> Option<ansi_term::Color> {
@@ -76,17 +111,49 @@ impl Style {
 
     pub fn decoration_ansi_term_style(&self) -> Option<ansi_term::Style> {
         match self.decoration_style {
-            DecorationStyle::Box(style) => Some(style),
+            DecorationStyle::Box(style, _) => Some(style),
             DecorationStyle::Underline(style) => Some(style),
             DecorationStyle::Overline(style) => Some(style),
             DecorationStyle::UnderOverline(style) => Some(style),
-            DecorationStyle::BoxWithUnderline(style) => Some(style),
-            DecorationStyle::BoxWithOverline(style) => Some(style),
-            DecorationStyle::BoxWithUnderOverline(style) => Some(style),
+            DecorationStyle::BoxWithUnderline(style, _) => Some(style),
+            DecorationStyle::BoxWithOverline(style, _) => Some(style),
+            DecorationStyle::BoxWithUnderOverline(style, _) => Some(style),
+            DecorationStyle::LeftBar(style) => Some(style),
+            DecorationStyle::Banner(style) => Some(style),
             DecorationStyle::NoDecoration => None,
         }
     }
 
+    /// Return this style without colors or other attributes, so that painting with it emits no
+    /// escape sequences. The shape of the decoration (box, underline, etc) is retained.
+    pub fn to_monochrome(self) -> Self {
+        let plain = ansi_term::Style::new();
+        Self {
+            ansi_term_style: plain,
+            is_syntax_highlighted: false,
+            decoration_style: match self.decoration_style {
+                DecorationStyle::Box(_, shape) => DecorationStyle::Box(plain, shape),
+                DecorationStyle::Underline(_) => DecorationStyle::Underline(plain),
+                DecorationStyle::Overline(_) => DecorationStyle::Overline(plain),
+                DecorationStyle::UnderOverline(_) => DecorationStyle::UnderOverline(plain),
+                DecorationStyle::BoxWithUnderline(_, shape) => {
+                    DecorationStyle::BoxWithUnderline(plain, shape)
+                }
+                DecorationStyle::BoxWithOverline(_, shape) => {
+                    DecorationStyle::BoxWithOverline(plain, shape)
+                }
+                DecorationStyle::BoxWithUnderOverline(_, shape) => {
+                    DecorationStyle::BoxWithUnderOverline(plain, shape)
+                }
+                DecorationStyle::LeftBar(_) => DecorationStyle::LeftBar(plain),
+                DecorationStyle::Banner(_) => DecorationStyle::Banner(plain),
+                DecorationStyle::NoDecoration => DecorationStyle::NoDecoration,
+            },
+            extended_attributes: ExtendedAttributes::default(),
+            ..self
+        }
+    }
+
     pub fn is_applied_to(&self, s: &str) -> bool {
         match ansi::parse::parse_first_style(s) {
             Some(parsed_style) => ansi_term_style_equality(parsed_style, self.ansi_term_style),
@@ -124,8 +191,19 @@ impl Style {
         if self.ansi_term_style.is_strikethrough {
             words.push("strike".to_string());
         }
-        if self.ansi_term_style.is_underline {
-            words.push("ul".to_string());
+        match (
+            self.ansi_term_style.is_underline,
+            self.extended_attributes.underline_shape,
+        ) {
+            (_, Some(shape)) => words.push(format!("ul:{}", shape)),
+            (true, None) => words.push("ul".to_string()),
+            (false, None) => {}
+        }
+        if let Some(color) = self.extended_attributes.underline_color {
+            words.push(format!("ul-color:{}", color::color_to_string(color)));
+        }
+        if self.extended_attributes.is_overline {
+            words.push("ol".to_string());
         }
 
         match (self.is_syntax_highlighted, self.ansi_term_style.foreground) {
@@ -143,6 +221,76 @@ impl Style {
     }
 }
 
+impl ExtendedAttributes {
+    /// Return the escape sequences that turn these attributes on, and those that restore the
+    /// state implied by `style`'s ansi_term::Style.
+    fn sgr_prefix_and_suffix(&self, style: &Style) -> (String, String) {
+        let mut on = Vec::new();
+        let mut off = Vec::new();
+        if let Some(shape) = self.underline_shape {
+            on.push(shape.sgr_parameter().to_string());
+            off.push(if style.ansi_term_style.is_underline {
+                "4"
+            } else {
+                "24"
+            });
+        }
+        if let Some(color) = self.underline_color {
+            on.push(format!("58;{}", sgr_color_parameters(color)));
+            off.push("59");
+        }
+        if self.is_overline {
+            on.push("53".to_string());
+            off.push("55");
+        }
+        (
+            format!("\x1b[{}m", on.join(";")),
+            format!("\x1b[{}m", off.join(";")),
+        )
+    }
+}
+
+impl UnderlineShape {
+    fn sgr_parameter(&self) -> &str {
+        match self {
+            UnderlineShape::Double => "4:2",
+            UnderlineShape::Curly => "4:3",
+            UnderlineShape::Dotted => "4:4",
+            UnderlineShape::Dashed => "4:5",
+        }
+    }
+}
+
+impl fmt::Display for UnderlineShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            UnderlineShape::Double => "double",
+            UnderlineShape::Curly => "curly",
+            UnderlineShape::Dotted => "dotted",
+            UnderlineShape::Dashed => "dashed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The SGR parameters specifying `color` in an extended color sequence such as SGR 58 (underline
+/// color).
+fn sgr_color_parameters(color: ansi_term::Color) -> String {
+    use ansi_term::Color::*;
+    match color {
+        Black => "5;0".to_string(),
+        Red => "5;1".to_string(),
+        Green => "5;2".to_string(),
+        Yellow => "5;3".to_string(),
+        Blue => "5;4".to_string(),
+        Purple => "5;5".to_string(),
+        Cyan => "5;6".to_string(),
+        White => "5;7".to_string(),
+        Fixed(n) => format!("5;{}", n),
+        RGB(r, g, b) => format!("2;{};{};{}", r, g, b),
+    }
+}
+
 fn ansi_term_style_equality(a: ansi_term::Style, b: ansi_term::Style) -> bool {
     let a_attrs = ansi_term::Style {
         foreground: None,
@@ -258,15 +406,37 @@ mod tests {
             ("normal",                          "+\x1b[mtext\x1b[m\n"),
             ("blink",                           "\x1b[5m+\x1b[m\x1b[5mtext\x1b[m\n"),
         ] {
-            assert!(Style::from_git_str(git_style_string).is_applied_to(git_output));
+            assert!(Style::from_git_str(git_style_string).unwrap().is_applied_to(git_output));
         }
     }
 
+    #[test]
+    fn test_paint_extended_attributes() {
+        let style =
+            Style::from_str("red ul:curly ul-color:yellow ol", None, None, true, false).unwrap();
+        assert_eq!(
+            style.paint("text").to_string(),
+            "\x1b[4;38;5;1m\x1b[4:3;58;5;3;53mtext\x1b[4;59;55m\x1b[0m"
+        );
+        assert_eq!(style.to_string(), "ul:curly ul-color:yellow ol red");
+
+        let style = Style::from_str("ul-color:#aabbcc", None, None, true, false).unwrap();
+        assert_eq!(
+            style.paint("text").to_string(),
+            "\x1b[58;2;170;187;204mtext\x1b[59m"
+        );
+
+        let style = Style::from_str("red ul", None, None, true, false).unwrap();
+        assert_eq!(style.paint("text").to_string(), "\x1b[4;38;5;1mtext\x1b[0m");
+    }
+
     #[test]
     fn test_is_applied_to_negative_assertion() {
         let style_string_from_24 = "bold #aabbcc ul 19 strike";
         let git_output_from_25 = "\x1b[1;4;9;38;5;19;48;2;170;187;204m+\x1b[m\x1b[1;4;9;38;5;19;48;2;170;187;204mtext\x1b[m\n";
-        assert!(!Style::from_git_str(style_string_from_24).is_applied_to(git_output_from_25));
+        assert!(!Style::from_git_str(style_string_from_24)
+            .unwrap()
+            .is_applied_to(git_output_from_25));
     }
 
     #[test]
@@ -0,0 +1,274 @@
+/// SVG output (--output-format=svg).
+///
+/// As with HTML output, delta's terminal output is rendered first, and the ANSI escape sequences in
+/// it are then interpreted. Here the text is laid out in a monospace grid of character cells: each
+/// run of text with the same style becomes a `<text>` element, preceded by a `<rect>` if it has a
+/// background color. The grid is as wide as the decorations (--width), or as the longest line if
+/// the width is variable.
+use std::io::{BufRead, Write};
+
+use bytelines::ByteLines;
+use unicode_width::UnicodeWidthChar;
+
+use crate::ansi::render::{css_color, escape, render, Renderer, State};
+use crate::cli;
+use crate::config::Config;
+use crate::delta::delta;
+
+const FONT_SIZE: f64 = 14.0;
+const CELL_WIDTH: f64 = 8.4;
+const CELL_HEIGHT: f64 = 18.0;
+const BASELINE: f64 = 14.0;
+const PADDING: f64 = 10.0;
+
+/// Run delta on `lines`, writing an SVG image to `writer`.
+pub fn delta_svg<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<()>
+where
+    I: BufRead + Send,
+{
+    let mut output = Vec::new();
+    delta(lines, &mut output, config)?;
+    writer.write_all(ansi_to_svg(&String::from_utf8_lossy(&output), config).as_bytes())
+}
+
+/// Convert text containing ANSI escape sequences, as emitted by delta, to an SVG image.
+pub fn ansi_to_svg(ansi: &str, config: &Config) -> String {
+    let mut renderer = SvgRenderer::new();
+    let state = render(ansi, &mut renderer, config);
+    if renderer.rows.last().map(|row| row.is_empty()) == Some(true) {
+        renderer.rows.pop();
+    }
+
+    let n_columns = match config.decorations_width {
+        cli::Width::Fixed(width) => width,
+        cli::Width::Variable => renderer
+            .rows
+            .iter()
+            .filter_map(|row| row.iter().map(|run| run.column + run.width).max())
+            .max()
+            .unwrap_or(0),
+    };
+    let width = 2.0 * PADDING + n_columns as f64 * CELL_WIDTH;
+    let height = 2.0 * PADDING + renderer.rows.len() as f64 * CELL_HEIGHT;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"{font_size}\">\n",
+        width = px(width),
+        height = px(height),
+        font_size = FONT_SIZE,
+    );
+    if let Some(color) = state.page_background {
+        svg.push_str(&format!(
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+            css_color(color)
+        ));
+    }
+    for (i, row) in renderer.rows.iter().enumerate() {
+        let y = PADDING + i as f64 * CELL_HEIGHT;
+        for run in row {
+            let x = PADDING + run.column as f64 * CELL_WIDTH;
+            if let Some(color) = run.background {
+                let run_width = match run.text {
+                    Some(_) => run.width,
+                    // A fill extends to the right-hand edge of the grid.
+                    None => n_columns.saturating_sub(run.column),
+                };
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    px(x),
+                    px(y),
+                    px(run_width as f64 * CELL_WIDTH),
+                    CELL_HEIGHT,
+                    css_color(color)
+                ));
+            }
+            if let Some(text) = &run.text {
+                if run.width == 0
+                    || (text.trim().is_empty() && !run.attributes.contains("text-decoration"))
+                {
+                    continue;
+                }
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\" \
+                     xml:space=\"preserve\"{}>{}</text>\n",
+                    px(x),
+                    px(y + BASELINE),
+                    px(run.width as f64 * CELL_WIDTH),
+                    run.attributes,
+                    escape(text)
+                ));
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Format a coordinate, rounded to avoid floating point noise such as 50.400000000000006.
+fn px(x: f64) -> String {
+    format!("{}", (x * 100.0).round() / 100.0)
+}
+
+/// A run of text in a single style, or (if `text` is None) a background fill to the end of the
+/// line.
+struct Run {
+    column: usize,
+    width: usize,
+    text: Option<String>,
+    background: Option<ansi_term::Color>,
+    attributes: String,
+}
+
+struct SvgRenderer {
+    rows: Vec<Vec<Run>>,
+    column: usize,
+    /// Text not yet emitted, all of which has the current style.
+    text: String,
+    text_width: usize,
+}
+
+impl SvgRenderer {
+    fn new() -> Self {
+        Self {
+            rows: vec![Vec::new()],
+            column: 0,
+            text: String::new(),
+            text_width: 0,
+        }
+    }
+
+    fn current_row(&mut self) -> &mut Vec<Run> {
+        self.rows.last_mut().unwrap()
+    }
+
+    /// SVG presentation attributes for text in style `state`.
+    fn attributes(state: &State) -> String {
+        let mut attributes = String::new();
+        if let Some(color) = state.effective_colors().0.or(state.page_foreground) {
+            attributes.push_str(&format!(" fill=\"{}\"", css_color(color)));
+        }
+        if state.style.is_bold {
+            attributes.push_str(" font-weight=\"bold\"");
+        }
+        if state.style.is_dimmed {
+            attributes.push_str(" opacity=\"0.6\"");
+        }
+        if state.style.is_italic {
+            attributes.push_str(" font-style=\"italic\"");
+        }
+        if state.style.is_hidden {
+            attributes.push_str(" visibility=\"hidden\"");
+        }
+        let text_decorations = state.text_decorations();
+        if !text_decorations.is_empty() {
+            attributes.push_str(&format!(
+                " text-decoration=\"{}\"",
+                text_decorations.join(" ")
+            ));
+        }
+        attributes
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn print(&mut self, c: char) {
+        let c = if c == '\t' { ' ' } else { c };
+        self.text.push(c);
+        self.text_width += c.width().unwrap_or(0);
+    }
+
+    fn flush_text(&mut self, state: &State) {
+        if self.text.is_empty() {
+            return;
+        }
+        let run = Run {
+            column: self.column,
+            width: self.text_width,
+            text: Some(self.text.clone()),
+            background: state.effective_colors().1,
+            attributes: Self::attributes(state),
+        };
+        self.column += self.text_width;
+        self.current_row().push(run);
+        self.text.clear();
+        self.text_width = 0;
+    }
+
+    fn end_line(&mut self, _state: &State) {
+        self.rows.push(Vec::new());
+        self.column = 0;
+    }
+
+    fn fill_line(&mut self, state: &State) {
+        if let Some(background) = state.effective_colors().1 {
+            let run = Run {
+                column: self.column,
+                width: 0,
+                text: None,
+                background: Some(background),
+                attributes: String::new(),
+            };
+            self.current_row().push(run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::ansi::render::tests::DIFF;
+    use crate::tests::integration_test_utils::integration_test_utils::make_config_from_args;
+
+    #[test]
+    fn test_ansi_to_svg_layout() {
+        let config = make_config_from_args(&["--syntax-theme", "none", "--width", "10"]);
+        let svg = ansi_to_svg("ab\x1b[1;48;2;1;2;3mc<\x1b[0K\x1b[0m\nd\n", &config);
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"104\" height=\"56\" "));
+        assert!(svg.contains(concat!(
+            "<text x=\"10\" y=\"24\" textLength=\"16.8\" lengthAdjust=\"spacingAndGlyphs\" ",
+            "xml:space=\"preserve\">ab</text>\n",
+            "<rect x=\"26.8\" y=\"10\" width=\"16.8\" height=\"18\" fill=\"#010203\"/>\n",
+            "<text x=\"26.8\" y=\"24\" textLength=\"16.8\" lengthAdjust=\"spacingAndGlyphs\" ",
+            "xml:space=\"preserve\" font-weight=\"bold\">c&lt;</text>\n",
+            "<rect x=\"43.6\" y=\"10\" width=\"50.4\" height=\"18\" fill=\"#010203\"/>\n",
+            "<text x=\"10\" y=\"42\" "
+        )));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_delta_svg() {
+        let config = make_config_from_args(&[
+            "--output-format",
+            "svg",
+            "--syntax-theme",
+            "none",
+            "--width",
+            "40",
+            "--minus-style",
+            "#ff0000",
+            "--plus-style",
+            "#00ff00",
+        ]);
+        let mut output = Vec::new();
+        delta_svg(
+            ByteLines::new(BufReader::new(DIFF.as_bytes())),
+            &mut output,
+            &config,
+        )
+        .unwrap();
+        let svg = String::from_utf8(output).unwrap();
+        assert!(!svg.contains('\x1b'));
+        assert!(svg.contains(" width=\"356\" "));
+        assert!(svg.contains(" fill=\"#ff0000\">a </text>"));
+        assert!(svg.contains(" fill=\"#00ff00\">a </text>"));
+    }
+}
@@ -0,0 +1,267 @@
+/// Determine whether the terminal has a light or dark background by asking the terminal for its
+/// background color (OSC 11). Terminals that do not support the query are detected by following it
+/// with a query that all terminals answer (primary device attributes, DA1): if the answer to that
+/// arrives first, the terminal will not answer the background color query.
+use std::time::Duration;
+
+use crate::color;
+
+/// How long to wait for the terminal to answer.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Return true if the terminal background is light, false if it is dark, and None if it could not
+/// be determined.
+pub fn terminal_has_light_background() -> Option<bool> {
+    query_terminal_background_color(TIMEOUT).map(is_light_color)
+}
+
+#[cfg(unix)]
+fn query_terminal_background_color(timeout: Duration) -> Option<(u8, u8, u8)> {
+    use std::fs::OpenOptions;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    unix::query_background_color(&mut tty, timeout)
+}
+
+#[cfg(not(unix))]
+fn query_terminal_background_color(_timeout: Duration) -> Option<(u8, u8, u8)> {
+    None
+}
+
+/// Return true if black text on this background has more contrast than white text.
+pub fn is_light_color(color: (u8, u8, u8)) -> bool {
+    color::contrast_ratio(color, (0x00, 0x00, 0x00))
+        > color::contrast_ratio(color, (0xff, 0xff, 0xff))
+}
+
+/// Parse the terminal's answer to an OSC 11 query, e.g. "\x1b]11;rgb:ffff/ffff/dddd\x07".
+/// Each component has 1 to 4 hex digits.
+fn parse_osc_11_response(response: &str) -> Option<(u8, u8, u8)> {
+    let start = response.find("\x1b]11;")? + "\x1b]11;".len();
+    let rest = &response[start..];
+    let end = rest.find(['\x07', '\x1b'].as_ref())?;
+    let spec = rest[..end].trim_start_matches("rgb:");
+    let components = spec
+        .split('/')
+        .map(|component| {
+            let digits = component.len();
+            if digits == 0 || digits > 4 {
+                return None;
+            }
+            let value = u32::from_str_radix(component, 16).ok()?;
+            let max = (1 << (4 * digits)) - 1;
+            Some((value * 255 / max) as u8)
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    match components.as_slice() {
+        [r, g, b] => Some((*r, *g, *b)),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    use termios::{tcflush, tcsetattr, Termios, ECHO, ICANON, TCIFLUSH, TCSANOW};
+
+    use super::parse_osc_11_response;
+
+    const QUERY: &[u8] = b"\x1b]11;?\x07\x1b[c";
+
+    /// How long to go on reading an answer that did not arrive in time, before giving up on it.
+    const DRAIN_TIMEOUT: Duration = Duration::from_millis(1000);
+
+    /// Send the background color query to the terminal `tty`, and read its answer, waiting at most
+    /// `timeout`. The terminal is placed in non-canonical, non-echoing mode while doing so.
+    pub fn query_background_color(tty: &mut File, timeout: Duration) -> Option<(u8, u8, u8)> {
+        let fd = tty.as_raw_fd();
+        let original_termios = Termios::from_fd(fd).ok()?;
+        let mut termios = original_termios;
+        termios.c_lflag &= !(ICANON | ECHO);
+        tcsetattr(fd, TCSANOW, &termios).ok()?;
+        let response = send_query_and_read_response(tty, timeout);
+        tcsetattr(fd, TCSANOW, &original_termios).ok()?;
+        parse_osc_11_response(&response?)
+    }
+
+    fn send_query_and_read_response(tty: &mut File, timeout: Duration) -> Option<String> {
+        tty.write_all(QUERY).ok()?;
+        tty.flush().ok()?;
+        let mut response = Vec::new();
+        if !read_response(tty, &mut response, timeout) {
+            // The answer of a slow terminal must still be read, or else it would be received as
+            // input by the pager or the shell.
+            read_response(tty, &mut response, DRAIN_TIMEOUT);
+            tcflush(tty.as_raw_fd(), TCIFLUSH).ok();
+            return None;
+        }
+        Some(String::from_utf8_lossy(&response).into_owned())
+    }
+
+    /// Read the terminal's answer into `response`, returning false if it is not complete within
+    /// `timeout`.
+    fn read_response(tty: &mut File, response: &mut Vec<u8>, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut buffer = [0; 64];
+        // The answer to the DA1 query ("\x1b[?...c") comes last.
+        while !is_complete(response) {
+            let remaining = match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) => remaining,
+                None => return false,
+            };
+            if !wait_until_readable(tty, remaining) {
+                return false;
+            }
+            match tty.read(&mut buffer) {
+                Ok(n) if n > 0 => response.extend_from_slice(&buffer[..n]),
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    fn is_complete(response: &[u8]) -> bool {
+        let response = String::from_utf8_lossy(response);
+        match response.rfind("\x1b[?") {
+            Some(i) => response[i..].contains('c'),
+            None => false,
+        }
+    }
+
+    fn wait_until_readable(tty: &File, timeout: Duration) -> bool {
+        let mut pollfd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout.as_millis() as libc::c_int;
+        unsafe { libc::poll(&mut pollfd, 1, timeout) > 0 }
+    }
+
+    #[cfg(test)]
+    #[cfg(target_os = "linux")]
+    mod tests {
+        use std::ffi::CStr;
+        use std::fs::{File, OpenOptions};
+        use std::io::{Read, Write};
+        use std::os::unix::io::FromRawFd;
+        use std::thread;
+        use std::time::Duration;
+
+        use super::*;
+
+        /// Open a pseudo-terminal, returning its (master, slave) ends.
+        fn open_pty() -> (File, File) {
+            unsafe {
+                let master_fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+                assert!(master_fd >= 0);
+                assert_eq!(libc::grantpt(master_fd), 0);
+                assert_eq!(libc::unlockpt(master_fd), 0);
+                let mut name = [0 as libc::c_char; 128];
+                assert_eq!(libc::ptsname_r(master_fd, name.as_mut_ptr(), name.len()), 0);
+                let slave_path = CStr::from_ptr(name.as_ptr()).to_str().unwrap().to_string();
+                let slave = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(slave_path)
+                    .unwrap();
+                (File::from_raw_fd(master_fd), slave)
+            }
+        }
+
+        /// Emulate a terminal which answers queries with `answer`.
+        fn query_emulated_terminal(answer: &'static [u8]) -> Option<(u8, u8, u8)> {
+            let (mut master, mut slave) = open_pty();
+            let terminal = thread::spawn(move || {
+                let mut query = vec![0; QUERY.len()];
+                master.read_exact(&mut query).unwrap();
+                assert_eq!(query, QUERY);
+                master.write_all(answer).unwrap();
+                master
+            });
+            let color = query_background_color(&mut slave, Duration::from_millis(2000));
+            terminal.join().unwrap();
+            color
+        }
+
+        #[test]
+        fn test_query_background_color_of_supporting_terminal() {
+            assert_eq!(
+                query_emulated_terminal(b"\x1b]11;rgb:fdfd/f6f6/e3e3\x1b\\\x1b[?62;22c"),
+                Some((0xfd, 0xf6, 0xe3))
+            );
+            assert_eq!(
+                query_emulated_terminal(b"\x1b]11;rgb:00/2b/36\x07\x1b[?1;2c"),
+                Some((0x00, 0x2b, 0x36))
+            );
+        }
+
+        #[test]
+        fn test_query_background_color_of_unsupporting_terminal() {
+            assert_eq!(query_emulated_terminal(b"\x1b[?1;2c"), None);
+        }
+
+        #[test]
+        fn test_late_answer_is_not_left_unread() {
+            let (mut master, mut slave) = open_pty();
+            let terminal = thread::spawn(move || {
+                let mut query = vec![0; QUERY.len()];
+                master.read_exact(&mut query).unwrap();
+                thread::sleep(Duration::from_millis(300));
+                master
+                    .write_all(b"\x1b]11;rgb:00/2b/36\x07\x1b[?1;2c")
+                    .unwrap();
+                master
+            });
+            let color = query_background_color(&mut slave, Duration::from_millis(100));
+            // The terminal is kept open, since closing it would make the tty readable.
+            let _master = terminal.join().unwrap();
+            assert_eq!(color, None);
+            // As for a shell reading its input a character at a time.
+            let mut termios = Termios::from_fd(slave.as_raw_fd()).unwrap();
+            termios.c_lflag &= !ICANON;
+            tcsetattr(slave.as_raw_fd(), TCSANOW, &termios).unwrap();
+            assert!(!wait_until_readable(&slave, Duration::from_millis(100)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc_11_response() {
+        assert_eq!(
+            parse_osc_11_response("\x1b]11;rgb:ffff/8080/0000\x07"),
+            Some((0xff, 0x80, 0x00))
+        );
+        assert_eq!(
+            parse_osc_11_response("\x1b]11;rgb:fff/000/888\x1b\\"),
+            Some((0xff, 0x00, 0x88))
+        );
+        assert_eq!(
+            parse_osc_11_response("\x1b]11;rgb:f/0/8\x07"),
+            Some((0xff, 0x00, 0x88))
+        );
+        assert_eq!(parse_osc_11_response("\x1b[?1;2c"), None);
+        assert_eq!(parse_osc_11_response("\x1b]11;rgb:ffff/ffff\x07"), None);
+    }
+
+    #[test]
+    fn test_is_light_color() {
+        assert!(is_light_color((0xff, 0xff, 0xff)));
+        assert!(is_light_color((0xfd, 0xf6, 0xe3))); // Solarized light
+        assert!(!is_light_color((0x00, 0x2b, 0x36))); // Solarized dark
+        assert!(!is_light_color((0x27, 0x28, 0x22))); // Monokai
+        assert!(!is_light_color((0x00, 0x00, 0x00)));
+    }
+}
@@ -1,5 +1,6 @@
 #[cfg(test)]
 pub mod integration_test_utils {
+    use std::convert::TryFrom;
     use std::fs::File;
     use std::io::{BufReader, Write};
     use std::path::Path;
@@ -21,6 +22,15 @@ pub mod integration_test_utils {
         let mut args: Vec<&str> = itertools::chain(&["/dev/null", "/dev/null"], args)
             .map(|s| *s)
             .collect();
+        // Tests must not depend on the terminal in which they are run, unless they set
+        // --detect-dark-light themselves.
+        if !args.contains(&"--detect-dark-light")
+            && !git_config_contents.map_or(false, |contents| {
+                String::from_utf8_lossy(contents).contains("detect-dark-light")
+            })
+        {
+            args.extend(&["--detect-dark-light", "never"]);
+        }
         let mut git_config = match (git_config_contents, git_config_path) {
             (Some(contents), Some(path)) => Some(make_git_config(contents, path)),
             _ => {
@@ -28,7 +38,7 @@ pub mod integration_test_utils {
                 None
             }
         };
-        cli::Opt::from_iter_and_git_config(args, &mut git_config)
+        cli::Opt::from_iter_and_git_config(args, &mut git_config).unwrap()
     }
 
     pub fn make_options_from_args(args: &[&str]) -> cli::Opt {
@@ -41,15 +51,16 @@ pub mod integration_test_utils {
         git_config_contents: Option<&[u8]>,
         git_config_path: Option<&str>,
     ) -> config::Config {
-        config::Config::from(make_options_from_args_and_git_config(
+        config::Config::try_from(make_options_from_args_and_git_config(
             args,
             git_config_contents,
             git_config_path,
         ))
+        .unwrap()
     }
 
     pub fn make_config_from_args(args: &[&str]) -> config::Config {
-        config::Config::from(make_options_from_args(args))
+        config::Config::try_from(make_options_from_args(args)).unwrap()
     }
 
     fn make_git_config(contents: &[u8], path: &str) -> GitConfig {
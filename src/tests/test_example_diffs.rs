@@ -1,9 +1,20 @@
 #[cfg(test)]
 mod tests {
+    use std::fs::{remove_dir_all, remove_file};
+    use std::io::{BufReader, Read, Write};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use bytelines::ByteLines;
     use console::strip_ansi_codes;
+    use regex::Regex;
 
     use crate::ansi;
-    use crate::delta::State;
+    use crate::delta::{delta, State};
+    use crate::git_attributes::tests::make_repo_with_gitattributes;
+    use crate::git_attributes::GitAttributes;
+    use crate::git_config_entry::GitConfigEntry;
     use crate::style;
     use crate::tests::ansi_test_utils::ansi_test_utils;
     use crate::tests::integration_test_utils::integration_test_utils;
@@ -253,6 +264,71 @@ commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
         ));
     }
 
+    #[test]
+    fn test_commit_meta_styles() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--commit-style",
+            "raw",
+            "--commit-decoration-style",
+            "omit",
+            "--commit-hash-length",
+            "7",
+            "--commit-branch-style",
+            "green",
+            "--commit-remote-branch-style",
+            "red",
+            "--commit-tag-style",
+            "yellow",
+            "--commit-author-style",
+            "blue",
+            "--commit-subject-style",
+            "bold",
+        ]);
+        let input = GIT_DIFF_SINGLE_HUNK.replace(
+            "commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e",
+            "commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e \
+             (HEAD -> master, origin/master, tag: v1.2)",
+        );
+        let output = integration_test_utils::run_delta(&input, &config);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            strip_ansi_codes(lines[0]),
+            "commit 94907c0 (HEAD -> master, origin/master, tag: v1.2)"
+        );
+        for (style, text) in &[
+            (config.commit_branch_style, "HEAD"),
+            (config.commit_branch_style, "master"),
+            (config.commit_remote_branch_style, "origin/master"),
+            (config.commit_tag_style, "v1.2"),
+        ] {
+            assert!(lines[0].contains(&style.paint(*text).to_string()));
+        }
+        assert_eq!(
+            lines[1],
+            format!(
+                "Author: {}",
+                config
+                    .commit_author_style
+                    .paint("Dan Davison <dandavison7@gmail.com>")
+            )
+        );
+        assert_eq!(lines[2], "Date:   Thu May 14 11:13:17 2020 -0400");
+        assert_eq!(
+            lines[4],
+            format!("    {}", config.commit_subject_style.paint("rustfmt"))
+        );
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        let config = integration_test_utils::make_config_from_args(&["--relative-dates"]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        let date_line = output.lines().find(|l| l.starts_with("Date:")).unwrap();
+        assert!(date_line.starts_with("Date:   "));
+        assert!(date_line.ends_with(" years ago"));
+    }
+
     #[test]
     fn test_commit_style_colored_input_color_is_stripped_under_normal() {
         let config = integration_test_utils::make_config_from_args(&[
@@ -368,7 +444,6 @@ commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
         ]);
     }
 
-    #[ignore]
     #[test]
     fn test_commit_style_box_ol() {
         _do_test_commit_style_box_ol(&[
@@ -379,6 +454,74 @@ commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
         ]);
     }
 
+    #[test]
+    fn test_commit_style_rounded_box() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--commit-style",
+            "blue",
+            "--commit-decoration-style",
+            "blue rounded-box",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains(
+            "\
+────────────────────────────────────────────────╮
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e │
+────────────────────────────────────────────────╯
+"
+        ));
+    }
+
+    #[test]
+    fn test_commit_style_double_box_ul_ol() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--commit-style",
+            "blue",
+            "--commit-decoration-style",
+            "blue double-box ul ol",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains("════════════════════════════════════════════════╦═"));
+        assert!(output.contains(
+            "═
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e ║
+════════════════════════════════════════════════╩═"
+        ));
+    }
+
+    #[test]
+    fn test_commit_style_left_bar() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--commit-style",
+            "blue",
+            "--commit-decoration-style",
+            "blue left-bar",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains("▌ commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e\n"));
+    }
+
+    #[test]
+    fn test_commit_style_banner() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--commit-style",
+            "blue",
+            "--commit-decoration-style",
+            "white blue banner",
+            "--width",
+            "60",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains(&format!(
+            "commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e{}\n",
+            " ".repeat(60 - 47)
+        )));
+    }
+
     #[test]
     fn test_commit_style_box_ul_deprecated_options() {
         _do_test_commit_style_box_ul(&["--commit-color", "blue", "--commit-style", "box"]);
@@ -476,13 +619,16 @@ commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e │
             &config,
         );
         let output = strip_ansi_codes(&output);
-        assert!(output.contains(
+        // The overline extends to the full width.
+        assert!(Regex::new(
             "\
-────────────────────────────────────────────────┬─
+────────────────────────────────────────────────┬─+
 commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e │
 ────────────────────────────────────────────────┘
 "
-        ));
+        )
+        .unwrap()
+        .is_match(&output));
     }
 
     #[test]
@@ -1125,7 +1271,7 @@ impl<'a> Alignment<'a> { │
         let output = integration_test_utils::run_delta(example_diff, &config);
         let line = output.lines().nth(6).unwrap();
         if base_style_has_background_color {
-            let style = style::Style::from_str(base_style, None, None, true, false);
+            let style = style::Style::from_str(base_style, None, None, true, false).unwrap();
             assert_eq!(
                 line,
                 &style
@@ -1134,7 +1280,8 @@ impl<'a> Alignment<'a> { │
                     .to_string()
             );
         } else {
-            let style = style::Style::from_str(empty_line_marker_style, None, None, true, false);
+            let style =
+                style::Style::from_str(empty_line_marker_style, None, None, true, false).unwrap();
             assert_eq!(
                 line,
                 &style
@@ -1197,6 +1344,492 @@ impl<'a> Alignment<'a> { │
         assert_eq!(output, input);
     }
 
+    #[test]
+    fn test_color_never() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--color",
+            "never",
+            "--commit-decoration-style",
+            "bold yellow box",
+            "--line-numbers",
+        ]);
+        let output = integration_test_utils::run_delta(
+            GIT_DIFF_SINGLE_HUNK_WITH_ANSI_ESCAPE_SEQUENCES,
+            &config,
+        );
+        assert!(!output.contains('\x1b'));
+        let mut lines = output.lines().skip(1);
+        assert_eq!(
+            lines.next().unwrap(),
+            "commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e ┃"
+        );
+        assert!(output.contains("\n 74 ⋮    │                let (left, diag, up) =[- (-]\n"));
+        assert!(output.contains("\n    ⋮ 74 │                let (left, diag, up) =\n"));
+    }
+
+    #[test]
+    fn test_color_never_with_hyperlinks() {
+        let mut config =
+            integration_test_utils::make_config_from_args(&["--color", "never", "--hyperlinks"]);
+        config.git_config_entries.insert(
+            "delta.__workdir__".to_string(),
+            GitConfigEntry::Path(std::path::PathBuf::from("/repo")),
+        );
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        assert!(!output.contains('\x1b'));
+        assert!(output.contains("\nsrc/align.rs\n"));
+    }
+
+    #[test]
+    fn test_emph_delimiters() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--color",
+            "never",
+            "--minus-emph-delimiters",
+            "<<..>>",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_DIFF_SINGLE_HUNK, &config);
+        assert!(output.contains("let (left, diag, up) =<< (>>\n"));
+
+        let config = integration_test_utils::make_config_from_args(&[
+            "--color",
+            "never",
+            "--plus-emph-delimiters",
+            "++..++",
+            "--side-by-side",
+            "--width",
+            "40",
+        ]);
+        let output = integration_test_utils::run_delta(
+            "\
+--- a/a.py
++++ b/a.py
+@@ -1 +1 @@
+-x = 1
++x = 2
+",
+            &config,
+        );
+        assert!(output.contains("│ 1  │x = [-1-]     │ 1  │x = ++2++\n"));
+    }
+
+    #[test]
+    fn test_exclude_hides_matching_files() {
+        let config = integration_test_utils::make_config_from_args(&["--exclude", "*.lock"]);
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_LOCKFILE_CHANGES, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(!output.contains("Cargo.lock"));
+        assert!(!output.contains("checksum"));
+        assert!(!output.contains("hidden"));
+        assert!(test_utils::contains_once(&output, "\nsrc/main.rs\n"));
+        assert!(test_utils::contains_once(&output, "\nREADME.md\n"));
+    }
+
+    #[test]
+    fn test_include_shows_only_matching_files() {
+        let config = integration_test_utils::make_config_from_args(&["--include", "src/**"]);
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_LOCKFILE_CHANGES, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(!output.contains("Cargo.lock"));
+        assert!(!output.contains("README.md"));
+        assert!(test_utils::contains_once(&output, "\nsrc/main.rs\n"));
+        // Commit lines are never hidden.
+        assert!(output.contains("commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e"));
+        assert!(output.contains("commit 1e5d2e7d8fb1a0ab6a4e4a6e5e7e0ea2e2f5b1c6"));
+    }
+
+    #[test]
+    fn test_summarize_hidden_files() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--exclude",
+            "*.lock *.md",
+            "--summarize-hidden-files",
+        ]);
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_LOCKFILE_CHANGES, &config);
+        let output = strip_ansi_codes(&output);
+        let lines: Vec<&str> = output.lines().collect();
+        // The summary for each commit precedes the next commit, or ends the output.
+        let first_summary = lines.iter().position(|l| *l == "1 file hidden").unwrap();
+        let second_commit = lines
+            .iter()
+            .position(|l| l.starts_with("commit 1e5d2e7"))
+            .unwrap();
+        assert!(first_summary < second_commit);
+        assert_eq!(lines.iter().filter(|l| **l == "1 file hidden").count(), 2);
+        let last_summary = lines.iter().rposition(|l| *l == "1 file hidden").unwrap();
+        assert!(last_summary > second_commit);
+    }
+
+    #[test]
+    fn test_generated_file_is_collapsed() {
+        let workdir = make_repo_with_gitattributes(
+            "generated_file_is_collapsed",
+            "Cargo.lock linguist-generated\n",
+        );
+        let mut config = integration_test_utils::make_config_from_args(&[]);
+        config.git_attributes = GitAttributes::try_create(&workdir);
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_LOCKFILE_CHANGES, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(test_utils::contains_once(
+            &output,
+            "\nCargo.lock (generated file: 2 lines changed, collapsed)\n"
+        ));
+        assert!(!output.contains("checksum"));
+        assert!(test_utils::contains_once(&output, "\nsrc/main.rs\n"));
+
+        let mut config =
+            integration_test_utils::make_config_from_args(&["--expand-generated-files"]);
+        config.git_attributes = GitAttributes::try_create(&workdir);
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_LOCKFILE_CHANGES, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(!output.contains("collapsed"));
+        assert!(output.contains("checksum"));
+        remove_dir_all(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_file_not_to_be_diffed_is_collapsed() {
+        let workdir =
+            make_repo_with_gitattributes("file_not_to_be_diffed_is_collapsed", "*.dat -diff\n");
+        let mut config = integration_test_utils::make_config_from_args(&[]);
+        config.git_attributes = GitAttributes::try_create(&workdir);
+        let output = integration_test_utils::run_delta(DIFF_WITH_FILE_NOT_TO_BE_DIFFED, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(test_utils::contains_once(
+            &output,
+            "data.dat (generated file: 0 lines changed, collapsed)\n"
+        ));
+        assert!(!output.contains("Binary files"));
+        assert!(test_utils::contains_once(&output, "\nmain.rs\n"));
+
+        let mut config =
+            integration_test_utils::make_config_from_args(&["--expand-generated-files"]);
+        config.git_attributes = GitAttributes::try_create(&workdir);
+        let output = integration_test_utils::run_delta(DIFF_WITH_FILE_NOT_TO_BE_DIFFED, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(!output.contains("collapsed"));
+        assert!(output.contains("Binary files a/data.dat and b/data.dat differ\n"));
+        remove_dir_all(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let mut config = integration_test_utils::make_config_from_args(&["--relative-paths"]);
+        config.cwd_relative_to_repo_root = Some("src".to_string());
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_LOCKFILE_CHANGES, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(test_utils::contains_once(&output, "\nmain.rs\n"));
+        assert!(test_utils::contains_once(&output, "\n../README.md\n"));
+    }
+
+    #[test]
+    fn test_diff_noprefix_git_config() {
+        let git_config_contents = b"
+[diff]
+    noprefix = true
+";
+        let git_config_path = "delta__test_diff_noprefix_git_config.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        // With diff.noprefix, "a/" is a directory name, not a prefix to be stripped.
+        let input = ADDED_FILE_INPUT.replace("b/a.py", "a/x.py");
+        let output = integration_test_utils::run_delta(&input, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(test_utils::contains_once(&output, "\nadded: a/x.py\n"));
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_syntax_map() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--syntax-map",
+            "a=py",
+            "--plus-style",
+            "syntax",
+        ]);
+        let input = ADDED_FILE_INPUT.replace("a.py", "a");
+        let output = integration_test_utils::run_delta(&input, &config);
+        ansi_test_utils::assert_line_is_syntax_highlighted(
+            &output,
+            12,
+            "class X:",
+            "py",
+            State::HunkPlus(None),
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_syntax_from_diff_driver_gitattribute() {
+        let workdir = make_repo_with_gitattributes("syntax_from_diff_driver", "a diff=python\n");
+        let mut config = integration_test_utils::make_config_from_args(&["--plus-style", "syntax"]);
+        config.git_attributes = GitAttributes::try_create(&workdir);
+        let input = ADDED_FILE_INPUT.replace("a.py", "a");
+        let output = integration_test_utils::run_delta(&input, &config);
+        ansi_test_utils::assert_line_is_syntax_highlighted(
+            &output,
+            12,
+            "class X:",
+            "py",
+            State::HunkPlus(None),
+            &config,
+        );
+        remove_dir_all(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_syntax_from_shebang() {
+        let config = integration_test_utils::make_config_from_args(&["--plus-style", "syntax"]);
+        let output = integration_test_utils::run_delta(ADDED_FILE_WITH_SHEBANG, &config);
+        ansi_test_utils::assert_line_is_syntax_highlighted(
+            &output,
+            6,
+            "class X:",
+            "py",
+            State::HunkPlus(None),
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_syntax_from_modeline() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--plus-style",
+            "syntax",
+            "--plus-emph-style",
+            "syntax",
+        ]);
+        let output = integration_test_utils::run_delta(MODIFIED_FILE_WITH_MODELINE, &config);
+        // The added line precedes the modeline, but is not painted until the modeline is seen.
+        ansi_test_utils::assert_line_is_syntax_highlighted(
+            &output,
+            6,
+            "class X:",
+            "py",
+            State::HunkPlus(None),
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_syntax_from_modeline_after_unchanged_lines() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output = integration_test_utils::run_delta(
+            MODIFIED_FILE_WITH_MODELINE_AFTER_UNCHANGED_LINES,
+            &config,
+        );
+        // The unchanged line precedes the modeline, but is highlighted using the syntax it
+        // specifies.
+        ansi_test_utils::assert_line_is_syntax_highlighted(
+            &output,
+            5,
+            "class W:",
+            "py",
+            State::HunkZero,
+            &config,
+        );
+    }
+
+    #[test]
+    fn test_long_lines_are_truncated() {
+        let input = format!(
+            "\
+--- a/a.js
++++ b/a.js
+@@ -1,2 +1,2 @@
+ {}
+-{}
++{}
+",
+            "x".repeat(20),
+            "var a=1;".repeat(5),
+            "var b=2;".repeat(5),
+        );
+        let config = integration_test_utils::make_config_from_args(&[
+            "--max-line-length",
+            "10",
+            "--file-style",
+            "omit",
+            "--hunk-header-style",
+            "omit",
+        ]);
+        let output = integration_test_utils::run_delta(&input, &config);
+        let output = strip_ansi_codes(&output);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "xxxxxxxxxx… (10 more chars)");
+        assert_eq!(lines[1], "var a=1;va… (30 more chars)");
+        assert_eq!(lines[2], "var b=2;va… (30 more chars)");
+
+        // The marker is not syntax highlighted: it has the line's background color only.
+        let config = integration_test_utils::make_config_from_args(&["--max-line-length", "10"]);
+        let output = integration_test_utils::run_delta(&input, &config);
+        let line = output
+            .lines()
+            .find(|line| strip_ansi_codes(line).starts_with("var b=2;"))
+            .unwrap();
+        let marker_start = line.find("… (30 more chars)").unwrap();
+        let marker_style =
+            ansi::parse::parse_first_style(&line[line[..marker_start].rfind('\x1b').unwrap()..])
+                .unwrap();
+        assert_eq!(marker_style.foreground, None);
+        assert_eq!(
+            marker_style.background,
+            config.plus_style.ansi_term_style.background
+        );
+
+        // Lines are displayed in full with --max-line-length=0, and with --color-only, which must
+        // not alter the text of the lines.
+        for args in &[&["--max-line-length", "0"][..], &["--color-only"][..]] {
+            let config = integration_test_utils::make_config_from_args(args);
+            let output = integration_test_utils::run_delta(&input, &config);
+            assert!(strip_ansi_codes(&output).contains(&"var b=2;".repeat(5)));
+        }
+    }
+
+    #[test]
+    fn test_complete_hunks_are_written_while_input_is_idle() {
+        // A reader of the input sent to it, which blocks until more is sent.
+        struct StreamReader {
+            inputs: mpsc::Receiver<&'static str>,
+            input: &'static [u8],
+        }
+
+        impl Read for StreamReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.input.is_empty() {
+                    match self.inputs.recv() {
+                        Ok(input) => self.input = input.as_bytes(),
+                        Err(_) => return Ok(0),
+                    }
+                }
+                self.input.read(buf)
+            }
+        }
+
+        /// Records the output written to it as of each flush: delta flushes its output when the
+        /// input is idle.
+        #[derive(Clone, Default)]
+        struct SharedWriter(Arc<Mutex<(Vec<u8>, Vec<String>)>>);
+
+        impl SharedWriter {
+            fn output(&self) -> String {
+                strip_ansi_codes(&String::from_utf8_lossy(&self.0.lock().unwrap().0)).to_string()
+            }
+
+            fn flushed_outputs(&self) -> Vec<String> {
+                self.0.lock().unwrap().1.clone()
+            }
+
+            /// Wait for the output as of the first flush after the `n`th, which must be written
+            /// within 10 seconds.
+            fn wait_for_flush(&self, n: usize) -> String {
+                let start = Instant::now();
+                loop {
+                    if let Some(output) = self.flushed_outputs().get(n) {
+                        return output.clone();
+                    }
+                    assert!(start.elapsed() < Duration::from_secs(10));
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+
+            /// Wait for a flush whose output contains `text`, and return the number of flushes.
+            fn wait_for_flushed_text(&self, text: &str) -> usize {
+                let start = Instant::now();
+                loop {
+                    let outputs = self.flushed_outputs();
+                    if outputs.iter().any(|output| output.contains(text)) {
+                        return outputs.len();
+                    }
+                    assert!(start.elapsed() < Duration::from_secs(10));
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+        }
+
+        impl Write for SharedWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().0.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                let output = self.output();
+                self.0.lock().unwrap().1.push(output);
+                Ok(())
+            }
+        }
+
+        for workers in &["1", "4"] {
+            let mut config = integration_test_utils::make_config_from_args(&[
+                "--workers",
+                workers,
+                "--color",
+                "never",
+            ]);
+            config.idle_flush_timeout = Duration::from_millis(1);
+            let (input_sender, input_receiver) = mpsc::channel();
+            let writer = SharedWriter::default();
+            let mut delta_writer = writer.clone();
+            let delta_thread = thread::spawn(move || {
+                let reader = StreamReader {
+                    inputs: input_receiver,
+                    input: b"",
+                };
+                delta(
+                    ByteLines::new(BufReader::new(reader)),
+                    &mut delta_writer,
+                    &config,
+                )
+                .unwrap();
+            });
+
+            input_sender
+                .send(
+                    "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ fn main() {
+-    let a = 1;
++    let a = 2;
+",
+                )
+                .unwrap();
+            writer.wait_for_flushed_text("    let a = [-1-];\n    let a = {+2+};\n");
+
+            input_sender
+                .send(
+                    "\
+diff --git a/b.rs b/b.rs
+index 1111111..2222222 100644
+--- a/b.rs
++++ b/b.rs
+@@ -1,2 +1,2 @@
+ fn b() {
+",
+                )
+                .unwrap();
+            // Once the last line sent has been flushed, delta waits for more input: the next flush
+            // follows the next line sent.
+            let n_flushes = writer.wait_for_flushed_text("fn b() {");
+            input_sender.send("-    let b = 1;\n").unwrap();
+            // The hunk is incomplete, so the removed line may yet be paired with an added line.
+            assert!(!writer.wait_for_flush(n_flushes).contains("let b"));
+
+            input_sender.send("+    let b = 2;\n").unwrap();
+            drop(input_sender);
+            delta_thread.join().unwrap();
+            assert!(writer
+                .output()
+                .contains("    let b = [-1-];\n    let b = {+2+};\n"));
+        }
+    }
+
     const GIT_DIFF_SINGLE_HUNK: &str = "\
 commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
 Author: Dan Davison <dandavison7@gmail.com>
@@ -1512,6 +2145,19 @@ diff --git a/foo b/foo
 new file mode 100644
 index 0000000..b572921
 Binary files /dev/null and b/foo differ
+";
+
+    const DIFF_WITH_FILE_NOT_TO_BE_DIFFED: &str = "\
+diff --git a/data.dat b/data.dat
+index 422c2b7..0f7bc76 100644
+Binary files a/data.dat and b/data.dat differ
+diff --git a/main.rs b/main.rs
+index 587be6b..975fbec 100644
+--- a/main.rs
++++ b/main.rs
+@@ -1 +1 @@
+-x
++y
 ";
 
     // git --no-pager show -p --cc --format=  --numstat --stat
@@ -1728,4 +2374,81 @@ index 386f291a..22666f79 100644
 @@ -5,0 +7 @@ int main() {
 +    return 0;
 "#;
+
+    const GIT_LOG_WITH_LOCKFILE_CHANGES: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Sat Oct 3 12:30:00 2020 -0400
+
+    Bump dependencies
+
+diff --git a/Cargo.lock b/Cargo.lock
+index 3e3fd5d..b5f2a6e 100644
+--- a/Cargo.lock
++++ b/Cargo.lock
+@@ -1,3 +1,3 @@
+ [[package]]
+ name = \"adler\"
+-checksum = \"aaaa\"
++checksum = \"bbbb\"
+diff --git a/src/main.rs b/src/main.rs
+index 2f1fb53..b1e7a3a 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"Hello\");
++    println!(\"Hello, world\");
+ }
+
+commit 1e5d2e7d8fb1a0ab6a4e4a6e5e7e0ea2e2f5b1c6
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Fri Oct 2 12:30:00 2020 -0400
+
+    Update README
+
+diff --git a/README.md b/README.md
+index 9d8e4a1..f3a6e2b 100644
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-# delta
++# Delta
+";
+
+    const ADDED_FILE_WITH_SHEBANG: &str = "\
+diff --git a/bin/run b/bin/run
+new file mode 100755
+index 0000000..8c55b7d
+--- /dev/null
++++ b/bin/run
+@@ -0,0 +1,3 @@
++#!/usr/bin/env python
++class X:
++    pass
+";
+
+    const MODIFIED_FILE_WITH_MODELINE: &str = "\
+diff --git a/etc/run b/etc/run
+index 8c55b7d..5d1ac0e 100644
+--- a/etc/run
++++ b/etc/run
+@@ -10,2 +10,2 @@
+-class Y:
++class X:
+ # vim: set ft=python :
+";
+
+    const MODIFIED_FILE_WITH_MODELINE_AFTER_UNCHANGED_LINES: &str = "\
+diff --git a/etc/run b/etc/run
+index 8c55b7d..5d1ac0e 100644
+--- a/etc/run
++++ b/etc/run
+@@ -8,4 +8,4 @@
+ class W:
+     pass
+-class Y:
++class X:
+ # vim: set ft=python :
+";
 }